@@ -0,0 +1,34 @@
+#![feature(test)]
+
+extern crate las;
+extern crate test;
+
+use las::Reader;
+use std::fs::File;
+use std::io::BufReader;
+use test::Bencher;
+
+const PATH: &'static str = "tests/data/autzen.las";
+
+fn read_all<R: std::io::Read + std::io::Seek>(mut reader: Reader<R>) {
+    for point in reader.points() {
+        let _ = point.unwrap();
+    }
+}
+
+#[bench]
+fn unbuffered(bencher: &mut Bencher) {
+    bencher.iter(|| read_all(Reader::new(File::open(PATH).unwrap()).unwrap()));
+}
+
+#[bench]
+fn buffered(bencher: &mut Bencher) {
+    bencher.iter(|| {
+        read_all(Reader::new(BufReader::new(File::open(PATH).unwrap())).unwrap())
+    });
+}
+
+#[bench]
+fn buffered_1_mib(bencher: &mut Bencher) {
+    bencher.iter(|| read_all(Reader::with_capacity(PATH, 1 << 20).unwrap()));
+}
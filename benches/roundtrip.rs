@@ -17,6 +17,13 @@ fn roundtrip(npoints: usize) {
     }
 }
 
+fn write_ref(points: &[Point]) {
+    let mut writer = Writer::default();
+    for point in points {
+        writer.write_ref(point).unwrap();
+    }
+}
+
 #[bench]
 fn roundtrip_0(bencher: &mut Bencher) {
     bencher.iter(|| roundtrip(0));
@@ -36,3 +43,9 @@ fn roundtrip_100(bencher: &mut Bencher) {
 fn roundtrip_10000(bencher: &mut Bencher) {
     bencher.iter(|| roundtrip(10000));
 }
+
+#[bench]
+fn write_ref_10000(bencher: &mut Bencher) {
+    let points = vec![Point::default(); 10000];
+    bencher.iter(|| write_ref(&points));
+}
@@ -0,0 +1,419 @@
+//! Read las points without blocking, using tokio.
+//!
+//! This module is only available with the `tokio` feature. An `AsyncReader` reads points
+//! sequentially from anything that implements `tokio::io::AsyncRead` and
+//! `tokio::io::AsyncSeek`. The header, vlr, evlr, and point parsing is shared with the blocking
+//! `Reader`; only the io is different.
+//!
+//! The points are read sequentially, so there's no random access. Any evlrs, which are stored
+//! after the points, are read when the reader is created by seeking past the points and back.
+//!
+//! ```
+//! # extern crate las;
+//! # extern crate tokio;
+//! # fn main() {
+//! use std::future::Future;
+//! use std::io::Cursor;
+//! use std::pin::Pin;
+//! use std::task::{Context, Poll, Waker};
+//! use las::AsyncReader;
+//!
+//! // You'd usually use a runtime, e.g. `tokio::runtime::Runtime::block_on`, instead of this
+//! // simple poll loop.
+//! fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+//!     let mut context = Context::from_waker(Waker::noop());
+//!     loop {
+//!         if let Poll::Ready(output) = Pin::new(&mut future).poll(&mut context) {
+//!             return output;
+//!         }
+//!     }
+//! }
+//!
+//! let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+//! let mut reader = block_on(AsyncReader::new(Cursor::new(bytes))).unwrap();
+//! while let Some(point) = block_on(reader.read()).unwrap() {
+//!     println!("({}, {}, {})", point.x, point.y, point.z);
+//! }
+//! # }
+//! ```
+
+use {Header, Phase, Point, Result, raw};
+use error::WithPhase;
+use reader::{Error, Options, Preamble};
+use std::future::Future;
+use std::io::{self, Cursor, SeekFrom};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// The smallest possible las header, from las 1.0 through 1.2.
+const MINIMUM_HEADER_SIZE: usize = 227;
+
+/// Reads las points from an `AsyncRead` and `AsyncSeek`.
+#[derive(Debug)]
+pub struct AsyncReader<R: AsyncRead + AsyncSeek + Unpin> {
+    buffer: Vec<u8>,
+    filled: usize,
+    header: Header,
+    number_read: u64,
+    offset_to_point_data: u64,
+    read: R,
+}
+
+/// A future that resolves to a new `AsyncReader`.
+///
+/// This struct is created by `AsyncReader::new`.
+#[derive(Debug)]
+pub struct New<R: AsyncRead + AsyncSeek + Unpin> {
+    buffer: Vec<u8>,
+    filled: usize,
+    preamble: Option<Preamble>,
+    read: Option<R>,
+    state: State,
+}
+
+/// Where a `New` is in reading the las data before the points.
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Header,
+    SeekToEvlrs { started: bool },
+    Evlrs,
+    SeekToPoints { started: bool },
+}
+
+/// A future that resolves to the next point, or `None` if all points have been read.
+///
+/// This struct is created by `AsyncReader::read`.
+#[derive(Debug)]
+pub struct ReadPoint<'a, R: 'a + AsyncRead + AsyncSeek + Unpin> {
+    reader: &'a mut AsyncReader<R>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncReader<R> {
+    /// Returns a future that reads the header, vlrs, and evlrs and resolves to a new reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::AsyncReader;
+    /// let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    /// let future = AsyncReader::new(Cursor::new(bytes));
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(read: R) -> New<R> {
+        New {
+            buffer: vec![0; MINIMUM_HEADER_SIZE],
+            filled: 0,
+            preamble: None,
+            read: Some(read),
+            state: State::Header,
+        }
+    }
+
+    /// Returns a reference to this reader's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::io::Cursor;
+    /// use std::pin::Pin;
+    /// use std::task::{Context, Poll, Waker};
+    /// use las::AsyncReader;
+    ///
+    /// let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    /// let mut future = AsyncReader::new(Cursor::new(bytes));
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// if let Poll::Ready(reader) = Pin::new(&mut future).poll(&mut context) {
+    ///     assert_eq!(106, reader.unwrap().header().number_of_points());
+    /// }
+    /// ```
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns a future that reads the next point.
+    ///
+    /// The future resolves to `Ok(None)` if we have already read the last point. If the future is
+    /// dropped before it resolves, any partially-read point is kept and the next call picks up
+    /// where it left off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::io::Cursor;
+    /// use std::pin::Pin;
+    /// use std::task::{Context, Poll, Waker};
+    /// use las::AsyncReader;
+    ///
+    /// let bytes = std::fs::read("tests/data/autzen.las").unwrap();
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// let mut future = AsyncReader::new(Cursor::new(bytes));
+    /// if let Poll::Ready(reader) = Pin::new(&mut future).poll(&mut context) {
+    ///     let mut reader = reader.unwrap();
+    ///     if let Poll::Ready(point) = Pin::new(&mut reader.read()).poll(&mut context) {
+    ///         let point = point.unwrap().unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn read(&mut self) -> ReadPoint<'_, R> {
+        ReadPoint { reader: self }
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> Future for New<R> {
+    type Output = Result<AsyncReader<R>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<AsyncReader<R>>> {
+        let new = self.get_mut();
+        loop {
+            let read = new.read.as_mut().expect("New polled after completion");
+            match new.state {
+                State::Header => {
+                    match poll_fill(read, cx, &mut new.buffer, &mut new.filled) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => {
+                            return Poll::Ready(Err(::Error::IoDuring(Phase::ReadingHeader, err)))
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    if new.buffer.len() == MINIMUM_HEADER_SIZE {
+                        let offset_to_point_data = offset_to_point_data(&new.buffer) as usize;
+                        if offset_to_point_data < MINIMUM_HEADER_SIZE {
+                            return Poll::Ready(Err(
+                                Error::OffsetToPointDataTooSmall(offset_to_point_data as u32)
+                                    .into(),
+                            ));
+                        } else if offset_to_point_data > MINIMUM_HEADER_SIZE {
+                            new.buffer.resize(offset_to_point_data, 0);
+                            continue;
+                        }
+                    }
+                    let preamble =
+                        match Preamble::read_from(Cursor::new(&new.buffer), Options::default()) {
+                            Ok(preamble) => preamble,
+                            Err(err) => return Poll::Ready(Err(err)),
+                        };
+                    new.state = if preamble.has_evlrs() {
+                        State::SeekToEvlrs { started: false }
+                    } else {
+                        // The buffer ends at the start of the point data, so there's no need to
+                        // seek there.
+                        State::SeekToPoints { started: true }
+                    };
+                    new.preamble = Some(preamble);
+                }
+                State::SeekToEvlrs { ref mut started } => {
+                    let position = new.preamble.as_ref().expect("preamble").offset_to_end_of_points;
+                    match poll_seek(read, cx, position, started) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => return Poll::Ready(Err(err.into())),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    new.buffer.clear();
+                    new.state = State::Evlrs;
+                }
+                State::Evlrs => {
+                    match poll_read_to_end(read, cx, &mut new.buffer) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => {
+                            return Poll::Ready(Err(::Error::IoDuring(Phase::ReadingEvlr, err)))
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    let preamble = new.preamble.as_mut().expect("preamble");
+                    if let Err(err) = preamble.read_evlrs_from(Cursor::new(&new.buffer)) {
+                        return Poll::Ready(Err(err));
+                    }
+                    new.state = State::SeekToPoints { started: false };
+                }
+                State::SeekToPoints { ref mut started } => {
+                    let position = new.preamble.as_ref().expect("preamble").offset_to_point_data;
+                    match poll_seek(read, cx, position, started) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => return Poll::Ready(Err(err.into())),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    let preamble = new.preamble.take().expect("preamble");
+                    let offset_to_point_data = preamble.offset_to_point_data;
+                    let header = match preamble.into_header() {
+                        Ok((header, _)) => header,
+                        Err(err) => return Poll::Ready(Err(err)),
+                    };
+                    return Poll::Ready(Ok(AsyncReader {
+                        buffer: vec![0; header.point_format().len() as usize],
+                        filled: 0,
+                        header: header,
+                        number_read: 0,
+                        offset_to_point_data: offset_to_point_data,
+                        read: new.read.take().expect("New polled after completion"),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, R: AsyncRead + AsyncSeek + Unpin> Future for ReadPoint<'a, R> {
+    type Output = Result<Option<Point>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<Option<Point>>> {
+        let reader = &mut *self.get_mut().reader;
+        if reader.number_read >= reader.header.number_of_points() {
+            return Poll::Ready(Ok(None));
+        }
+        let phase = Phase::ReadingPoint {
+            index: reader.number_read,
+            offset: reader.offset_to_point_data +
+                reader.number_read * reader.buffer.len() as u64,
+        };
+        match poll_fill(
+            &mut reader.read,
+            cx,
+            &mut reader.buffer,
+            &mut reader.filled,
+        ) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(::Error::IoDuring(phase, err))),
+            Poll::Pending => return Poll::Pending,
+        }
+        reader.filled = 0;
+        reader.number_read += 1;
+        let point = raw::Point::read_from(&reader.buffer[..], reader.header.point_format())
+            .with_phase(phase)
+            .map(|raw_point| Some(Point::new(raw_point, reader.header.transforms())));
+        Poll::Ready(point)
+    }
+}
+
+fn poll_fill<R: AsyncRead + Unpin>(
+    read: &mut R,
+    cx: &mut Context,
+    buffer: &mut [u8],
+    filled: &mut usize,
+) -> Poll<io::Result<()>> {
+    while *filled < buffer.len() {
+        let mut read_buf = ReadBuf::new(&mut buffer[*filled..]);
+        match Pin::new(&mut *read).poll_read(cx, &mut read_buf) {
+            Poll::Ready(Ok(())) => {
+                let n = read_buf.filled().len();
+                if n == 0 {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "unexpected end of las data",
+                    )));
+                }
+                *filled += n;
+            }
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+    Poll::Ready(Ok(()))
+}
+
+fn poll_read_to_end<R: AsyncRead + Unpin>(
+    read: &mut R,
+    cx: &mut Context,
+    buffer: &mut Vec<u8>,
+) -> Poll<io::Result<()>> {
+    let mut chunk = [0; 8192];
+    loop {
+        let mut read_buf = ReadBuf::new(&mut chunk);
+        match Pin::new(&mut *read).poll_read(cx, &mut read_buf) {
+            Poll::Ready(Ok(())) => {
+                if read_buf.filled().is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                buffer.extend_from_slice(read_buf.filled());
+            }
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+}
+
+/// Seeks to `position`, unless `started` says the seek has already been started.
+fn poll_seek<R: AsyncSeek + Unpin>(
+    read: &mut R,
+    cx: &mut Context,
+    position: u64,
+    started: &mut bool,
+) -> Poll<io::Result<()>> {
+    if !*started {
+        Pin::new(&mut *read).start_seek(SeekFrom::Start(position))?;
+        *started = true;
+    }
+    Pin::new(&mut *read).poll_complete(cx).map(|result| result.map(|_| ()))
+}
+
+fn offset_to_point_data(bytes: &[u8]) -> u32 {
+    use byteorder::{ByteOrder, LittleEndian};
+    LittleEndian::read_u32(&bytes[96..100])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Reader, Writer};
+    use std::task::Waker;
+
+    fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = Pin::new(&mut future).poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn autzen() {
+        let bytes = ::std::fs::read("tests/data/autzen.las").unwrap();
+        let mut async_reader = block_on(AsyncReader::new(Cursor::new(bytes))).unwrap();
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert_eq!(reader.header(), async_reader.header());
+        for point in reader.points() {
+            assert_eq!(point.unwrap(), block_on(async_reader.read()).unwrap().unwrap());
+        }
+        assert!(block_on(async_reader.read()).unwrap().is_none());
+    }
+
+    #[test]
+    fn evlrs() {
+        use {Builder, Vlr};
+
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: "gadomski".to_string(),
+            data: vec![42; 8],
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+        let reader = Reader::new(Cursor::new(bytes.clone())).unwrap();
+        let mut async_reader = block_on(AsyncReader::new(Cursor::new(bytes))).unwrap();
+        assert_eq!(reader.header(), async_reader.header());
+        assert_eq!(1, async_reader.header().evlrs().len());
+        assert!(block_on(async_reader.read()).unwrap().is_some());
+        assert!(block_on(async_reader.read()).unwrap().is_none());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        let mut bytes = writer.into_inner().unwrap().into_inner();
+        let len = bytes.len();
+        bytes.truncate(len - 1);
+        let mut async_reader = block_on(AsyncReader::new(Cursor::new(bytes))).unwrap();
+        match block_on(async_reader.read()) {
+            Err(::Error::IoDuring(Phase::ReadingPoint { index, .. }, _)) => assert_eq!(0, index),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Buffer points and write them sorted.
+
+use {Header, Point, Result, Transform, Vector, Writer, raw};
+use point::Format;
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Seek, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use writer::Error;
+
+/// The default number of points that are held in memory before spilling to disk.
+pub const DEFAULT_THRESHOLD: usize = 1_000_000;
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Accumulates points in any order, and writes them sorted by a key when closed.
+///
+/// Points are held in memory until there are `threshold` of them. Then they are sorted and
+/// spilled to a temporary file in `std::env::temp_dir()`, and buffering starts over. On close, the
+/// in-memory points and all spilled runs are merged in sorted order and written to the underlying
+/// `Writer`. This makes it possible to sort datasets that are larger than memory. The sort is
+/// stable: points with equal keys are written in the order they were given to the writer.
+///
+/// **Disk usage**: every spilled point takes up its point record length (e.g. 28 bytes for
+/// format 1) in the temporary directory, so sorting a dataset that never fits in memory needs
+/// about as much temporary space as the dataset's point data. The temporary files are removed on
+/// close, or when the `BufferingWriter` is dropped.
+///
+/// Points are only written on close. If a `BufferingWriter` is dropped without being closed, its
+/// points are discarded.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::{BufferingWriter, Point, Reader};
+///
+/// let mut writer = BufferingWriter::new(Cursor::new(Vec::new()), Default::default(), |point| {
+///     (point.x * 1000.) as i64
+/// }).unwrap();
+/// writer.write(Point { x: 2., ..Default::default() }).unwrap();
+/// writer.write(Point { x: 1., ..Default::default() }).unwrap();
+/// let cursor = writer.close().unwrap().into_inner().unwrap();
+/// let mut reader = Reader::new(cursor).unwrap();
+/// assert_eq!(1., reader.read().unwrap().unwrap().x);
+/// ```
+pub struct BufferingWriter<W: Write + Seek, K: Ord, F: FnMut(&Point) -> K> {
+    key: F,
+    points: Vec<Point>,
+    runs: Vec<Run>,
+    threshold: usize,
+    writer: Writer<W>,
+}
+
+struct Run {
+    path: PathBuf,
+}
+
+struct RunReader {
+    number_of_points: u64,
+    number_read: u64,
+    read: BufReader<File>,
+}
+
+impl<W: Write + Seek, K: Ord, F: FnMut(&Point) -> K> BufferingWriter<W, K, F> {
+    /// Creates a new buffering writer that spills to disk every
+    /// `las::buffering_writer::DEFAULT_THRESHOLD` points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::BufferingWriter;
+    /// let writer = BufferingWriter::new(Cursor::new(Vec::new()), Default::default(), |point| {
+    ///     point.point_source_id
+    /// });
+    /// ```
+    pub fn new(write: W, header: Header, key: F) -> Result<BufferingWriter<W, K, F>> {
+        BufferingWriter::with_threshold(write, header, DEFAULT_THRESHOLD, key)
+    }
+
+    /// Creates a new buffering writer that spills to disk every `threshold` points.
+    ///
+    /// A threshold of zero is treated as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::BufferingWriter;
+    /// let writer = BufferingWriter::with_threshold(
+    ///     Cursor::new(Vec::new()),
+    ///     Default::default(),
+    ///     10_000,
+    ///     |point| point.point_source_id,
+    /// );
+    /// ```
+    pub fn with_threshold(
+        write: W,
+        header: Header,
+        threshold: usize,
+        key: F,
+    ) -> Result<BufferingWriter<W, K, F>> {
+        Ok(BufferingWriter {
+            key: key,
+            points: Vec::new(),
+            runs: Vec::new(),
+            threshold: if threshold == 0 { 1 } else { threshold },
+            writer: Writer::new(write, header)?,
+        })
+    }
+
+    /// Returns a reference to this writer's header.
+    ///
+    /// The point counts and bounds are only updated when the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::BufferingWriter;
+    /// let writer = BufferingWriter::new(Cursor::new(Vec::new()), Default::default(), |point| {
+    ///     point.point_source_id
+    /// }).unwrap();
+    /// let header = writer.header();
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.writer.header()
+    }
+
+    /// Buffers a point.
+    ///
+    /// The point is checked against the header's point format right away, so a mismatch is
+    /// reported here instead of when the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::BufferingWriter;
+    /// let mut writer = BufferingWriter::new(Cursor::new(Vec::new()), Default::default(), |point| {
+    ///     point.point_source_id
+    /// }).unwrap();
+    /// writer.write(Default::default()).unwrap();
+    /// ```
+    pub fn write(&mut self, point: Point) -> Result<()> {
+        let format = self.writer.header().point_format();
+        if !point.matches(format) {
+            return Err(Error::PointAttributes(format, point).into());
+        }
+        self.points.push(point);
+        if self.points.len() >= self.threshold {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Sorts and writes all points, closes the underlying writer, and returns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::BufferingWriter;
+    /// let mut writer = BufferingWriter::new(Cursor::new(Vec::new()), Default::default(), |point| {
+    ///     point.point_source_id
+    /// }).unwrap();
+    /// writer.write(Default::default()).unwrap();
+    /// let writer = writer.close().unwrap();
+    /// assert_eq!(1, writer.header().number_of_points());
+    /// ```
+    pub fn close(mut self) -> Result<Writer<W>> {
+        let format = self.writer.header().point_format();
+        let transforms = self.writer.header().transforms();
+        self.sort_points();
+        {
+            let key = &mut self.key;
+            let writer = &mut self.writer;
+            let mut run_readers = Vec::with_capacity(self.runs.len());
+            for run in &self.runs {
+                run_readers.push(run.reader(format)?);
+            }
+            let mut heads = Vec::with_capacity(run_readers.len());
+            for run_reader in &mut run_readers {
+                let head = run_reader.read(format, transforms)?;
+                heads.push(head.map(|point| (key(&point), point)));
+            }
+            let mut points = self.points.drain(..);
+            let mut memory_head = points.next().map(|point| (key(&point), point));
+            loop {
+                let mut next: Option<usize> = None;
+                for (i, head) in heads.iter().enumerate() {
+                    if let Some((ref head_key, _)) = *head {
+                        let is_smaller = match next {
+                            Some(j) => *head_key < heads[j].as_ref().expect("head is some").0,
+                            None => true,
+                        };
+                        if is_smaller {
+                            next = Some(i);
+                        }
+                    }
+                }
+                let use_memory = match (&memory_head, next) {
+                    (&Some((ref memory_key, _)), Some(j)) => {
+                        // The in-memory points were written after every run, so they lose ties.
+                        *memory_key < heads[j].as_ref().expect("head is some").0
+                    }
+                    (&Some(_), None) => true,
+                    (&None, Some(_)) => false,
+                    (&None, None) => break,
+                };
+                if use_memory {
+                    let (_, point) = memory_head.take().expect("memory head is some");
+                    writer.write(point)?;
+                    memory_head = points.next().map(|point| (key(&point), point));
+                } else {
+                    let i = next.expect("a run has a point");
+                    let (_, point) = heads[i].take().expect("head is some");
+                    writer.write(point)?;
+                    heads[i] = run_readers[i].read(format, transforms)?.map(|point| {
+                        (key(&point), point)
+                    });
+                }
+            }
+        }
+        self.writer.close()?;
+        Ok(self.writer)
+    }
+
+    fn sort_points(&mut self) {
+        let key = &mut self.key;
+        let mut keyed: Vec<(K, Point)> = self.points
+            .drain(..)
+            .map(|point| (key(&point), point))
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        self.points.extend(keyed.into_iter().map(|(_, point)| point));
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        let format = self.writer.header().point_format();
+        let transforms = self.writer.header().transforms();
+        self.sort_points();
+        let run = Run::new()?;
+        {
+            let mut write = BufWriter::new(File::create(&run.path)?);
+            for point in self.points.drain(..) {
+                point.into_raw(transforms).and_then(|raw_point| {
+                    raw_point.write_to(&mut write, format)
+                })?;
+            }
+            write.flush()?;
+        }
+        self.runs.push(run);
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek, K: Ord, F: FnMut(&Point) -> K> fmt::Debug for BufferingWriter<W, K, F>
+where
+    W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferingWriter")
+            .field("points", &self.points.len())
+            .field("runs", &self.runs.len())
+            .field("threshold", &self.threshold)
+            .field("writer", &self.writer)
+            .finish()
+    }
+}
+
+impl Run {
+    fn new() -> Result<Run> {
+        let path = env::temp_dir().join(format!(
+            "las-rs-sort-{}-{}.tmp",
+            process::id(),
+            RUN_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        OpenOptions::new().write(true).create_new(true).open(&path)?;
+        Ok(Run { path: path })
+    }
+
+    fn reader(&self, format: Format) -> Result<RunReader> {
+        let file = File::open(&self.path)?;
+        let len = file.metadata()?.len();
+        Ok(RunReader {
+            number_of_points: len / u64::from(format.len()),
+            number_read: 0,
+            read: BufReader::new(file),
+        })
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl RunReader {
+    fn read(&mut self, format: Format, transforms: Vector<Transform>) -> Result<Option<Point>> {
+        if self.number_read >= self.number_of_points {
+            return Ok(None);
+        }
+        self.number_read += 1;
+        raw::Point::read_from(&mut self.read, format).map(|raw_point| {
+            Some(Point::new(raw_point, transforms))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Reader;
+    use std::io::Cursor;
+
+    #[test]
+    fn spills_and_merges() {
+        let mut writer = BufferingWriter::with_threshold(
+            Cursor::new(Vec::new()),
+            Default::default(),
+            7,
+            |point| point.point_source_id,
+        ).unwrap();
+        for i in 0..50u16 {
+            writer
+                .write(Point {
+                    point_source_id: (i * 37) % 50,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        assert_eq!(7, writer.runs.len());
+        let paths: Vec<_> = writer.runs.iter().map(|run| run.path.clone()).collect();
+        let cursor = writer.close().unwrap().into_inner().unwrap();
+        assert!(paths.iter().all(|path| !path.exists()));
+        let mut reader = Reader::new(cursor).unwrap();
+        let ids = reader
+            .points()
+            .map(|point| point.unwrap().point_source_id)
+            .collect::<Vec<_>>();
+        assert_eq!((0..50).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn stable() {
+        let mut writer = BufferingWriter::with_threshold(
+            Cursor::new(Vec::new()),
+            Default::default(),
+            3,
+            |point| point.point_source_id,
+        ).unwrap();
+        for i in 0..10u16 {
+            writer
+                .write(Point {
+                    intensity: i,
+                    point_source_id: i % 2,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let cursor = writer.close().unwrap().into_inner().unwrap();
+        let mut reader = Reader::new(cursor).unwrap();
+        let intensities = reader
+            .points()
+            .map(|point| point.unwrap().intensity)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 2, 4, 6, 8, 1, 3, 5, 7, 9], intensities);
+    }
+
+    #[test]
+    fn point_attributes() {
+        let mut writer = BufferingWriter::new(
+            Cursor::new(Vec::new()),
+            Default::default(),
+            |point| point.point_source_id,
+        ).unwrap();
+        let point = Point {
+            gps_time: Some(42.),
+            ..Default::default()
+        };
+        assert!(writer.write(point).is_err());
+    }
+}
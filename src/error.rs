@@ -1,4 +1,5 @@
-use {Transform, Version, header, point, reader, vlr, writer};
+use {Result, Transform, Version, extra_bytes, geo_keys, header, laz, point, reader, vlr, writer};
+use std::fmt;
 use std::io;
 use std::str;
 
@@ -6,11 +7,25 @@ quick_error! {
     /// Crate-specific error enum.
     #[derive(Debug)]
     pub enum Error {
+        /// Wrapper around `las::extra_bytes::Error`.
+        ExtraBytes(err: extra_bytes::Error) {
+            from()
+            cause(err)
+            description("extra bytes error")
+            display("extra bytes error: {}", err)
+        }
         /// Feature is not supported by version.
         Feature(version: Version, feature: &'static str) {
             description("feature is not supported by version")
             display("feature {} is not supported by version {}", feature, version)
         }
+        /// Wrapper around `las::geo_keys::Error`.
+        GeoKeys(err: geo_keys::Error) {
+            from()
+            cause(err)
+            description("geo keys error")
+            display("geo keys error: {}", err)
+        }
         /// A wrapper around `las::header::Error`.
         Header(err: header::Error) {
             from()
@@ -18,6 +33,21 @@ quick_error! {
             description("las header error")
             display("header error: {}", err)
         }
+        /// The bin width of a percentile histogram isn't finite and positive.
+        InvalidHistogramWidth(width: f64) {
+            description("the histogram bin width is not finite and positive")
+            display("the histogram bin width is not finite and positive: {}", width)
+        }
+        /// The target precision for picking transforms isn't finite and positive.
+        InvalidPrecision(precision: f64) {
+            description("the target precision is not finite and positive")
+            display("the target precision is not finite and positive: {}", precision)
+        }
+        /// The string is not a version, e.g. "1.4".
+        InvalidVersion(s: String) {
+            description("the string is not a las version")
+            display("the string is not a las version: {}", s)
+        }
         /// The value can't have the inverse transform applied.
         InverseTransform(n: f64, transform: Transform) {
             description("cannot apply inverse transform")
@@ -30,10 +60,28 @@ quick_error! {
             description(err.description())
             display("io error: {}", err)
         }
+        /// An `std::io::Error` that happened during a known phase of reading or writing.
+        IoDuring(phase: Phase, err: io::Error) {
+            cause(err)
+            description(err.description())
+            display("io error while {}: {}", phase, err)
+        }
         /// The las data is laszip compressed.
         Laszip {
             description("the las data is laszip compressed, and laszip compression is not supported by this build")
         }
+        /// Wrapper around `las::laz::Error`.
+        Laz(err: laz::Error) {
+            from()
+            cause(err)
+            description("laz error")
+            display("laz error: {}", err)
+        }
+        /// A point coordinate is NaN or infinite, so it can't be written.
+        NonFiniteCoordinate(axis: char, n: f64) {
+            description("a point coordinate is not finite")
+            display("the {} coordinate is not finite: {}", axis, n)
+        }
         /// This string is not ASCII.
         NotAscii(s: String) {
             description("the string is not an ascii string")
@@ -58,6 +106,11 @@ quick_error! {
             description("reader error")
             display("reader error: {}", err)
         }
+        /// The points could not be reprojected.
+        Reprojection(message: String) {
+            description("reprojection error")
+            display("reprojection error: {}", message)
+        }
         /// This string is too long for the target slice.
         StringTooLong(s: String, len: usize) {
             description("the string is too long for the target slice")
@@ -70,6 +123,12 @@ quick_error! {
             description(err.description())
             display("utf8 error: {}", err)
         }
+        /// Two vlrs with the same user id and record id have different data, and couldn't be
+        /// merged.
+        VlrConflict(user_id: String, record_id: u16) {
+            description("conflicting vlrs")
+            display("conflicting vlrs: user id {}, record id {}", user_id, record_id)
+        }
         /// Wrapper around `las::writer::Error`.
         Writer(err: writer::Error) {
             from()
@@ -86,3 +145,81 @@ quick_error! {
         }
     }
 }
+
+/// The phase of reading or writing las data during which an io error happened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    /// Reading the header.
+    ReadingHeader,
+    /// Reading a vlr.
+    ReadingVlr,
+    /// Reading an evlr.
+    ReadingEvlr,
+    /// Reading a point.
+    ReadingPoint {
+        /// The index of the point, zero-indexed.
+        index: u64,
+        /// The byte offset of the start of the point's record in the las data.
+        offset: u64,
+    },
+    /// Writing the header.
+    WritingHeader,
+    /// Writing a vlr.
+    WritingVlr,
+    /// Writing an evlr.
+    WritingEvlr,
+    /// Writing a point.
+    WritingPoint {
+        /// The index of the point, zero-indexed.
+        index: u64,
+    },
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Phase::ReadingHeader => write!(f, "reading the header"),
+            Phase::ReadingVlr => write!(f, "reading a vlr"),
+            Phase::ReadingEvlr => write!(f, "reading an evlr"),
+            Phase::ReadingPoint { index, offset } => {
+                write!(f, "reading point {} at byte {}", index, offset)
+            }
+            Phase::WritingHeader => write!(f, "writing the header"),
+            Phase::WritingVlr => write!(f, "writing a vlr"),
+            Phase::WritingEvlr => write!(f, "writing an evlr"),
+            Phase::WritingPoint { index } => write!(f, "writing point {}", index),
+        }
+    }
+}
+
+/// Adds a phase to any io errors in a result.
+pub trait WithPhase<T> {
+    /// Converts an `Error::Io` into an `Error::IoDuring` with the given phase.
+    fn with_phase(self, phase: Phase) -> Result<T>;
+}
+
+impl<T> WithPhase<T> for Result<T> {
+    fn with_phase(self, phase: Phase) -> Result<T> {
+        self.map_err(|err| match err {
+            Error::Io(err) => Error::IoDuring(phase, err),
+            err => err,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_phase() {
+        let result: Result<()> = Err(io::Error::new(io::ErrorKind::UnexpectedEof, "eof").into());
+        match result.with_phase(Phase::ReadingPoint {
+            index: 42,
+            offset: 1000,
+        }) {
+            Err(Error::IoDuring(Phase::ReadingPoint { index, .. }, _)) => assert_eq!(42, index),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
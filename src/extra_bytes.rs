@@ -0,0 +1,694 @@
+//! Describe and decode the extra bytes on each point.
+//!
+//! Las 1.4 introduced the "Extra Bytes" vlr (user id `LASF_Spec`, record id 4), which contains a
+//! list of descriptors. Each descriptor gives a name, a data type, and optionally a scale and
+//! offset to a span of each point's extra bytes. The descriptors are stored in order, so the
+//! first descriptor describes the first bytes of the extra bytes, and so on.
+//!
+//! ```
+//! use las::{Builder, Point};
+//! use las::extra_bytes::{DataType, Descriptor, Value};
+//!
+//! let mut builder = Builder::from((1, 4));
+//! builder.point_format.extra_bytes = 2;
+//! let descriptor = Descriptor::new("amplitude", DataType::U16);
+//! builder.vlrs.push(Descriptor::into_vlr(&[descriptor]).unwrap());
+//! let header = builder.into_header().unwrap();
+//!
+//! let mut point = Point { extra_bytes: vec![0, 0], ..Default::default() };
+//! point.set_extra_byte_value("amplitude", Value::U16(42), &header).unwrap();
+//! assert_eq!(Some(Value::U16(42)), point.extra_byte_value("amplitude", &header));
+//! ```
+
+use {Result, Vlr};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// The user id of the extra bytes vlr.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The record id of the extra bytes vlr.
+pub const RECORD_ID: u16 = 4;
+
+/// The length of one descriptor, in bytes.
+pub const DESCRIPTOR_LEN: usize = 192;
+
+const NO_DATA_MASK: u8 = 1;
+const MIN_MASK: u8 = 2;
+const MAX_MASK: u8 = 4;
+const SCALE_MASK: u8 = 8;
+const OFFSET_MASK: u8 = 16;
+
+quick_error! {
+    /// Extra bytes errors.
+    #[derive(Clone, Debug)]
+    pub enum Error {
+        /// The data type code is not defined by the las spec.
+        DataType(n: u8) {
+            description("invalid extra bytes data type")
+            display("invalid extra bytes data type: {}", n)
+        }
+        /// The number of dimensions is not one, two, or three.
+        Dimensions(n: u8) {
+            description("the extra bytes descriptor must have one, two, or three dimensions")
+            display("the extra bytes descriptor must have one, two, or three dimensions: {}", n)
+        }
+        /// The extra bytes vlr's data are not a whole number of descriptors.
+        Length(len: usize) {
+            description("the extra bytes vlr data are not a multiple of the descriptor length")
+            display("the extra bytes vlr data are not a multiple of {}: {}", DESCRIPTOR_LEN, len)
+        }
+        /// There is no descriptor with this name.
+        NotFound(name: String) {
+            description("no extra bytes descriptor with that name")
+            display("no extra bytes descriptor named {}", name)
+        }
+        /// The bytes are too short for the descriptor.
+        TooShort(name: String, len: usize) {
+            description("the bytes are too short for the extra bytes descriptor")
+            display("{} bytes are too short for the extra bytes descriptor {}", len, name)
+        }
+        /// The value does not fit the descriptor's data type.
+        Value(name: String, value: Value) {
+            description("the value does not match the extra bytes descriptor")
+            display("the value {:?} does not match the extra bytes descriptor {}", value, name)
+        }
+    }
+}
+
+/// The type of data stored in an extra bytes span.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum DataType {
+    /// Bytes without any meaning, the number of which is provided.
+    Undocumented(u8),
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+/// A decoded extra bytes value.
+///
+/// If the descriptor has a scale or an offset, the value is always `F64`.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum Value {
+    Undocumented(Vec<u8>),
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    /// Two or three values, for descriptors with more than one dimension.
+    Array(Vec<Value>),
+}
+
+/// Describes one named span of the extra bytes.
+///
+/// The no data, min, max, scale, and offset values are stored as three `f64`s, one per
+/// dimension. For integer data types, the las spec stores these as 64-bit integers, so very large
+/// values lose precision.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Descriptor {
+    /// The type of data.
+    pub data_type: DataType,
+
+    /// The number of values, between one and three.
+    ///
+    /// Decoding, encoding, and writing return an error for any other number.
+    pub dimensions: u8,
+
+    /// The name of this span.
+    pub name: String,
+
+    /// A description of this span.
+    pub description: String,
+
+    /// The value used to indicate "no data".
+    pub no_data: Option<[f64; 3]>,
+
+    /// The minimum value.
+    pub min: Option<[f64; 3]>,
+
+    /// The maximum value.
+    pub max: Option<[f64; 3]>,
+
+    /// The scale applied to the stored value.
+    pub scale: Option<[f64; 3]>,
+
+    /// The offset applied to the stored value.
+    pub offset: Option<[f64; 3]>,
+}
+
+impl DataType {
+    /// Returns the number of bytes used to store one value of this type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::DataType;
+    /// assert_eq!(2, DataType::U16.len());
+    /// assert_eq!(3, DataType::Undocumented(3).len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match *self {
+            DataType::Undocumented(n) => usize::from(n),
+            DataType::U8 | DataType::I8 => 1,
+            DataType::U16 | DataType::I16 => 2,
+            DataType::U32 | DataType::I32 | DataType::F32 => 4,
+            DataType::U64 | DataType::I64 | DataType::F64 => 8,
+        }
+    }
+
+    fn is_signed(&self) -> bool {
+        matches!(*self, DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64)
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(*self, DataType::F32 | DataType::F64)
+    }
+
+    fn code(&self) -> u8 {
+        match *self {
+            DataType::Undocumented(_) => 0,
+            DataType::U8 => 1,
+            DataType::I8 => 2,
+            DataType::U16 => 3,
+            DataType::I16 => 4,
+            DataType::U32 => 5,
+            DataType::I32 => 6,
+            DataType::U64 => 7,
+            DataType::I64 => 8,
+            DataType::F32 => 9,
+            DataType::F64 => 10,
+        }
+    }
+
+    fn from_code(n: u8) -> Result<DataType> {
+        Ok(match n {
+            1 => DataType::U8,
+            2 => DataType::I8,
+            3 => DataType::U16,
+            4 => DataType::I16,
+            5 => DataType::U32,
+            6 => DataType::I32,
+            7 => DataType::U64,
+            8 => DataType::I64,
+            9 => DataType::F32,
+            10 => DataType::F64,
+            _ => return Err(Error::DataType(n).into()),
+        })
+    }
+}
+
+impl Value {
+    /// Returns this value as an `f64`, if it is a single number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::Value;
+    /// assert_eq!(Some(42.), Value::U8(42).as_f64());
+    /// assert_eq!(None, Value::Undocumented(vec![42]).as_f64());
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::U8(n) => Some(f64::from(n)),
+            Value::I8(n) => Some(f64::from(n)),
+            Value::U16(n) => Some(f64::from(n)),
+            Value::I16(n) => Some(f64::from(n)),
+            Value::U32(n) => Some(f64::from(n)),
+            Value::I32(n) => Some(f64::from(n)),
+            Value::U64(n) => Some(n as f64),
+            Value::I64(n) => Some(n as f64),
+            Value::F32(n) => Some(f64::from(n)),
+            Value::F64(n) => Some(n),
+            Value::Undocumented(_) | Value::Array(_) => None,
+        }
+    }
+
+    fn same_type(&self, other: &Value) -> bool {
+        use std::mem::discriminant;
+        discriminant(self) == discriminant(other)
+    }
+}
+
+impl Descriptor {
+    /// Creates a new one-dimensional descriptor without any scale, offset, or statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let descriptor = Descriptor::new("reflectance", DataType::F32);
+    /// assert_eq!(4, descriptor.len());
+    /// ```
+    pub fn new(name: &str, data_type: DataType) -> Descriptor {
+        Descriptor {
+            data_type: data_type,
+            dimensions: 1,
+            name: name.to_string(),
+            description: String::new(),
+            no_data: None,
+            min: None,
+            max: None,
+            scale: None,
+            offset: None,
+        }
+    }
+
+    /// Reads all of the descriptors from an extra bytes vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let vlr = Descriptor::into_vlr(&[Descriptor::new("amplitude", DataType::U16)]).unwrap();
+    /// let descriptors = Descriptor::from_vlr(&vlr).unwrap();
+    /// assert_eq!("amplitude", descriptors[0].name);
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Result<Vec<Descriptor>> {
+        if !vlr.data.len().is_multiple_of(DESCRIPTOR_LEN) {
+            return Err(Error::Length(vlr.data.len()).into());
+        }
+        vlr.data
+            .chunks(DESCRIPTOR_LEN)
+            .map(Descriptor::read_from)
+            .collect()
+    }
+
+    /// Creates an extra bytes vlr from descriptors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let vlr = Descriptor::into_vlr(&[Descriptor::new("amplitude", DataType::U16)]).unwrap();
+    /// assert_eq!("LASF_Spec", vlr.user_id);
+    /// assert_eq!(4, vlr.record_id);
+    /// assert_eq!(192, vlr.data.len());
+    /// ```
+    pub fn into_vlr(descriptors: &[Descriptor]) -> Result<Vlr> {
+        let mut data = Vec::with_capacity(descriptors.len() * DESCRIPTOR_LEN);
+        for descriptor in descriptors {
+            descriptor.write_to(&mut data)?;
+        }
+        Ok(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            description: "Extra bytes".to_string(),
+            data: data,
+        })
+    }
+
+    /// Reads one descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let mut bytes = Vec::new();
+    /// let descriptor = Descriptor::new("amplitude", DataType::U16);
+    /// descriptor.write_to(&mut bytes).unwrap();
+    /// assert_eq!(descriptor, Descriptor::read_from(&bytes[..]).unwrap());
+    /// ```
+    pub fn read_from<R: Read>(mut read: R) -> Result<Descriptor> {
+        use utils::AsLasStr;
+
+        let _reserved = read.read_u16::<LittleEndian>()?;
+        let code = read.read_u8()?;
+        let options = read.read_u8()?;
+        let mut name = [0; 32];
+        read.read_exact(&mut name)?;
+        let mut unused = [0; 4];
+        read.read_exact(&mut unused)?;
+        let (data_type, dimensions) = match code {
+            0 => (DataType::Undocumented(options), 1),
+            1..=10 => (DataType::from_code(code)?, 1),
+            11..=20 => (DataType::from_code(code - 10)?, 2),
+            21..=30 => (DataType::from_code(code - 20)?, 3),
+            _ => return Err(Error::DataType(code).into()),
+        };
+        let no_data = read_any(&mut read, data_type)?;
+        let min = read_any(&mut read, data_type)?;
+        let max = read_any(&mut read, data_type)?;
+        let scale = read_f64s(&mut read)?;
+        let offset = read_f64s(&mut read)?;
+        let mut description = [0; 32];
+        read.read_exact(&mut description)?;
+        let is_set = |mask: u8| code != 0 && options & mask == mask;
+        Ok(Descriptor {
+            data_type: data_type,
+            dimensions: dimensions,
+            name: name.as_ref().as_las_str()?.to_string(),
+            description: description.as_ref().as_las_str()?.to_string(),
+            no_data: if is_set(NO_DATA_MASK) { Some(no_data) } else { None },
+            min: if is_set(MIN_MASK) { Some(min) } else { None },
+            max: if is_set(MAX_MASK) { Some(max) } else { None },
+            scale: if is_set(SCALE_MASK) { Some(scale) } else { None },
+            offset: if is_set(OFFSET_MASK) { Some(offset) } else { None },
+        })
+    }
+
+    /// Writes one descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let mut bytes = Vec::new();
+    /// Descriptor::new("amplitude", DataType::U16).write_to(&mut bytes).unwrap();
+    /// assert_eq!(192, bytes.len());
+    /// ```
+    pub fn write_to<W: Write>(&self, mut write: W) -> Result<()> {
+        use utils::FromLasStr;
+
+        let dimensions = self.checked_dimensions()? as u8;
+        let (code, options) = match self.data_type {
+            DataType::Undocumented(n) => (0, n),
+            data_type => {
+                let mut options = 0;
+                for &(value, mask) in &[
+                    (self.no_data, NO_DATA_MASK),
+                    (self.min, MIN_MASK),
+                    (self.max, MAX_MASK),
+                    (self.scale, SCALE_MASK),
+                    (self.offset, OFFSET_MASK),
+                ]
+                {
+                    if value.is_some() {
+                        options |= mask;
+                    }
+                }
+                (data_type.code() + 10 * (dimensions - 1), options)
+            }
+        };
+        write.write_u16::<LittleEndian>(0)?;
+        write.write_u8(code)?;
+        write.write_u8(options)?;
+        let mut name = [0; 32];
+        name.as_mut().from_las_str(&self.name)?;
+        write.write_all(&name)?;
+        write.write_all(&[0; 4])?;
+        for value in &[self.no_data, self.min, self.max] {
+            write_any(&mut write, self.data_type, value.unwrap_or([0.; 3]))?;
+        }
+        for value in &[self.scale, self.offset] {
+            for &n in &value.unwrap_or([0.; 3]) {
+                write.write_f64::<LittleEndian>(n)?;
+            }
+        }
+        let mut description = [0; 32];
+        description.as_mut().from_las_str(&self.description)?;
+        write.write_all(&description)?;
+        Ok(())
+    }
+
+    /// Returns the number of extra bytes described by this descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let mut descriptor = Descriptor::new("amplitude", DataType::U16);
+    /// assert_eq!(2, descriptor.len());
+    /// descriptor.dimensions = 3;
+    /// assert_eq!(6, descriptor.len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.data_type.len() * usize::from(self.dimensions.max(1))
+    }
+
+    /// Decodes a value from the start of a slice of bytes, applying any scale and offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor, Value};
+    /// let mut descriptor = Descriptor::new("amplitude", DataType::U8);
+    /// assert_eq!(Value::U8(2), descriptor.decode(&[2]).unwrap());
+    /// descriptor.scale = Some([0.5, 0., 0.]);
+    /// assert_eq!(Value::F64(1.), descriptor.decode(&[2]).unwrap());
+    /// ```
+    pub fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        let dimensions = self.checked_dimensions()?;
+        if bytes.len() < self.len() {
+            return Err(Error::TooShort(self.name.clone(), bytes.len()).into());
+        }
+        if let DataType::Undocumented(n) = self.data_type {
+            return Ok(Value::Undocumented(bytes[..usize::from(n)].to_vec()));
+        }
+        let n = self.data_type.len();
+        let mut values = (0..dimensions)
+            .map(|i| {
+                let value = decode_one(self.data_type, &bytes[i * n..(i + 1) * n]);
+                if self.scale.is_some() || self.offset.is_some() {
+                    let scale = self.scale.map_or(1., |s| s[i]);
+                    let offset = self.offset.map_or(0., |o| o[i]);
+                    Value::F64(value.as_f64().expect("numeric value") * scale + offset)
+                } else {
+                    value
+                }
+            })
+            .collect::<Vec<_>>();
+        if values.len() == 1 {
+            Ok(values.remove(0))
+        } else {
+            Ok(Value::Array(values))
+        }
+    }
+
+    /// Encodes a value into the start of a slice of bytes, removing any scale and offset.
+    ///
+    /// If the descriptor has a scale or offset, any numeric value is accepted. Otherwise, the
+    /// value must exactly match the data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor, Value};
+    /// let mut descriptor = Descriptor::new("amplitude", DataType::U8);
+    /// let mut bytes = [0];
+    /// descriptor.encode(&Value::U8(2), &mut bytes).unwrap();
+    /// assert_eq!([2], bytes);
+    /// assert!(descriptor.encode(&Value::I8(2), &mut bytes).is_err());
+    ///
+    /// descriptor.scale = Some([0.5, 0., 0.]);
+    /// descriptor.encode(&Value::F64(2.), &mut bytes).unwrap();
+    /// assert_eq!([4], bytes);
+    /// ```
+    pub fn encode(&self, value: &Value, bytes: &mut [u8]) -> Result<()> {
+        let dimensions = self.checked_dimensions()?;
+        if bytes.len() < self.len() {
+            return Err(Error::TooShort(self.name.clone(), bytes.len()).into());
+        }
+        let values = match (self.data_type, value) {
+            (DataType::Undocumented(n), Value::Undocumented(v)) if v.len() == usize::from(n) => {
+                bytes[..v.len()].copy_from_slice(v);
+                return Ok(());
+            }
+            (_, Value::Array(values)) if values.len() == dimensions && dimensions > 1 => {
+                values.iter().collect::<Vec<_>>()
+            }
+            (DataType::Undocumented(_), _) => return Err(self.value_error(value)),
+            (_, value) if dimensions == 1 => vec![value],
+            _ => return Err(self.value_error(value)),
+        };
+        let n = self.data_type.len();
+        for (i, value) in values.into_iter().enumerate() {
+            let chunk = &mut bytes[i * n..(i + 1) * n];
+            if self.scale.is_some() || self.offset.is_some() {
+                let scale = self.scale.map_or(1., |s| s[i]);
+                let offset = self.offset.map_or(0., |o| o[i]);
+                let n = value.as_f64().ok_or_else(|| self.value_error(value))?;
+                let n = (n - offset) / scale;
+                let n = if self.data_type.is_float() { n } else { n.round() };
+                let value = from_f64(self.data_type, n).ok_or_else(|| self.value_error(value))?;
+                encode_one(&value, chunk);
+            } else if decode_one(self.data_type, chunk).same_type(value) {
+                encode_one(value, chunk);
+            } else {
+                return Err(self.value_error(value));
+            }
+        }
+        Ok(())
+    }
+
+    fn checked_dimensions(&self) -> Result<usize> {
+        match self.dimensions {
+            1..=3 => Ok(usize::from(self.dimensions)),
+            n => Err(Error::Dimensions(n).into()),
+        }
+    }
+
+    fn value_error(&self, value: &Value) -> ::Error {
+        Error::Value(self.name.clone(), value.clone()).into()
+    }
+}
+
+pub(crate) fn decode_one(data_type: DataType, bytes: &[u8]) -> Value {
+    match data_type {
+        DataType::Undocumented(_) => Value::Undocumented(bytes.to_vec()),
+        DataType::U8 => Value::U8(bytes[0]),
+        DataType::I8 => Value::I8(bytes[0] as i8),
+        DataType::U16 => Value::U16(LittleEndian::read_u16(bytes)),
+        DataType::I16 => Value::I16(LittleEndian::read_i16(bytes)),
+        DataType::U32 => Value::U32(LittleEndian::read_u32(bytes)),
+        DataType::I32 => Value::I32(LittleEndian::read_i32(bytes)),
+        DataType::U64 => Value::U64(LittleEndian::read_u64(bytes)),
+        DataType::I64 => Value::I64(LittleEndian::read_i64(bytes)),
+        DataType::F32 => Value::F32(LittleEndian::read_f32(bytes)),
+        DataType::F64 => Value::F64(LittleEndian::read_f64(bytes)),
+    }
+}
+
+fn encode_one(value: &Value, bytes: &mut [u8]) {
+    match *value {
+        Value::U8(n) => bytes[0] = n,
+        Value::I8(n) => bytes[0] = n as u8,
+        Value::U16(n) => LittleEndian::write_u16(bytes, n),
+        Value::I16(n) => LittleEndian::write_i16(bytes, n),
+        Value::U32(n) => LittleEndian::write_u32(bytes, n),
+        Value::I32(n) => LittleEndian::write_i32(bytes, n),
+        Value::U64(n) => LittleEndian::write_u64(bytes, n),
+        Value::I64(n) => LittleEndian::write_i64(bytes, n),
+        Value::F32(n) => LittleEndian::write_f32(bytes, n),
+        Value::F64(n) => LittleEndian::write_f64(bytes, n),
+        Value::Undocumented(ref v) => bytes[..v.len()].copy_from_slice(v),
+        Value::Array(_) => unreachable!(),
+    }
+}
+
+fn from_f64(data_type: DataType, n: f64) -> Option<Value> {
+    use num::NumCast;
+    Some(match data_type {
+        DataType::U8 => Value::U8(NumCast::from(n)?),
+        DataType::I8 => Value::I8(NumCast::from(n)?),
+        DataType::U16 => Value::U16(NumCast::from(n)?),
+        DataType::I16 => Value::I16(NumCast::from(n)?),
+        DataType::U32 => Value::U32(NumCast::from(n)?),
+        DataType::I32 => Value::I32(NumCast::from(n)?),
+        DataType::U64 => Value::U64(NumCast::from(n)?),
+        DataType::I64 => Value::I64(NumCast::from(n)?),
+        DataType::F32 => Value::F32(n as f32),
+        DataType::F64 => Value::F64(n),
+        DataType::Undocumented(_) => return None,
+    })
+}
+
+fn read_any<R: Read>(mut read: R, data_type: DataType) -> Result<[f64; 3]> {
+    let mut values = [0.; 3];
+    for value in &mut values {
+        *value = if data_type.is_float() {
+            read.read_f64::<LittleEndian>()?
+        } else if data_type.is_signed() {
+            read.read_i64::<LittleEndian>()? as f64
+        } else {
+            read.read_u64::<LittleEndian>()? as f64
+        };
+    }
+    Ok(values)
+}
+
+fn write_any<W: Write>(mut write: W, data_type: DataType, values: [f64; 3]) -> Result<()> {
+    for &value in &values {
+        if data_type.is_float() {
+            write.write_f64::<LittleEndian>(value)?;
+        } else if data_type.is_signed() {
+            write.write_i64::<LittleEndian>(value as i64)?;
+        } else {
+            write.write_u64::<LittleEndian>(value as u64)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_f64s<R: Read>(mut read: R) -> Result<[f64; 3]> {
+    let mut values = [0.; 3];
+    for value in &mut values {
+        *value = read.read_f64::<LittleEndian>()?;
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut descriptor = Descriptor::new("reflectance", DataType::I16);
+        descriptor.dimensions = 2;
+        descriptor.description = "Some reflectance values".to_string();
+        descriptor.min = Some([-1., -2., 0.]);
+        descriptor.scale = Some([0.01, 0.01, 0.]);
+        let vlr = Descriptor::into_vlr(&[descriptor.clone(), Descriptor::new("b", DataType::F64)])
+            .unwrap();
+        let descriptors = Descriptor::from_vlr(&vlr).unwrap();
+        assert_eq!(2, descriptors.len());
+        assert_eq!(descriptor, descriptors[0]);
+    }
+
+    #[test]
+    fn undocumented() {
+        let descriptor = Descriptor::new("", DataType::Undocumented(3));
+        let vlr = Descriptor::into_vlr(&[descriptor.clone()]).unwrap();
+        assert_eq!(descriptor, Descriptor::from_vlr(&vlr).unwrap()[0]);
+        assert_eq!(
+            Value::Undocumented(vec![1, 2, 3]),
+            descriptor.decode(&[1, 2, 3]).unwrap()
+        );
+    }
+
+    #[test]
+    fn bad_length() {
+        let vlr = Vlr {
+            data: vec![0; 191],
+            ..Default::default()
+        };
+        assert!(Descriptor::from_vlr(&vlr).is_err());
+    }
+
+    #[test]
+    fn scaled_array() {
+        let mut descriptor = Descriptor::new("xyz", DataType::I32);
+        descriptor.dimensions = 3;
+        descriptor.scale = Some([0.1, 0.1, 0.1]);
+        descriptor.offset = Some([1., 2., 3.]);
+        let mut bytes = [0; 12];
+        let value = Value::Array(vec![Value::F64(1.5), Value::F64(2.), Value::F64(-3.)]);
+        descriptor.encode(&value, &mut bytes).unwrap();
+        assert_eq!(value, descriptor.decode(&bytes).unwrap());
+    }
+
+    #[test]
+    fn bad_dimensions() {
+        let mut descriptor = Descriptor::new("a", DataType::U8);
+        descriptor.dimensions = 4;
+        assert!(descriptor.decode(&[0; 4]).is_err());
+        assert!(descriptor.encode(&Value::U8(0), &mut [0; 4]).is_err());
+        assert!(descriptor.write_to(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut descriptor = Descriptor::new("a", DataType::U8);
+        descriptor.scale = Some([1., 0., 0.]);
+        assert!(descriptor.encode(&Value::F64(256.), &mut [0]).is_err());
+        assert!(descriptor.encode(&Value::F64(-1.), &mut [0]).is_err());
+    }
+}
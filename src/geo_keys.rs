@@ -0,0 +1,415 @@
+//! Parse the GeoTIFF keys that describe a coordinate reference system.
+//!
+//! Las files can describe their coordinate reference system with GeoTIFF keys, stored in up to
+//! three vlrs with the user id `LASF_Projection`:
+//!
+//! - the GeoKeyDirectoryTag (record id 34735), which is required and holds the key entries,
+//! - the GeoDoubleParamsTag (record id 34736), which holds any double-valued parameters, and
+//! - the GeoAsciiParamsTag (record id 34737), which holds any ascii parameters.
+//!
+//! A `GeoKeyDirectory` resolves each key entry to its value, wherever it's stored:
+//!
+//! ```
+//! use las::Reader;
+//! use las::geo_keys::Value;
+//!
+//! let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+//! assert_eq!(Some(2994), geo_keys.projected_cs_type());
+//! assert_eq!(
+//!     Some(&Value::Ascii("NAD83(HARN) / Oregon Lambert (ft)".to_string())),
+//!     geo_keys.get(1026)
+//! );
+//! ```
+
+use {Result, Vlr};
+use byteorder::{ByteOrder, LittleEndian};
+
+/// The user id of the GeoTIFF vlrs.
+pub const USER_ID: &str = "LASF_Projection";
+
+/// The record id of the GeoKeyDirectoryTag vlr.
+pub const DIRECTORY_RECORD_ID: u16 = 34735;
+
+/// The record id of the GeoDoubleParamsTag vlr.
+pub const DOUBLE_PARAMS_RECORD_ID: u16 = 34736;
+
+/// The record id of the GeoAsciiParamsTag vlr.
+pub const ASCII_PARAMS_RECORD_ID: u16 = 34737;
+
+/// The record id of the OGC coordinate system WKT vlr, which replaces GeoTIFF in las 1.4.
+pub const WKT_RECORD_ID: u16 = 2112;
+
+/// The key id of GTModelTypeGeoKey, e.g. 1 for projected or 2 for geographic.
+pub const GT_MODEL_TYPE: u16 = 1024;
+
+/// The key id of GeographicTypeGeoKey, the EPSG code of a geographic coordinate system.
+pub const GEOGRAPHIC_TYPE: u16 = 2048;
+
+/// The key id of ProjectedCSTypeGeoKey, the EPSG code of a projected coordinate system.
+pub const PROJECTED_CS_TYPE: u16 = 3072;
+
+/// The key id of VerticalCSTypeGeoKey, the EPSG code of a vertical coordinate system.
+pub const VERTICAL_CS_TYPE: u16 = 4096;
+
+quick_error! {
+    /// GeoTIFF key errors.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Error {
+        /// The key directory's data are too short for its header or its number of keys.
+        Length(len: usize) {
+            description("the geo key directory is too short")
+            display("the geo key directory is too short: {} bytes", len)
+        }
+        /// A key's value is stored somewhere that isn't supported.
+        Location(key_id: u16, location: u16) {
+            description("unsupported geo key value location")
+            display("geo key {} has an unsupported value location: {}", key_id, location)
+        }
+        /// A key's value is outside of its parameters vlr, or that vlr is missing.
+        Parameters(key_id: u16, record_id: u16) {
+            description("the geo key value is outside of its parameters vlr")
+            display("geo key {} points outside of the parameters in vlr {}", key_id, record_id)
+        }
+    }
+}
+
+/// The value of one GeoTIFF key.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A short value, stored directly in the key entry.
+    Short(u16),
+    /// Double values, stored in the GeoDoubleParamsTag vlr.
+    Doubles(Vec<f64>),
+    /// An ascii value, stored in the GeoAsciiParamsTag vlr.
+    ///
+    /// The `|` terminator is not included.
+    Ascii(String),
+}
+
+/// One GeoTIFF key entry, with its value resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoKey {
+    /// The key id, e.g. `PROJECTED_CS_TYPE`.
+    pub id: u16,
+    /// The key's value.
+    pub value: Value,
+}
+
+/// The GeoTIFF keys from a las file's GeoKeyDirectoryTag vlr.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoKeyDirectory {
+    /// The key directory version, key revision, and minor revision.
+    pub version: (u16, u16, u16),
+    /// The keys, in the order that they're stored.
+    pub keys: Vec<GeoKey>,
+}
+
+impl GeoKeyDirectory {
+    /// Creates a new, empty key directory with version 1.1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geo_keys::GeoKeyDirectory;
+    /// let geo_keys = GeoKeyDirectory::new();
+    /// assert!(geo_keys.keys.is_empty());
+    /// ```
+    pub fn new() -> GeoKeyDirectory {
+        GeoKeyDirectory {
+            version: (1, 1, 0),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Reads a key directory from its vlr and the optional double and ascii parameter vlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geo_keys::{GeoKey, GeoKeyDirectory, Value, PROJECTED_CS_TYPE};
+    /// let mut geo_keys = GeoKeyDirectory::new();
+    /// geo_keys.keys.push(GeoKey { id: PROJECTED_CS_TYPE, value: Value::Short(32610) });
+    /// let vlrs = geo_keys.to_vlrs();
+    /// assert_eq!(geo_keys, GeoKeyDirectory::from_vlrs(&vlrs[0], None, None).unwrap());
+    /// ```
+    pub fn from_vlrs(
+        directory: &Vlr,
+        doubles: Option<&Vlr>,
+        ascii: Option<&Vlr>,
+    ) -> Result<GeoKeyDirectory> {
+        let data = &directory.data;
+        if data.len() < 8 {
+            return Err(Error::Length(data.len()).into());
+        }
+        let short = |i: usize| LittleEndian::read_u16(&data[2 * i..]);
+        let number_of_keys = usize::from(short(3));
+        if data.len() < 8 * (number_of_keys + 1) {
+            return Err(Error::Length(data.len()).into());
+        }
+        let mut keys = Vec::with_capacity(number_of_keys);
+        for i in 1..(number_of_keys + 1) {
+            let (id, location, count, offset) =
+                (short(4 * i), short(4 * i + 1), short(4 * i + 2), short(4 * i + 3));
+            let start = usize::from(offset);
+            let end = start + usize::from(count);
+            let value = match location {
+                0 => Value::Short(offset),
+                DOUBLE_PARAMS_RECORD_ID => {
+                    let data = match doubles {
+                        Some(vlr) if end * 8 <= vlr.data.len() => &vlr.data,
+                        _ => return Err(Error::Parameters(id, location).into()),
+                    };
+                    Value::Doubles(
+                        (start..end)
+                            .map(|j| LittleEndian::read_f64(&data[8 * j..]))
+                            .collect(),
+                    )
+                }
+                ASCII_PARAMS_RECORD_ID => {
+                    let data = match ascii {
+                        Some(vlr) if end <= vlr.data.len() => &vlr.data,
+                        _ => return Err(Error::Parameters(id, location).into()),
+                    };
+                    let s = String::from_utf8_lossy(&data[start..end]);
+                    Value::Ascii(s.trim_end_matches(['|', '\0']).to_string())
+                }
+                _ => return Err(Error::Location(id, location).into()),
+            };
+            keys.push(GeoKey {
+                id: id,
+                value: value,
+            });
+        }
+        Ok(GeoKeyDirectory {
+            version: (short(0), short(1), short(2)),
+            keys: keys,
+        })
+    }
+
+    /// Creates the GeoTIFF vlrs for this key directory.
+    ///
+    /// The first vlr is always the key directory. The double and ascii parameter vlrs follow, but
+    /// only if any keys need them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geo_keys::{GeoKey, GeoKeyDirectory, Value};
+    /// let mut geo_keys = GeoKeyDirectory::new();
+    /// geo_keys.keys.push(GeoKey { id: 1026, value: Value::Ascii("WGS 84".to_string()) });
+    /// let vlrs = geo_keys.to_vlrs();
+    /// assert_eq!(2, vlrs.len());
+    /// assert_eq!(b"WGS 84|\0", &vlrs[1].data[..]);
+    /// ```
+    pub fn to_vlrs(&self) -> Vec<Vlr> {
+        let mut directory = vec![0; 8 * (self.keys.len() + 1)];
+        let mut doubles = Vec::new();
+        let mut ascii = Vec::new();
+        {
+            let mut put = |i: usize, n: u16| LittleEndian::write_u16(&mut directory[2 * i..], n);
+            put(0, self.version.0);
+            put(1, self.version.1);
+            put(2, self.version.2);
+            put(3, self.keys.len() as u16);
+            for (i, key) in self.keys.iter().enumerate() {
+                let i = 4 * (i + 1);
+                put(i, key.id);
+                let (location, count, offset) = match key.value {
+                    Value::Short(n) => (0, 1, n),
+                    Value::Doubles(ref values) => {
+                        let offset = doubles.len() / 8;
+                        for &value in values {
+                            let mut bytes = [0; 8];
+                            LittleEndian::write_f64(&mut bytes, value);
+                            doubles.extend(&bytes);
+                        }
+                        (DOUBLE_PARAMS_RECORD_ID, values.len(), offset as u16)
+                    }
+                    Value::Ascii(ref s) => {
+                        let offset = ascii.len();
+                        ascii.extend(s.as_bytes());
+                        ascii.push(b'|');
+                        (ASCII_PARAMS_RECORD_ID, s.len() + 1, offset as u16)
+                    }
+                };
+                put(i + 1, location);
+                put(i + 2, count as u16);
+                put(i + 3, offset);
+            }
+        }
+        let mut vlrs = vec![
+            Vlr {
+                user_id: USER_ID.to_string(),
+                record_id: DIRECTORY_RECORD_ID,
+                description: "GeoTiff GeoKeyDirectoryTag".to_string(),
+                data: directory,
+            },
+        ];
+        if !doubles.is_empty() {
+            vlrs.push(Vlr {
+                user_id: USER_ID.to_string(),
+                record_id: DOUBLE_PARAMS_RECORD_ID,
+                description: "GeoTiff GeoDoubleParamsTag".to_string(),
+                data: doubles,
+            });
+        }
+        if !ascii.is_empty() {
+            ascii.push(0);
+            vlrs.push(Vlr {
+                user_id: USER_ID.to_string(),
+                record_id: ASCII_PARAMS_RECORD_ID,
+                description: "GeoTiff GeoAsciiParamsTag".to_string(),
+                data: ascii,
+            });
+        }
+        vlrs
+    }
+
+    /// Returns the value of the first key with this id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::geo_keys::{GeoKey, GeoKeyDirectory, Value};
+    /// let mut geo_keys = GeoKeyDirectory::new();
+    /// geo_keys.keys.push(GeoKey { id: 1024, value: Value::Short(1) });
+    /// assert_eq!(Some(&Value::Short(1)), geo_keys.get(1024));
+    /// assert_eq!(None, geo_keys.get(1025));
+    /// ```
+    pub fn get(&self, id: u16) -> Option<&Value> {
+        self.keys.iter().find(|key| key.id == id).map(|key| &key.value)
+    }
+
+    /// Returns the GTModelTypeGeoKey, e.g. 1 for projected or 2 for geographic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+    /// assert_eq!(Some(1), geo_keys.model_type());
+    /// ```
+    pub fn model_type(&self) -> Option<u16> {
+        self.short(GT_MODEL_TYPE)
+    }
+
+    /// Returns the ProjectedCSTypeGeoKey, which is the EPSG code of the projected coordinate
+    /// system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+    /// assert_eq!(Some(2994), geo_keys.projected_cs_type());
+    /// ```
+    pub fn projected_cs_type(&self) -> Option<u16> {
+        self.short(PROJECTED_CS_TYPE)
+    }
+
+    /// Returns the GeographicTypeGeoKey, which is the EPSG code of the geographic coordinate
+    /// system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+    /// assert_eq!(None, geo_keys.geographic_type());
+    /// ```
+    pub fn geographic_type(&self) -> Option<u16> {
+        self.short(GEOGRAPHIC_TYPE)
+    }
+
+    /// Returns the EPSG code of the horizontal coordinate system.
+    ///
+    /// This is the projected coordinate system if there is one, otherwise the geographic one. The
+    /// GeoTIFF values 0 (undefined) and 32767 (user-defined) are not EPSG codes, and return
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+    /// assert_eq!(Some(2994), geo_keys.epsg_code());
+    /// ```
+    pub fn epsg_code(&self) -> Option<u16> {
+        self.projected_cs_type()
+            .or_else(|| self.geographic_type())
+            .and_then(|code| if code == 0 || code == 32767 {
+                None
+            } else {
+                Some(code)
+            })
+    }
+
+    fn short(&self, id: u16) -> Option<u16> {
+        match self.get(id) {
+            Some(&Value::Short(n)) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+impl Default for GeoKeyDirectory {
+    fn default() -> GeoKeyDirectory {
+        GeoKeyDirectory::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_all_locations() {
+        let mut geo_keys = GeoKeyDirectory::new();
+        geo_keys.keys = vec![
+            GeoKey {
+                id: GT_MODEL_TYPE,
+                value: Value::Short(2),
+            },
+            GeoKey {
+                id: 2049,
+                value: Value::Ascii("WGS 84".to_string()),
+            },
+            GeoKey {
+                id: 2057,
+                value: Value::Doubles(vec![6378137.]),
+            },
+            GeoKey {
+                id: 2058,
+                value: Value::Doubles(vec![298.257223563]),
+            },
+        ];
+        let vlrs = geo_keys.to_vlrs();
+        assert_eq!(3, vlrs.len());
+        assert_eq!(16, vlrs[1].data.len());
+        let read = GeoKeyDirectory::from_vlrs(&vlrs[0], Some(&vlrs[1]), Some(&vlrs[2])).unwrap();
+        assert_eq!(geo_keys, read);
+    }
+
+    #[test]
+    fn missing_parameters() {
+        let mut geo_keys = GeoKeyDirectory::new();
+        geo_keys.keys.push(GeoKey {
+            id: 2049,
+            value: Value::Ascii("WGS 84".to_string()),
+        });
+        let vlrs = geo_keys.to_vlrs();
+        assert!(GeoKeyDirectory::from_vlrs(&vlrs[0], None, None).is_err());
+    }
+
+    #[test]
+    fn too_short() {
+        let mut vlr = GeoKeyDirectory::new().to_vlrs().remove(0);
+        vlr.data[6] = 1;
+        assert!(GeoKeyDirectory::from_vlrs(&vlr, None, None).is_err());
+    }
+}
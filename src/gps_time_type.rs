@@ -23,6 +23,26 @@ impl GpsTimeType {
             GpsTimeType::Standard => true,
         }
     }
+
+    /// Returns the range of gps times that are plausible for this time type, inclusive.
+    ///
+    /// Week time is the number of seconds since the start of the gps week, so it's between zero
+    /// and 604,800. Adjusted standard time is the number of seconds since the gps epoch (January
+    /// 6th, 1980) minus one billion, so it's between -1e9 at the gps epoch and 1e9, which is
+    /// sometime in 2043.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::GpsTimeType;
+    /// assert_eq!((0., 604_800.), GpsTimeType::Week.plausible_range());
+    /// ```
+    pub fn plausible_range(&self) -> (f64, f64) {
+        match *self {
+            GpsTimeType::Week => (0., 604_800.),
+            GpsTimeType::Standard => (-1e9, 1e9),
+        }
+    }
 }
 
 impl From<GpsTimeType> for u16 {
@@ -4,9 +4,10 @@ use header::Error;
 use point::Format;
 use std::collections::HashMap;
 use uuid::Uuid;
+use waveform::WaveformLocation;
 
 /// Builds headers.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct Builder {
     /// The date of file creation.
     pub date: Option<Date<Utc>>,
@@ -56,14 +57,54 @@ pub struct Builder {
     /// The extended variable length records.
     pub evlrs: Vec<Vlr>,
 
+    /// Where the waveform data packets are stored.
+    ///
+    /// Required for point formats with waveforms, and must be `None` for the others.
+    pub waveform_location: Option<WaveformLocation>,
+
+    /// Should vlrs whose data are too large for a regular vlr be moved to the evlrs?
+    ///
+    /// This is true by default. If false, or if the version doesn't support evlrs, such vlrs
+    /// cause `into_header` to error. Use `into_header_with_promoted_vlrs` to find out which vlrs
+    /// were moved.
+    pub auto_promote_large_vlrs: bool,
+
     number_of_points_by_return: HashMap<u8, u64>,
     number_of_points: u64,
     bounds: Bounds,
 }
 
+/// How `Builder::merge_vlrs` resolves vlrs that conflict with the ones already in the builder.
+///
+/// Two vlrs conflict if they have the same user id and record id but different data. Vlrs and
+/// evlrs are compared with each other, so a conflict is found even if one of the files stored the
+/// record as an evlr. Vlrs whose data are the same as one already in the builder are always
+/// skipped, whatever the strategy.
+///
+/// The coordinate reference system vlrs, i.e. the GeoTIFF keys and the WKT with the
+/// "LASF_Projection" user id, are merged as one unit, so the result never mixes records from two
+/// coordinate reference systems. They conflict if the two sets of records are not the same.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VlrMergeStrategy {
+    /// Keep the vlr already in the builder, and drop the conflicting one.
+    KeepFirst,
+    /// Replace the vlr already in the builder with the conflicting one.
+    KeepLast,
+    /// Return `Error::VlrConflict` on any conflict.
+    Error,
+    /// Keep both of the conflicting vlrs, only dropping exact duplicates.
+    ///
+    /// Conflicting coordinate reference systems still cause `Error::VlrConflict`, since two
+    /// coordinate reference systems can't both describe the points.
+    Deduplicate,
+}
+
 impl Builder {
     /// Creates a new builder from a raw header.
     ///
+    /// The raw header's file source id and global encoding are only used if the version has them:
+    /// in las 1.0 both are reserved, and in las 1.1 the global encoding is reserved.
+    ///
     /// # Examples
     ///
     /// ```
@@ -72,6 +113,7 @@ impl Builder {
     /// ```
     pub fn new(raw_header: raw::Header) -> Result<Builder> {
         use chrono::TimeZone;
+        use feature::{FileSourceId, GpsStandardTime};
         use utils::AsLasStr;
 
         let number_of_points = if raw_header.number_of_point_records > 0 {
@@ -84,7 +126,18 @@ impl Builder {
         };
         let number_of_points_by_return =
             if raw_header.number_of_points_by_return.iter().any(|&n| n > 0) {
-                number_of_points_hash_map(&raw_header.number_of_points_by_return)
+                // The legacy fields can't hold return numbers above five, so those still come from
+                // the las 1.4 fields.
+                let mut number_of_points_by_return =
+                    number_of_points_hash_map(&raw_header.number_of_points_by_return);
+                if let Some(large_file) = raw_header.large_file {
+                    number_of_points_by_return.extend(
+                        number_of_points_hash_map(&large_file.number_of_points_by_return)
+                            .into_iter()
+                            .filter(|&(return_number, _)| return_number > 5),
+                    );
+                }
+                number_of_points_by_return
             } else {
                 raw_header
                     .large_file
@@ -101,22 +154,40 @@ impl Builder {
         } else if n < raw_header.point_data_record_length {
             point_format.extra_bytes = raw_header.point_data_record_length - n;
         }
+        // Las 1.0 has four reserved bytes where later versions have the file source id and the
+        // global encoding, and in las 1.1 the global encoding is still reserved.
+        let file_source_id = if raw_header.version.supports::<FileSourceId>() {
+            raw_header.file_source_id
+        } else {
+            0
+        };
+        let global_encoding = if raw_header.version.supports::<GpsStandardTime>() {
+            raw_header.global_encoding
+        } else {
+            0
+        };
+        let waveform_location = if point_format.has_waveform {
+            WaveformLocation::from_global_encoding(global_encoding)?
+        } else {
+            None
+        };
         Ok(Builder {
+            auto_promote_large_vlrs: true,
             date: Utc.yo_opt(
                 i32::from(raw_header.file_creation_year),
                 u32::from(raw_header.file_creation_day_of_year),
             ).single(),
             point_padding: Vec::new(),
             evlrs: Vec::new(),
-            file_source_id: raw_header.file_source_id,
+            file_source_id: file_source_id,
             generating_software: raw_header
                 .generating_software
                 .as_ref()
                 .as_las_str()?
                 .to_string(),
-            gps_time_type: raw_header.global_encoding.into(),
+            gps_time_type: global_encoding.into(),
             guid: Uuid::from_bytes(&raw_header.guid).unwrap(),
-            has_synthetic_return_numbers: raw_header.global_encoding & 8 == 8,
+            has_synthetic_return_numbers: global_encoding & 8 == 8,
             padding: raw_header.padding,
             point_format: point_format,
             system_identifier: raw_header
@@ -155,6 +226,7 @@ impl Builder {
             },
             number_of_points: number_of_points,
             number_of_points_by_return: number_of_points_by_return,
+            waveform_location: waveform_location,
         })
     }
 
@@ -166,9 +238,26 @@ impl Builder {
     /// use las::Builder;
     /// let header = Builder::new(Default::default()).unwrap().into_header().unwrap();
     /// ```
-    pub fn into_header(mut self) -> Result<Header> {
+    pub fn into_header(self) -> Result<Header> {
+        self.into_header_with_promoted_vlrs().map(|(header, _)| header)
+    }
+
+    /// Converts this builder into a `Header`, also returning the user id and record id of each
+    /// vlr that was moved to the evlrs because its data are too large for a regular vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(Vlr { data: vec![0; 100_000], ..Default::default() });
+    /// let (header, promoted) = builder.into_header_with_promoted_vlrs().unwrap();
+    /// assert_eq!(1, header.evlrs().len());
+    /// assert_eq!(vec![(String::new(), 0)], promoted);
+    /// ```
+    pub fn into_header_with_promoted_vlrs(mut self) -> Result<(Header, Vec<(String, u16)>)> {
         use raw::POINT_DATA_START_SIGNATURE;
-        use feature::{Evlrs, FileSourceId, GpsStandardTime, SyntheticReturnNumbers};
+        use feature::{Evlrs, FileSourceId, GpsStandardTime, SyntheticReturnNumbers, Waveforms};
         use header::Error;
 
         let n = self.vlr_padding.len();
@@ -186,14 +275,28 @@ impl Builder {
         if self.gps_time_type.is_standard() {
             self.version.verify_support_for::<GpsStandardTime>()?;
         }
-        // TODO check waveforms
+        if self.point_format.has_waveform != self.waveform_location.is_some() {
+            return Err(
+                Error::WaveformLocation(self.point_format, self.waveform_location).into(),
+            );
+        }
+        if self.waveform_location.is_some() {
+            self.version.verify_support_for::<Waveforms>()?;
+        }
         if !self.version.supports_point_format(self.point_format) {
             return Err(Error::Format(self.version, self.point_format).into());
         }
+        let promoted = if self.auto_promote_large_vlrs && self.version.supports::<Evlrs>() {
+            self.promote_large_vlrs()
+        } else {
+            Vec::new()
+        };
         let mut vlrs = Vec::new();
         let mut evlrs = Vec::new();
         for evlr in self.evlrs {
-            if self.version.supports::<Evlrs>() || evlr.has_large_data() {
+            if self.version.supports::<Evlrs>() || evlr.has_large_data() ||
+                (self.version.supports::<Waveforms>() && evlr.is_waveform_data_packets())
+            {
                 evlrs.push(evlr);
             } else {
                 vlrs.push(evlr);
@@ -201,13 +304,25 @@ impl Builder {
         }
         for vlr in self.vlrs {
             if vlr.has_large_data() {
-                evlrs.push(vlr);
+                return Err(Error::VlrTooLarge(vlr.data.len()).into());
             } else {
                 vlrs.push(vlr);
             }
         }
+        if vlrs.len() > u32::MAX as usize {
+            return Err(Error::TooManyVlrs(vlrs.len()).into());
+        }
+        if evlrs.len() > u32::MAX as usize {
+            return Err(Error::TooManyEvlrs(evlrs.len()).into());
+        }
         if !evlrs.is_empty() {
-            self.version.verify_support_for::<Evlrs>()?;
+            // Las 1.3 doesn't have evlrs, but its one waveform data packet record has an evlr
+            // header and comes after the points, just like a las 1.4 evlr.
+            let is_waveform_data_packet_record = evlrs.len() == 1 &&
+                evlrs[0].is_waveform_data_packets();
+            if !(is_waveform_data_packet_record && self.version.supports::<Waveforms>()) {
+                self.version.verify_support_for::<Evlrs>()?;
+            }
         } else if !self.point_padding.is_empty() {
             return Err(Error::PointPadding.into());
         }
@@ -230,8 +345,247 @@ impl Builder {
             version: self.version,
             vlr_padding: self.vlr_padding,
             vlrs: vlrs,
+            waveform_location: self.waveform_location,
         };
-        Ok(header)
+        Ok((header, promoted))
+    }
+
+    /// Removes all extended variable length records.
+    ///
+    /// The point padding is removed as well, since it can only exist before evlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.evlrs.push(Vlr::default());
+    /// builder.point_padding = vec![0];
+    /// builder.clear_evlrs();
+    /// assert!(builder.evlrs.is_empty());
+    /// assert!(builder.point_padding.is_empty());
+    /// ```
+    pub fn clear_evlrs(&mut self) {
+        self.evlrs.clear();
+        self.point_padding.clear();
+    }
+
+    /// Removes all variable length records.
+    ///
+    /// The vlr padding is left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Reader};
+    /// let header = Reader::from_path("tests/data/autzen.las").unwrap().header().clone();
+    /// let mut builder = Builder::from(header);
+    /// builder.clear_vlrs();
+    /// assert!(builder.into_header().unwrap().vlrs().is_empty());
+    /// ```
+    pub fn clear_vlrs(&mut self) {
+        self.vlrs.clear();
+    }
+
+    /// Copies everything that affects the byte layout up to the point data from a template header.
+    ///
+    /// This is the version (which determines the header size), the header padding, the vlrs and the
+    /// vlr padding (which, together with the header size, determine the offset to the point data),
+    /// and the point format (which determines the point record length), along with its waveform
+    /// location. Use this when a downstream tool hard-codes offsets from a template file. Other
+    /// fields, e.g. the system identifier or transforms, are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Reader};
+    /// let template = Reader::from_path("tests/data/autzen.las").unwrap().header().clone();
+    /// let mut builder = Builder::default();
+    /// builder.match_layout(&template);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(
+    ///     template.clone().into_raw().unwrap().offset_to_point_data,
+    ///     header.into_raw().unwrap().offset_to_point_data
+    /// );
+    /// ```
+    pub fn match_layout(&mut self, template: &Header) {
+        self.version = template.version();
+        self.padding = template.padding().clone();
+        self.point_format = template.point_format();
+        self.waveform_location = template.waveform_location();
+        self.vlrs = template.vlrs().clone();
+        self.vlr_padding = template.vlr_padding().clone();
+    }
+
+    /// Merges another header's vlrs and evlrs into this builder's.
+    ///
+    /// Vlrs that don't conflict with any already in the builder are added, the vlrs to the vlrs
+    /// and the evlrs to the evlrs. Conflicts are resolved by the strategy, see
+    /// `VlrMergeStrategy`. The coordinate reference system vlrs are merged as one unit. On an
+    /// error, the builder is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr, VlrMergeStrategy};
+    /// let vlr = |data| Vlr { user_id: "gadomski".to_string(), data: data, ..Default::default() };
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(vlr(vec![1]));
+    /// let mut other = Builder::from((1, 4));
+    /// other.vlrs.push(vlr(vec![2]));
+    /// let other = other.into_header().unwrap();
+    /// assert!(builder.merge_vlrs(&other, VlrMergeStrategy::Error).is_err());
+    /// builder.merge_vlrs(&other, VlrMergeStrategy::KeepLast).unwrap();
+    /// assert_eq!(vec![2], builder.vlrs[0].data);
+    /// ```
+    pub fn merge_vlrs(&mut self, header: &Header, strategy: VlrMergeStrategy) -> Result<()> {
+        use geo_keys::USER_ID;
+
+        let is_crs = |vlr: &Vlr| vlr.user_id == USER_ID;
+        let replace_crs = {
+            let theirs = crs_records(header.vlrs(), header.evlrs());
+            let mine = crs_records(&self.vlrs, &self.evlrs);
+            if theirs.is_empty() || mine == theirs {
+                false
+            } else if mine.is_empty() {
+                true
+            } else {
+                match strategy {
+                    VlrMergeStrategy::KeepFirst => false,
+                    VlrMergeStrategy::KeepLast => true,
+                    VlrMergeStrategy::Error |
+                    VlrMergeStrategy::Deduplicate => {
+                        return Err(::Error::VlrConflict(USER_ID.to_string(), theirs[0].0))
+                    }
+                }
+            }
+        };
+        // Merge into copies, so that the builder is unchanged if there's a conflict.
+        let mut vlrs = self.vlrs.clone();
+        let mut evlrs = self.evlrs.clone();
+        if replace_crs {
+            vlrs.retain(|vlr| !is_crs(vlr));
+            evlrs.retain(|evlr| !is_crs(evlr));
+            vlrs.extend(header.vlrs().iter().filter(|vlr| is_crs(vlr)).cloned());
+            evlrs.extend(header.evlrs().iter().filter(|evlr| is_crs(evlr)).cloned());
+        }
+
+        let theirs = header.vlrs().iter().map(|vlr| (vlr, false));
+        let their_evlrs = header.evlrs().iter().map(|evlr| (evlr, true));
+        for (vlr, is_evlr) in theirs.chain(their_evlrs).filter(|&(vlr, _)| !is_crs(vlr)) {
+            let is_same_key = |other: &Vlr| {
+                other.user_id == vlr.user_id && other.record_id == vlr.record_id
+            };
+            if vlrs.iter().chain(&evlrs).any(|other| {
+                is_same_key(other) && other.data == vlr.data
+            })
+            {
+                continue;
+            }
+            let existing = vlrs.iter_mut().chain(&mut evlrs).find(|other| is_same_key(other));
+            if let Some(existing) = existing {
+                let conflict = ::Error::VlrConflict(vlr.user_id.clone(), vlr.record_id);
+                match strategy {
+                    VlrMergeStrategy::KeepFirst => continue,
+                    VlrMergeStrategy::KeepLast => {
+                        *existing = vlr.clone();
+                        continue;
+                    }
+                    VlrMergeStrategy::Error => return Err(conflict),
+                    VlrMergeStrategy::Deduplicate => {}
+                }
+            }
+            if is_evlr {
+                evlrs.push(vlr.clone());
+            } else {
+                vlrs.push(vlr.clone());
+            }
+        }
+        self.vlrs = vlrs;
+        self.evlrs = evlrs;
+        Ok(())
+    }
+
+    /// Moves any vlrs whose data are too large for a regular vlr into the evlrs.
+    ///
+    /// Returns the user id and record id of each promoted vlr, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    /// let mut builder = Builder::from((1, 4));
+    /// let mut vlr = Vlr::default();
+    /// vlr.user_id = "gadomski".to_string();
+    /// vlr.data = vec![0; 100_000];
+    /// builder.vlrs.push(vlr);
+    /// assert_eq!(vec![("gadomski".to_string(), 0)], builder.promote_large_vlrs());
+    /// assert!(builder.vlrs.is_empty());
+    /// assert_eq!(1, builder.evlrs.len());
+    /// ```
+    pub fn promote_large_vlrs(&mut self) -> Vec<(String, u16)> {
+        let mut promoted = Vec::new();
+        let mut vlrs = Vec::new();
+        for vlr in self.vlrs.drain(..) {
+            if vlr.has_large_data() {
+                promoted.push((vlr.user_id.clone(), vlr.record_id));
+                self.evlrs.push(vlr);
+            } else {
+                vlrs.push(vlr);
+            }
+        }
+        self.vlrs = vlrs;
+        promoted
+    }
+
+    /// Sets the point format from its number, keeping the current number of extra bytes.
+    ///
+    /// Returns an error, and leaves the point format alone, if the number isn't a valid point
+    /// format number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// let mut builder = Builder::default();
+    /// builder.point_format.extra_bytes = 2;
+    /// builder.set_point_format_number(3).unwrap();
+    /// assert_eq!(3, builder.point_format.to_u8().unwrap());
+    /// assert_eq!(2, builder.point_format.extra_bytes);
+    /// assert!(builder.set_point_format_number(11).is_err());
+    /// ```
+    pub fn set_point_format_number(&mut self, n: u8) -> Result<()> {
+        let mut point_format = Format::new(n)?;
+        point_format.extra_bytes = self.point_format.extra_bytes;
+        self.point_format = point_format;
+        Ok(())
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder {
+            auto_promote_large_vlrs: true,
+            bounds: Default::default(),
+            date: Default::default(),
+            evlrs: Default::default(),
+            file_source_id: Default::default(),
+            generating_software: Default::default(),
+            gps_time_type: Default::default(),
+            guid: Default::default(),
+            has_synthetic_return_numbers: Default::default(),
+            number_of_points: Default::default(),
+            number_of_points_by_return: Default::default(),
+            padding: Default::default(),
+            point_format: Default::default(),
+            point_padding: Default::default(),
+            system_identifier: Default::default(),
+            transforms: Default::default(),
+            version: Default::default(),
+            vlr_padding: Default::default(),
+            vlrs: Default::default(),
+            waveform_location: Default::default(),
+        }
     }
 }
 
@@ -246,6 +600,7 @@ impl<V: Into<Version>> From<V> for Builder {
 impl From<Header> for Builder {
     fn from(header: Header) -> Builder {
         Builder {
+            auto_promote_large_vlrs: true,
             bounds: header.bounds,
             date: header.date,
             evlrs: header.evlrs,
@@ -264,10 +619,23 @@ impl From<Header> for Builder {
             version: header.version,
             vlr_padding: header.vlr_padding,
             vlrs: header.vlrs,
+            waveform_location: header.waveform_location,
         }
     }
 }
 
+/// Returns the record ids and data of the coordinate reference system vlrs, sorted so that two
+/// sets of records can be compared no matter their order or whether they're evlrs.
+fn crs_records<'a>(vlrs: &'a [Vlr], evlrs: &'a [Vlr]) -> Vec<(u16, &'a [u8])> {
+    let mut records = vlrs.iter()
+        .chain(evlrs)
+        .filter(|vlr| vlr.user_id == ::geo_keys::USER_ID)
+        .map(|vlr| (vlr.record_id, vlr.data.as_slice()))
+        .collect::<Vec<_>>();
+    records.sort();
+    records
+}
+
 fn number_of_points_hash_map<T: Copy + Into<u64>>(slice: &[T]) -> HashMap<u8, u64> {
     use std::u8;
     assert!(slice.len() < u8::MAX as usize);
@@ -308,6 +676,15 @@ mod tests {
 
     // TODO assert wkt properties
 
+    #[test]
+    fn synthetic_return_numbers_bit() {
+        let mut builder = Builder::from((1, 3));
+        builder.has_synthetic_return_numbers = true;
+        let raw_header = builder.into_header().unwrap().into_raw().unwrap();
+        assert_eq!(8, raw_header.global_encoding & 8);
+        assert!(Builder::new(raw_header).unwrap().has_synthetic_return_numbers);
+    }
+
     #[test]
     fn evlr_downgrade() {
         let mut builder = Builder::from((1, 2));
@@ -328,6 +705,35 @@ mod tests {
         assert_eq!(1, header.evlrs().len());
     }
 
+    #[test]
+    fn auto_promote_large_vlrs() {
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(Vlr::default());
+        let mut vlr = Vlr::default();
+        vlr.data = vec![42; 100_000];
+        builder.vlrs.push(vlr);
+        let mut disabled = builder.clone();
+        disabled.auto_promote_large_vlrs = false;
+        assert!(disabled.into_header().is_err());
+        let (header, promoted) = builder.into_header_with_promoted_vlrs().unwrap();
+        assert_eq!(1, header.vlrs().len());
+        assert_eq!(1, header.evlrs().len());
+        assert_eq!(100_000, header.evlrs()[0].data.len());
+        assert_eq!(vec![(String::new(), 0)], promoted);
+    }
+
+    #[test]
+    fn vlr_too_large() {
+        let mut builder = Builder::from((1, 2));
+        let mut vlr = Vlr::default();
+        vlr.data = vec![0; ::std::u16::MAX as usize + 1];
+        builder.vlrs.push(vlr);
+        match builder.into_header().unwrap_err() {
+            ::Error::Header(Error::VlrTooLarge(n)) => assert_eq!(::std::u16::MAX as usize + 1, n),
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
     #[test]
     fn point_padding_no_evlrs() {
         let mut builder = Builder::from((1, 4));
@@ -345,4 +751,259 @@ mod tests {
         let builder = Builder::from((1, 2));
         assert!(builder.into_header().unwrap().vlr_padding().is_empty());
     }
+
+    #[test]
+    fn waveform_location_required() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(4).unwrap();
+        assert!(builder.clone().into_header().is_err());
+        builder.waveform_location = Some(WaveformLocation::Internal);
+        assert!(builder.into_header().is_ok());
+    }
+
+    #[test]
+    fn waveform_location_without_waveforms() {
+        let mut builder = Builder::from((1, 4));
+        builder.waveform_location = Some(WaveformLocation::External);
+        assert!(builder.into_header().is_err());
+    }
+
+    #[test]
+    fn both_waveform_location_bits() {
+        let mut raw_header = raw::Header {
+            version: (1, 4).into(),
+            point_data_record_format: 4,
+            point_data_record_length: 57,
+            global_encoding: 6,
+            ..Default::default()
+        };
+        assert!(Builder::new(raw_header.clone()).is_err());
+        raw_header.global_encoding = 0;
+        assert!(Builder::new(raw_header.clone()).unwrap().into_header().is_err());
+        raw_header.global_encoding = 4;
+        assert_eq!(
+            Some(WaveformLocation::External),
+            Builder::new(raw_header).unwrap().waveform_location
+        );
+    }
+
+    #[test]
+    fn reserved_fields_las_1_0() {
+        let raw_header = raw::Header {
+            version: (1, 0).into(),
+            file_source_id: 42,
+            global_encoding: 1,
+            ..Default::default()
+        };
+        let header = Builder::new(raw_header).unwrap().into_header().unwrap();
+        assert_eq!(0, header.file_source_id());
+        assert_eq!(GpsTimeType::Week, header.gps_time_type());
+    }
+
+    #[test]
+    fn reserved_fields_las_1_1() {
+        let raw_header = raw::Header {
+            version: (1, 1).into(),
+            file_source_id: 42,
+            global_encoding: 1,
+            ..Default::default()
+        };
+        let header = Builder::new(raw_header).unwrap().into_header().unwrap();
+        assert_eq!(42, header.file_source_id());
+        assert_eq!(GpsTimeType::Week, header.gps_time_type());
+    }
+
+    #[test]
+    fn global_encoding_las_1_2() {
+        let raw_header = raw::Header {
+            version: (1, 2).into(),
+            file_source_id: 42,
+            global_encoding: 1,
+            ..Default::default()
+        };
+        let header = Builder::new(raw_header).unwrap().into_header().unwrap();
+        assert_eq!(42, header.file_source_id());
+        assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+    }
+
+    #[test]
+    fn clear_evlrs_with_point_padding() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr::default());
+        builder.point_padding = vec![0];
+        let mut builder = Builder::from(builder.into_header().unwrap());
+        builder.clear_evlrs();
+        assert!(builder.into_header().unwrap().evlrs().is_empty());
+    }
+
+    #[test]
+    fn match_layout() {
+        use {Point, Vlr, Writer};
+        use std::io::Cursor;
+
+        let mut builder = Builder::from((1, 3));
+        builder.point_format = Format::new(1).unwrap();
+        builder.padding = vec![1, 2, 3];
+        builder.vlrs.push(Vlr {
+            data: vec![4; 10],
+            ..Default::default()
+        });
+        builder.vlr_padding = vec![5; 7];
+        let template = builder.into_header().unwrap();
+
+        let mut files = Vec::new();
+        for (i, generating_software) in vec!["a", "b"].into_iter().enumerate() {
+            let mut builder = Builder::default();
+            builder.generating_software = generating_software.to_string();
+            builder.match_layout(&template);
+            let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+                .unwrap();
+            writer
+                .write(Point {
+                    x: i as f64,
+                    gps_time: Some(0.),
+                    ..Default::default()
+                })
+                .unwrap();
+            files.push(writer.into_inner().unwrap().into_inner());
+        }
+        let raw_headers = files
+            .iter()
+            .map(|file| raw::Header::read_from(&file[..]).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(raw_headers[0].header_size, raw_headers[1].header_size);
+        assert_eq!(
+            raw_headers[0].offset_to_point_data,
+            raw_headers[1].offset_to_point_data
+        );
+        assert_eq!(
+            raw_headers[0].point_data_record_length,
+            raw_headers[1].point_data_record_length
+        );
+        let header_size = raw_headers[0].header_size as usize;
+        let offset = raw_headers[0].offset_to_point_data as usize;
+        assert_eq!(
+            template.clone().into_raw().unwrap().offset_to_point_data as usize,
+            offset
+        );
+        assert_eq!(&files[0][header_size..offset], &files[1][header_size..offset]);
+    }
+
+    fn vlr(user_id: &str, data: u8) -> Vlr {
+        Vlr {
+            user_id: user_id.to_string(),
+            data: vec![data],
+            ..Default::default()
+        }
+    }
+
+    fn merge(strategy: VlrMergeStrategy, user_id: &str) -> (Builder, Result<()>) {
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(vlr(user_id, 1));
+        builder.vlrs.push(vlr("other", 1));
+        let mut other = Builder::from((1, 4));
+        other.vlrs.push(vlr("other", 1));
+        other.evlrs.push(vlr(user_id, 2));
+        other.vlrs.push(vlr("new", 3));
+        let result = builder.merge_vlrs(&other.into_header().unwrap(), strategy);
+        (builder, result)
+    }
+
+    #[test]
+    fn merge_vlrs_keep_first() {
+        let (builder, result) = merge(VlrMergeStrategy::KeepFirst, "gadomski");
+        result.unwrap();
+        assert_eq!(
+            vec![vlr("gadomski", 1), vlr("other", 1), vlr("new", 3)],
+            builder.vlrs
+        );
+        assert!(builder.evlrs.is_empty());
+    }
+
+    #[test]
+    fn merge_vlrs_keep_last() {
+        let (builder, result) = merge(VlrMergeStrategy::KeepLast, "gadomski");
+        result.unwrap();
+        assert_eq!(
+            vec![vlr("gadomski", 2), vlr("other", 1), vlr("new", 3)],
+            builder.vlrs
+        );
+        assert!(builder.evlrs.is_empty());
+    }
+
+    #[test]
+    fn merge_vlrs_error() {
+        let (builder, result) = merge(VlrMergeStrategy::Error, "gadomski");
+        match result {
+            Err(::Error::VlrConflict(ref user_id, 0)) if user_id == "gadomski" => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(vec![vlr("gadomski", 1), vlr("other", 1)], builder.vlrs);
+        assert!(builder.evlrs.is_empty());
+    }
+
+    #[test]
+    fn merge_vlrs_deduplicate() {
+        let (builder, result) = merge(VlrMergeStrategy::Deduplicate, "gadomski");
+        result.unwrap();
+        assert_eq!(
+            vec![vlr("gadomski", 1), vlr("other", 1), vlr("new", 3)],
+            builder.vlrs
+        );
+        assert_eq!(vec![vlr("gadomski", 2)], builder.evlrs);
+
+        let (_, result) = merge(VlrMergeStrategy::Deduplicate, ::geo_keys::USER_ID);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn merge_vlrs_crs() {
+        use geo_keys::{DIRECTORY_RECORD_ID, DOUBLE_PARAMS_RECORD_ID, USER_ID, WKT_RECORD_ID};
+
+        let crs = |record_id, data| Vlr { record_id: record_id, ..vlr(USER_ID, data) };
+        let merge = |strategy| {
+            let mut builder = Builder::from((1, 4));
+            builder.vlrs.push(crs(WKT_RECORD_ID, 1));
+            let mut other = Builder::from((1, 4));
+            other.vlrs.push(crs(DIRECTORY_RECORD_ID, 2));
+            other.vlrs.push(crs(DOUBLE_PARAMS_RECORD_ID, 3));
+            let result = builder.merge_vlrs(&other.into_header().unwrap(), strategy);
+            (builder, result)
+        };
+
+        let (builder, result) = merge(VlrMergeStrategy::KeepFirst);
+        result.unwrap();
+        assert_eq!(vec![crs(WKT_RECORD_ID, 1)], builder.vlrs);
+
+        let (builder, result) = merge(VlrMergeStrategy::KeepLast);
+        result.unwrap();
+        assert_eq!(
+            vec![crs(DIRECTORY_RECORD_ID, 2), crs(DOUBLE_PARAMS_RECORD_ID, 3)],
+            builder.vlrs
+        );
+
+        for &strategy in &[VlrMergeStrategy::Error, VlrMergeStrategy::Deduplicate] {
+            let (builder, result) = merge(strategy);
+            assert!(result.is_err());
+            assert_eq!(vec![crs(WKT_RECORD_ID, 1)], builder.vlrs);
+        }
+    }
+
+    #[test]
+    fn merge_vlrs_same_crs() {
+        use geo_keys::{DIRECTORY_RECORD_ID, USER_ID, WKT_RECORD_ID};
+
+        let crs = |record_id, data| Vlr { record_id: record_id, ..vlr(USER_ID, data) };
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs.push(crs(WKT_RECORD_ID, 1));
+        builder.vlrs.push(crs(DIRECTORY_RECORD_ID, 2));
+        let mut other = Builder::from((1, 4));
+        other.evlrs.push(crs(DIRECTORY_RECORD_ID, 2));
+        other.vlrs.push(crs(WKT_RECORD_ID, 1));
+        builder
+            .merge_vlrs(&other.into_header().unwrap(), VlrMergeStrategy::Error)
+            .unwrap();
+        assert_eq!(2, builder.vlrs.len());
+        assert!(builder.evlrs.is_empty());
+    }
 }
@@ -48,12 +48,16 @@
 
 mod builder;
 
-pub use self::builder::Builder;
+pub use self::builder::{Builder, VlrMergeStrategy};
 
-use {Bounds, GpsTimeType, Point, Result, Transform, Vector, Version, Vlr, raw};
+use {Bounds, GpsTimeType, Point, Result, Transform, Vector, Version, Vlr, Warning, raw};
+use waveform::WaveformLocation;
 use chrono::{Date, Datelike, Utc};
+use extra_bytes::Descriptor;
+use geo_keys::GeoKeyDirectory;
 use point::Format;
 use std::collections::HashMap;
+use std::fmt;
 use std::iter::Chain;
 use std::slice::Iter;
 use utils::FromLasStr;
@@ -63,10 +67,18 @@ quick_error! {
     /// Header-specific errors.
     #[derive(Clone, Copy, Debug)]
     pub enum Error {
+        /// The target version is newer than the header's version.
+        Downgrade(from: Version, to: Version) {
+            description("cannot downgrade to a newer version")
+            display("cannot downgrade from version {} to version {}", from, to)
+        }
         /// The file signature is not LASF.
         FileSignature(b: [u8; 4]) {
             description("the file signature was not LASF")
-            display("the file signature was not LASF: {:?}", b)
+            display(
+                "the file signature was {:?}, not \"LASF\", so this is probably not las data",
+                String::from_utf8_lossy(b)
+            )
         }
         /// The point format is not supported by version.
         Format(version: Version, format: Format) {
@@ -102,6 +114,11 @@ quick_error! {
             description("too many points for this version")
             display("too many points for this version {}: {}", version, n)
         }
+        /// Too many points with the same return number for this version's legacy return counts.
+        TooManyPointsByReturn(return_number: u8, n: u64, version: Version) {
+            description("too many points with the same return number for this version")
+            display("too many points with return number {} for version {}: {} (upgrade to las 1.4 or saturate the count)", return_number, version, n)
+        }
         /// Too many variable length records.
         TooManyVlrs(count: usize) {
             description("too many variable length records")
@@ -112,6 +129,28 @@ quick_error! {
             description("the header size is too small")
             display("the header size is too small: {}", len)
         }
+        /// The version is not a known las version.
+        UnknownVersion(version: Version) {
+            description("the version is not a known las version")
+            display("{} is not a known las version", version)
+        }
+        /// The vlr's data is too large for a regular vlr.
+        VlrTooLarge(len: usize) {
+            description("the vlr data is too large for a regular vlr")
+            display("the vlr data is too large for a regular vlr: {} bytes", len)
+        }
+        /// The waveform location doesn't match the point format.
+        ///
+        /// Formats with waveforms need a location, and formats without waveforms can't have one.
+        WaveformLocation(format: Format, location: Option<WaveformLocation>) {
+            description("the waveform location does not match the point format")
+            display("waveform location {:?} is invalid for point format {}", location, format)
+        }
+        /// Both the internal and external waveform bits are set in the global encoding.
+        WaveformLocationBits(global_encoding: u16) {
+            description("both the internal and external waveform bits are set")
+            display("both the internal and external waveform bits are set in the global encoding: {:#b}", global_encoding)
+        }
         /// Wkt is required for this point format.
         WktRequired(format: Format) {
             description("wkt is required for this point format")
@@ -144,6 +183,7 @@ pub struct Header {
     version: Version,
     vlr_padding: Vec<u8>,
     vlrs: Vec<Vlr>,
+    waveform_location: Option<WaveformLocation>,
 }
 
 /// An iterator over a header's variable length records.
@@ -187,6 +227,17 @@ impl Header {
         self.bounds = Default::default();
     }
 
+    /// Sets the point count and bounds directly, for writers that know them before writing.
+    pub(crate) fn set_number_of_points_and_bounds(&mut self, number_of_points: u64, bounds: Bounds) {
+        self.number_of_points = number_of_points;
+        self.bounds = bounds;
+    }
+
+    /// Replaces the data of one of the vlrs, for writers that update a vlr after writing points.
+    pub(crate) fn set_vlr_data(&mut self, index: usize, data: Vec<u8>) {
+        self.vlrs[index].data = data;
+    }
+
     /// Adds a point to this header, incrementing the point counts and growing the bounds.
     ///
     /// # Examples
@@ -208,6 +259,43 @@ impl Header {
         self.bounds.grow(&point);
     }
 
+    /// Clamps any return counts that don't fit in this version's legacy 32-bit fields.
+    ///
+    /// Before las 1.4, the number of points by return is stored as five `u32`s, so at most
+    /// `u32::MAX` points can share a return number. A header with a larger count can't be
+    /// converted into a raw header; either upgrade to las 1.4, which has 64-bit counts, or use
+    /// this method to saturate the counts at `u32::MAX`. Returns a warning for each clamped
+    /// count. Las 1.4 headers are left untouched, since their legacy fields are zeroed instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let mut header = Header::from((1, 2));
+    /// header.add_point(&Default::default());
+    /// assert!(header.saturate_number_of_points_by_return().is_empty());
+    /// ```
+    pub fn saturate_number_of_points_by_return(&mut self) -> Vec<Warning> {
+        use feature::LargeFiles;
+
+        let mut warnings = Vec::new();
+        if self.version.supports::<LargeFiles>() {
+            return warnings;
+        }
+        for return_number in 1..6 {
+            if let Some(count) = self.number_of_points_by_return.get_mut(&return_number) {
+                if *count > u64::from(u32::MAX) {
+                    warnings.push(Warning::ReturnCountSaturated {
+                        return_number: return_number,
+                        count: *count,
+                    });
+                    *count = u64::from(u32::MAX);
+                }
+            }
+        }
+        warnings
+    }
+
     /// Returns this header's file source id.
     ///
     /// For airborne data, this is often the flight line number.
@@ -254,6 +342,10 @@ impl Header {
 
     /// Returns this header's guid.
     ///
+    /// This is the project id, stored in the four "Project ID - GUID data" fields of the header.
+    /// Its sixteen bytes are kept exactly as they are in the las data, so they round-trip through
+    /// a `Builder` and a `Writer` unchanged.
+    ///
     /// # Examples
     ///
     /// ```
@@ -345,6 +437,26 @@ impl Header {
         self.point_format
     }
 
+    /// Returns true if the point data are laszip compressed.
+    ///
+    /// This crate can't read compressed points, but a header can still be built from the raw
+    /// header of compressed data, e.g. to decide whether to hand the data to a laz decoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use las::{Builder, Header, raw};
+    /// assert!(!Header::default().is_compressed());
+    /// let file = File::open("tests/data/autzen.laz").unwrap();
+    /// let raw_header = raw::Header::read_from(file).unwrap();
+    /// let header = Builder::new(raw_header).unwrap().into_header().unwrap();
+    /// assert!(header.is_compressed());
+    /// ```
+    pub fn is_compressed(&self) -> bool {
+        self.point_format.is_compressed
+    }
+
     /// Returns this header's transforms.
     ///
     /// The transforms are the scales and offsets used to convert floating point numbers to `i16`.
@@ -362,6 +474,45 @@ impl Header {
         self.transforms
     }
 
+    /// Returns the precision of the coordinates on each axis, i.e. the transforms' scales.
+    ///
+    /// Coordinates are stored as integer multiples of the scale, so a scale of 0.001 means that
+    /// the coordinates have a precision of one thousandth of a unit, e.g. millimeters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let precision = Header::default().effective_precision();
+    /// assert_eq!(0.001, precision.x);
+    /// ```
+    pub fn effective_precision(&self) -> Vector<f64> {
+        Vector {
+            x: self.transforms.x.scale,
+            y: self.transforms.y.scale,
+            z: self.transforms.z.scale,
+        }
+    }
+
+    /// Returns true if any axis has a precision of one unit or coarser.
+    ///
+    /// A scale of 1 or more usually means that the coordinates were quantized to whole units
+    /// (e.g. integer meters) by mistake, which throws away most of the detail in lidar data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Transform};
+    /// let mut builder = Builder::default();
+    /// assert!(!builder.clone().into_header().unwrap().has_coarse_precision());
+    /// builder.transforms.z = Transform { scale: 1., offset: 0. };
+    /// assert!(builder.into_header().unwrap().has_coarse_precision());
+    /// ```
+    pub fn has_coarse_precision(&self) -> bool {
+        let precision = self.effective_precision();
+        precision.x >= 1. || precision.y >= 1. || precision.z >= 1.
+    }
+
     /// Returns the bounds of this header.
     ///
     /// The bounds describe the min and max values in each dimension.
@@ -389,6 +540,67 @@ impl Header {
         self.number_of_points
     }
 
+    /// Returns the number of points per unit of area, from the number of points and the x and y
+    /// bounds.
+    ///
+    /// The area is in the squared units of the coordinates. Returns zero if the bounds have no
+    /// area, e.g. if there aren't any points or they're all on a line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let header = Reader::from_path("tests/data/autzen.las").unwrap().header().clone();
+    /// assert!(header.point_density() > 0.);
+    /// ```
+    pub fn point_density(&self) -> f64 {
+        let bounds = self.bounds;
+        let area = (bounds.max.x - bounds.min.x) * (bounds.max.y - bounds.min.y);
+        if area > 0. && area.is_finite() {
+            self.number_of_points as f64 / area
+        } else {
+            0.
+        }
+    }
+
+    /// Returns the average distance between points, the inverse square root of the point density.
+    ///
+    /// Returns zero if the point density is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert_eq!(0., Header::default().point_spacing());
+    /// ```
+    pub fn point_spacing(&self) -> f64 {
+        let density = self.point_density();
+        if density > 0. { 1. / density.sqrt() } else { 0. }
+    }
+
+    /// Returns the number of bytes that las data with this header and `point_count` points will
+    /// take up.
+    ///
+    /// This is the header, the vlrs, the points, and the evlrs, including any padding, i.e.
+    /// everything a `Writer` writes. It can be used to check for enough disk space before starting
+    /// a large write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// let header = Header::default();
+    /// assert_eq!(227 + 20 * 10, header.estimated_file_size(10));
+    /// ```
+    pub fn estimated_file_size(&self, point_count: u64) -> u64 {
+        let vlr_len = self.vlrs.iter().map(|vlr| vlr.len(false) as u64).sum::<u64>();
+        let evlr_len = self.evlrs.iter().map(|evlr| evlr.len(true) as u64).sum::<u64>();
+        u64::from(self.version.header_size()) + self.padding.len() as u64 + vlr_len +
+            self.vlr_padding.len() as u64 +
+            point_count * u64::from(self.point_format.len()) +
+            self.point_padding.len() as u64 + evlr_len
+    }
+
     /// Returns this header's number of points for a given return number.
     ///
     /// Note that return numbers are 1-indexed.
@@ -404,6 +616,32 @@ impl Header {
         self.number_of_points_by_return.get(&n).map(|&n| n)
     }
 
+    /// Returns this header's number of points for every return number that its version records.
+    ///
+    /// There are five counts before las 1.4 and fifteen in las 1.4, and the first count is for
+    /// return number one. Return numbers that the version can't record, e.g. return number six in
+    /// las 1.2, aren't included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert_eq!(vec![0; 5], Header::from((1, 2)).return_counts());
+    /// assert_eq!(vec![0; 15], Header::from((1, 4)).return_counts());
+    /// ```
+    pub fn return_counts(&self) -> Vec<u64> {
+        use feature::LargeFiles;
+
+        let len = if self.version.supports::<LargeFiles>() {
+            15
+        } else {
+            5
+        };
+        (1..len + 1)
+            .map(|n| self.number_of_points_by_return(n).unwrap_or(0))
+            .collect()
+    }
+
     /// Returns a reference to this header's vlr padding.
     ///
     /// These are bytes after the vlrs but before the points. Again, not recommended for use.
@@ -462,6 +700,36 @@ impl Header {
         &self.evlrs
     }
 
+    /// Returns the number of this header's vlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::default();
+    /// builder.vlrs.push(Vlr::default());
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(1, header.vlr_count());
+    /// ```
+    pub fn vlr_count(&self) -> usize {
+        self.vlrs.len()
+    }
+
+    /// Returns the number of this header's extended variable length records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.evlrs.push(Vlr::default());
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(1, header.evlr_count());
+    /// ```
+    pub fn evlr_count(&self) -> usize {
+        self.evlrs.len()
+    }
+
     /// Returns an iterator over all this header's vlrs, both extended and regular.
     ///
     /// # Examples
@@ -477,6 +745,330 @@ impl Header {
         Vlrs(self.vlrs.iter().chain(&self.evlrs))
     }
 
+    /// Returns all vlrs and evlrs with this user id and record id.
+    ///
+    /// Record ids are only unique within a user id, so both must match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(Vlr { user_id: "LASF_Projection".to_string(), record_id: 34735, ..Default::default() });
+    /// builder.evlrs.push(Vlr { user_id: "other".to_string(), record_id: 34735, ..Default::default() });
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(1, header.vlrs_by("LASF_Projection", 34735).len());
+    /// assert!(header.vlrs_by("LASF_Projection", 34736).is_empty());
+    /// ```
+    pub fn vlrs_by(&self, user_id: &str, record_id: u16) -> Vec<&Vlr> {
+        self.all_vlrs()
+            .filter(|vlr| vlr.user_id == user_id && vlr.record_id == record_id)
+            .collect()
+    }
+
+    /// Returns the user id and record id of every key shared by more than one vlr or evlr.
+    ///
+    /// Each key is only returned once, in the order of its first duplicate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Vlr, Builder};
+    /// let mut builder = Builder::default();
+    /// builder.vlrs.push(Vlr::default());
+    /// builder.vlrs.push(Vlr::default());
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(vec![(String::new(), 0)], header.duplicate_vlrs());
+    /// ```
+    pub fn duplicate_vlrs(&self) -> Vec<(String, u16)> {
+        let mut seen = Vec::new();
+        let mut duplicates = Vec::new();
+        for vlr in self.all_vlrs() {
+            let key = (vlr.user_id.clone(), vlr.record_id);
+            if !seen.contains(&key) {
+                seen.push(key);
+            } else if !duplicates.contains(&key) {
+                duplicates.push(key);
+            }
+        }
+        duplicates
+    }
+
+    /// Returns where this header's waveform data packets are stored.
+    ///
+    /// This is `None` for point formats without waveforms. For formats with waveforms, exactly
+    /// one of the global encoding's internal or external waveform bits is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// use las::point::Format;
+    /// use las::waveform::WaveformLocation;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(4).unwrap();
+    /// builder.waveform_location = Some(WaveformLocation::External);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(Some(WaveformLocation::External), header.waveform_location());
+    /// ```
+    pub fn waveform_location(&self) -> Option<WaveformLocation> {
+        self.waveform_location
+    }
+
+    /// Returns the offset from the start of the las data to the waveform data packet record.
+    ///
+    /// This is the offset of the evlr that holds internal waveforms, or `None` if there isn't one.
+    /// It is computed from the evlrs, so it is always consistent with what a `Writer` writes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.evlrs.push(Vlr {
+    ///     user_id: "LASF_Spec".to_string(),
+    ///     record_id: 65535,
+    ///     ..Default::default()
+    /// });
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(Some(375), header.start_of_waveform_data_packet_record().unwrap());
+    /// ```
+    pub fn start_of_waveform_data_packet_record(&self) -> Result<Option<u64>> {
+        let mut start = match self.evlr()? {
+            Some(evlr) => evlr.start_of_first_evlr,
+            None => return Ok(None),
+        };
+        for evlr in &self.evlrs {
+            if evlr.is_waveform_data_packets() {
+                return Ok(Some(start));
+            }
+            start += evlr.len(true) as u64;
+        }
+        Ok(None)
+    }
+
+    /// Returns the descriptors from this header's extra bytes vlr.
+    ///
+    /// Returns an empty vector if there is no extra bytes vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    /// use las::extra_bytes::{DataType, Descriptor};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.vlrs.push(Descriptor::into_vlr(&[Descriptor::new("a", DataType::U8)]).unwrap());
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!("a", header.extra_bytes_descriptors().unwrap()[0].name);
+    /// ```
+    pub fn extra_bytes_descriptors(&self) -> Result<Vec<Descriptor>> {
+        use extra_bytes::{RECORD_ID, USER_ID};
+        self.vlrs_by(USER_ID, RECORD_ID)
+            .first()
+            .map_or_else(|| Ok(Vec::new()), |vlr| Descriptor::from_vlr(vlr))
+    }
+
+    /// Returns the GeoTIFF keys from this header's GeoKeyDirectoryTag vlr.
+    ///
+    /// Double and ascii values are resolved from the GeoDoubleParamsTag and GeoAsciiParamsTag
+    /// vlrs. Returns `None` if there is no GeoKeyDirectoryTag vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+    /// assert_eq!(Some(2994), geo_keys.epsg_code());
+    /// ```
+    pub fn geo_keys(&self) -> Result<Option<GeoKeyDirectory>> {
+        use geo_keys::{ASCII_PARAMS_RECORD_ID, DIRECTORY_RECORD_ID, DOUBLE_PARAMS_RECORD_ID,
+                       USER_ID};
+        let directory = match self.vlrs_by(USER_ID, DIRECTORY_RECORD_ID).first() {
+            Some(&directory) => directory,
+            None => return Ok(None),
+        };
+        GeoKeyDirectory::from_vlrs(
+            directory,
+            self.vlrs_by(USER_ID, DOUBLE_PARAMS_RECORD_ID).first().cloned(),
+            self.vlrs_by(USER_ID, ASCII_PARAMS_RECORD_ID).first().cloned(),
+        ).map(Some)
+    }
+
+    /// Downgrades this header to an older las version, e.g. for legacy tools that need las 1.2.
+    ///
+    /// Evlrs are converted to regular vlrs, except for the waveform data packet record in las 1.3,
+    /// and bits of the global encoding that the older version doesn't have (e.g. synthetic return
+    /// numbers) are cleared. Returns an error if:
+    ///
+    /// - The version is newer than this header's version.
+    /// - The point format can't be represented in the version, e.g. extended formats in las 1.2.
+    /// - There are more points than fit in a 32-bit point count.
+    /// - An evlr is too large to be a regular vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.evlrs.push(Vlr::default());
+    /// let header = builder.into_header().unwrap().downgrade_to((1, 2)).unwrap();
+    /// assert_eq!((1, 2), header.version().into());
+    /// assert_eq!(1, header.vlrs().len());
+    /// assert!(header.evlrs().is_empty());
+    /// ```
+    pub fn downgrade_to<V: Into<Version>>(self, version: V) -> Result<Header> {
+        use feature::{Evlrs, LargeFiles, SyntheticReturnNumbers};
+
+        let version = version.into();
+        if version > self.version {
+            return Err(Error::Downgrade(self.version, version).into());
+        }
+        if !version.supports::<LargeFiles>() && self.number_of_points > u64::from(u32::MAX) {
+            return Err(Error::TooManyPoints(self.number_of_points, version).into());
+        }
+        let mut builder = Builder::from(self);
+        builder.version = version;
+        if !version.supports::<SyntheticReturnNumbers>() {
+            builder.has_synthetic_return_numbers = false;
+        }
+        if !version.supports::<Evlrs>() {
+            builder.point_padding.clear();
+        }
+        builder.into_header()
+    }
+
+    /// Checks this header against a version of the las spec, returning every violation.
+    ///
+    /// Unlike the checks that happen when a header is built or written, which stop at the first
+    /// error, this collects all of the problems as `Warning::NonConforming`s. It checks:
+    ///
+    /// - that the version supports the point format,
+    /// - that the point counts and return numbers fit in the version's fields,
+    /// - that the version supports any evlrs, waveforms, synthetic return numbers, gps standard
+    ///   time, and file source id,
+    /// - that the strings and vlrs fit in their fields,
+    /// - that las 1.0 data have the point data start signature, and
+    /// - that coordinate reference systems use geotiff before las 1.4 and wkt for point formats
+    ///   6 through 10.
+    ///
+    /// Returns an error if the version isn't a known las version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Version};
+    /// use las::point::Format;
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(6).unwrap();
+    /// let header = builder.into_header().unwrap();
+    /// assert!(header.validate_against(Version::new(1, 4)).unwrap().is_empty());
+    /// assert_eq!(1, header.validate_against(Version::new(1, 2)).unwrap().len());
+    /// ```
+    pub fn validate_against(&self, version: Version) -> Result<Vec<Warning>> {
+        use feature::{Evlrs, FileSourceId, GpsStandardTime, LargeFiles, SyntheticReturnNumbers,
+                      Waveforms};
+        use raw::POINT_DATA_START_SIGNATURE;
+
+        if version.major != 1 || version.minor > 4 {
+            return Err(Error::UnknownVersion(version).into());
+        }
+        let mut reasons = Vec::new();
+        if !version.supports_point_format(self.point_format) {
+            reasons.push(format!("{} is not supported", self.point_format));
+        }
+        if !version.supports::<LargeFiles>() {
+            if self.number_of_points > u64::from(u32::MAX) {
+                reasons.push(format!("{} points don't fit in 32 bits", self.number_of_points));
+            }
+            let mut return_numbers = self.number_of_points_by_return.keys().collect::<Vec<_>>();
+            return_numbers.sort();
+            for &return_number in return_numbers {
+                let n = self.number_of_points_by_return[&return_number];
+                if return_number > 5 && n > 0 {
+                    reasons.push(format!("return number {} is above five", return_number));
+                } else if n > u64::from(u32::MAX) {
+                    reasons.push(format!(
+                        "{} points with return number {} don't fit in 32 bits",
+                        n,
+                        return_number
+                    ));
+                }
+            }
+        }
+        let is_waveform_data_packet_record = self.evlrs.len() == 1 &&
+            self.evlrs[0].is_waveform_data_packets();
+        if !self.evlrs.is_empty() && !version.supports::<Evlrs>() &&
+            (!is_waveform_data_packet_record || !version.supports::<Waveforms>())
+        {
+            reasons.push("evlrs are not supported".to_string());
+        }
+        if self.waveform_location.is_some() && !version.supports::<Waveforms>() {
+            reasons.push("waveforms are not supported".to_string());
+        }
+        if self.has_synthetic_return_numbers && !version.supports::<SyntheticReturnNumbers>() {
+            reasons.push("synthetic return numbers are not supported".to_string());
+        }
+        if self.gps_time_type == GpsTimeType::Standard && !version.supports::<GpsStandardTime>() {
+            reasons.push("gps standard time is not supported".to_string());
+        }
+        if self.file_source_id != 0 && !version.supports::<FileSourceId>() {
+            reasons.push("the file source id is reserved".to_string());
+        }
+        for &(field, value) in &[
+            ("system identifier", &self.system_identifier),
+            ("generating software", &self.generating_software),
+        ]
+        {
+            if value.len() > 32 {
+                reasons.push(format!("the {} is longer than 32 bytes", field));
+            }
+        }
+        for vlr in &self.vlrs {
+            if vlr.user_id.len() > 16 {
+                reasons.push(format!("vlr user id {} is longer than 16 bytes", vlr.user_id));
+            }
+            if vlr.description.len() > 32 {
+                reasons.push(format!(
+                    "vlr description {} is longer than 32 bytes",
+                    vlr.description
+                ));
+            }
+            if vlr.data.len() > usize::from(u16::MAX) {
+                reasons.push(format!("vlr {} has too much data for a vlr", vlr.user_id));
+            }
+        }
+        let n = self.vlr_padding.len();
+        if version.requires_point_data_start_signature() &&
+            (n < 2 || self.vlr_padding[n - 2..] != POINT_DATA_START_SIGNATURE)
+        {
+            reasons.push("the point data start signature is missing".to_string());
+        }
+        let has_geotiff = !self.vlrs_by(::geo_keys::USER_ID, ::geo_keys::DIRECTORY_RECORD_ID)
+            .is_empty();
+        let has_wkt = !self.vlrs_by(::geo_keys::USER_ID, ::geo_keys::WKT_RECORD_ID)
+            .is_empty();
+        if has_wkt && !version.supports::<Evlrs>() {
+            reasons.push("wkt coordinate reference systems are not supported".to_string());
+        }
+        if has_geotiff && self.point_format.is_extended {
+            reasons.push(format!("{} requires wkt, not geotiff", self.point_format));
+        }
+        Ok(
+            reasons
+                .into_iter()
+                .map(|reason| {
+                    Warning::NonConforming {
+                        version: version,
+                        reason: reason,
+                    }
+                })
+                .collect(),
+        )
+    }
+
     /// Converts this header into a raw header.
     ///
     /// # Examples
@@ -515,8 +1107,7 @@ impl Header {
             min_y: self.bounds.min.y,
             max_z: self.bounds.max.z,
             min_z: self.bounds.min.z,
-            // FIXME waveforms
-            start_of_waveform_data_packet_record: None,
+            start_of_waveform_data_packet_record: self.start_of_waveform_data_packet_record()?,
             evlr: self.evlr()?,
             large_file: self.large_file()?,
             padding: self.padding,
@@ -528,6 +1119,9 @@ impl Header {
         if self.has_synthetic_return_numbers {
             bits |= 8;
         }
+        if let Some(waveform_location) = self.waveform_location {
+            bits |= waveform_location.global_encoding_bit();
+        }
         if self.point_format.is_extended {
             bits |= 16;
         }
@@ -614,7 +1208,7 @@ impl Header {
             } else if i > 0 {
                 if n > u64::from(u32::MAX) {
                     if !self.version.supports::<LargeFiles>() {
-                        return Err(Error::TooManyPoints(n, self.version).into());
+                        return Err(Error::TooManyPointsByReturn(i, n, self.version).into());
                     }
                 } else {
                     number_of_points_by_return[i as usize - 1] = n as u32;
@@ -683,10 +1277,30 @@ impl Default for Header {
             version: Default::default(),
             vlr_padding: Vec::new(),
             vlrs: Vec::new(),
+            waveform_location: None,
         }
     }
 }
 
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bounds = self.bounds;
+        write!(
+            f,
+            "las {}, {}, {} points, bounds ({}, {}, {}) to ({}, {}, {})",
+            self.version,
+            self.point_format,
+            self.number_of_points,
+            bounds.min.x,
+            bounds.min.y,
+            bounds.min.z,
+            bounds.max.x,
+            bounds.max.y,
+            bounds.max.z
+        )
+    }
+}
+
 impl<V: Into<Version>> From<V> for Header {
     fn from(version: V) -> Header {
         Builder::from(version).into_header().expect(
@@ -706,6 +1320,243 @@ impl<'a> Iterator for Vlrs<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn estimated_file_size() {
+        use std::io::Cursor;
+        use {Point, Writer};
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(3).unwrap();
+        builder.vlrs.push(Vlr {
+            data: vec![1; 10],
+            ..Default::default()
+        });
+        builder.evlrs.push(Vlr {
+            data: vec![2; 100],
+            ..Default::default()
+        });
+        builder.vlr_padding = vec![0; 3];
+        let header = builder.into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+        for _ in 0..5 {
+            writer
+                .write(Point {
+                    gps_time: Some(1.),
+                    color: Some(Default::default()),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let len = writer.into_inner().unwrap().into_inner().len() as u64;
+        assert_eq!(len, header.estimated_file_size(5));
+    }
+
+    #[test]
+    fn point_density() {
+        let mut header = Header::default();
+        header.number_of_points = 400;
+        header.bounds = Bounds {
+            min: Vector {
+                x: 0.,
+                y: 0.,
+                z: 0.,
+            },
+            max: Vector {
+                x: 10.,
+                y: 10.,
+                z: 1.,
+            },
+        };
+        assert_eq!(4., header.point_density());
+        assert_eq!(0.5, header.point_spacing());
+        header.bounds.max.y = 0.;
+        assert_eq!(0., header.point_density());
+        assert_eq!(0., header.point_spacing());
+    }
+
+    #[test]
+    fn downgrade_to() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(3).unwrap();
+        builder.has_synthetic_return_numbers = true;
+        builder.evlrs.push(Vlr::default());
+        builder.point_padding = vec![0; 4];
+        let mut header = builder.into_header().unwrap();
+        header.number_of_points = 42;
+        let header = header.downgrade_to((1, 2)).unwrap();
+        assert_eq!(Version::new(1, 2), header.version());
+        assert_eq!(Format::new(3).unwrap(), header.point_format());
+        assert_eq!(42, header.number_of_points());
+        assert!(!header.has_synthetic_return_numbers());
+        assert_eq!(1, header.vlrs().len());
+        assert!(header.evlrs().is_empty());
+        assert!(header.point_padding().is_empty());
+        assert_eq!(0, header.into_raw().unwrap().global_encoding);
+    }
+
+    #[test]
+    fn downgrade_to_too_many_points() {
+        use std::u32;
+
+        let mut header = Header::from((1, 4));
+        header.number_of_points = u64::from(u32::MAX) + 1;
+        match header.downgrade_to((1, 2)) {
+            Err(::Error::Header(Error::TooManyPoints(_, _))) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn downgrade_to_extended_format() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let header = builder.into_header().unwrap();
+        assert!(header.downgrade_to((1, 2)).is_err());
+    }
+
+    #[test]
+    fn downgrade_to_newer_version() {
+        assert!(Header::from((1, 2)).downgrade_to((1, 4)).is_err());
+    }
+
+    #[test]
+    fn return_counts() {
+        use std::io::Cursor;
+        use {Point, Reader, Writer};
+
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let return_counts = reader.header().return_counts();
+        assert_eq!(5, return_counts.len());
+        assert_eq!(
+            reader.header().number_of_points(),
+            return_counts.iter().sum::<u64>()
+        );
+        assert_eq!(
+            reader.header().number_of_points_by_return(1).unwrap(),
+            return_counts[0]
+        );
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        for &return_number in &[1, 2, 2, 15] {
+            writer
+                .write(Point {
+                    return_number: return_number,
+                    number_of_returns: 15,
+                    gps_time: Some(0.),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let return_counts = reader.header().return_counts();
+        assert_eq!(15, return_counts.len());
+        assert_eq!(&[1, 2, 0], &return_counts[..3]);
+        assert_eq!(1, return_counts[14]);
+    }
+
+    #[test]
+    fn validate_against() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.has_synthetic_return_numbers = true;
+        builder.file_source_id = 1;
+        builder.evlrs.push(Vlr::default());
+        builder.vlrs.push(Vlr {
+            user_id: ::geo_keys::USER_ID.to_string(),
+            record_id: ::geo_keys::DIRECTORY_RECORD_ID,
+            ..Default::default()
+        });
+        let mut header = builder.into_header().unwrap();
+        header.number_of_points = 1;
+        header.number_of_points_by_return.insert(7, 1);
+        let reasons = header
+            .validate_against(Version::new(1, 0))
+            .unwrap()
+            .into_iter()
+            .map(|warning| match warning {
+                Warning::NonConforming { version, reason } => {
+                    assert_eq!(Version::new(1, 0), version);
+                    reason
+                }
+                warning => panic!("unexpected warning: {:?}", warning),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "point format 6 is not supported",
+                "return number 7 is above five",
+                "evlrs are not supported",
+                "synthetic return numbers are not supported",
+                "the file source id is reserved",
+                "the point data start signature is missing",
+                "point format 6 requires wkt, not geotiff",
+            ],
+            reasons
+        );
+        assert_eq!(5, header.validate_against(Version::new(1, 2)).unwrap().len());
+    }
+
+    #[test]
+    fn validate_against_conforming() {
+        assert!(
+            Header::from((1, 0))
+                .validate_against(Version::new(1, 0))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            Header::from((1, 2))
+                .validate_against(Version::new(1, 4))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn validate_against_unknown_version() {
+        match Header::from((1, 4)).validate_against(Version::new(1, 5)) {
+            Err(::Error::Header(Error::UnknownVersion(_))) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn start_of_waveform_data_packet_record_after_other_evlrs() {
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        builder.evlrs.push(Vlr {
+            user_id: "LASF_Spec".to_string(),
+            record_id: 65535,
+            ..Default::default()
+        });
+        let header = builder.into_header().unwrap();
+        assert_eq!(
+            Some(375 + 60 + 3),
+            header.start_of_waveform_data_packet_record().unwrap()
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut header = Header::from((1, 4));
+        header.add_point(&Point {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+            ..Default::default()
+        });
+        assert_eq!(
+            "las 1.4, point format 0, 1 points, bounds (1, 2, 3) to (1, 2, 3)",
+            header.to_string()
+        );
+    }
+
     #[test]
     fn number_of_points_by_return_zero_return_number() {
         let mut header = Header::default();
@@ -744,6 +1595,63 @@ mod tests {
         assert!(header.into_raw().is_err());
     }
 
+    #[test]
+    fn vlrs_by_matches_user_id_and_record_id() {
+        let mut builder = Builder::from((1, 4));
+        for (user_id, data) in vec![("LASF_Projection", 1), ("not_projection", 2)] {
+            builder.vlrs.push(Vlr {
+                user_id: user_id.to_string(),
+                record_id: 34735,
+                data: vec![data],
+                ..Default::default()
+            });
+        }
+        let header = builder.into_header().unwrap();
+        let vlrs = header.vlrs_by("LASF_Projection", 34735);
+        assert_eq!(1, vlrs.len());
+        assert_eq!(vec![1], vlrs[0].data);
+        let vlrs = header.vlrs_by("not_projection", 34735);
+        assert_eq!(1, vlrs.len());
+        assert_eq!(vec![2], vlrs[0].data);
+    }
+
+    #[test]
+    fn number_of_points_by_return_overflow() {
+        let mut header = Header::from((1, 2));
+        header.number_of_points = 1;
+        header.number_of_points_by_return.insert(2, u64::from(u32::MAX) + 1);
+        match header.clone().into_raw() {
+            Err(::Error::Header(Error::TooManyPointsByReturn(2, n, _))) => {
+                assert_eq!(u64::from(u32::MAX) + 1, n)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            vec![
+                Warning::ReturnCountSaturated {
+                    return_number: 2,
+                    count: u64::from(u32::MAX) + 1,
+                },
+            ],
+            header.saturate_number_of_points_by_return()
+        );
+        assert_eq!(
+            [0, u32::MAX, 0, 0, 0],
+            header.into_raw().unwrap().number_of_points_by_return
+        );
+    }
+
+    #[test]
+    fn number_of_points_by_return_overflow_las_1_4() {
+        let mut header = Header::from((1, 4));
+        header.number_of_points_by_return.insert(2, u64::from(u32::MAX) + 1);
+        assert!(header.saturate_number_of_points_by_return().is_empty());
+        assert_eq!(
+            u64::from(u32::MAX) + 1,
+            header.number_of_points_by_return(2).unwrap()
+        );
+    }
+
     #[test]
     fn synchronize_legacy_fields() {
         let mut header = Header::from((1, 4));
@@ -0,0 +1,269 @@
+//! Locate the parts of laszip compressed las data.
+//!
+//! This crate can't decompress laszip data yet, but it can find the pieces that a decompressor
+//! needs. Laszip stores its settings in a vlr with the user id `laszip encoded` and the record id
+//! 22204, and (for chunked compressors) a chunk table that records where each chunk of compressed
+//! points starts.
+//!
+//! The chunk table is found through the i64 that laszip writes immediately before the compressed
+//! points, at the offset to point data:
+//!
+//! - Laszip 2.x (point formats 0 through 5, the pointwise chunked compressor) and laszip 3.x (point
+//!   formats 6 through 10, the layered chunked compressor) both write the chunk table after the
+//!   points, and fill in that i64 with its offset once the points are written.
+//! - If the writer couldn't seek back to fill it in, e.g. because it was writing to a stream, the
+//!   i64 is -1. The offset is then stored in the last eight bytes of the file, after any evlrs.
+//! - Laszip 1.x's pointwise compressor has no chunks, so there is neither an i64 nor a chunk table.
+//!
+//! ```
+//! use std::fs::File;
+//! use las::laz::{self, Placement};
+//!
+//! let location = laz::locate_chunk_table(File::open("tests/data/autzen.laz").unwrap())
+//!     .unwrap()
+//!     .unwrap();
+//! assert_eq!(2476, location.offset);
+//! assert_eq!(Placement::BeforePoints, location.placement);
+//! ```
+
+use {Result, raw};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
+use std::io::{Read, Seek, SeekFrom};
+
+/// The user id of the laszip vlr.
+pub const USER_ID: &str = "laszip encoded";
+
+/// The record id of the laszip vlr.
+pub const RECORD_ID: u16 = 22204;
+
+/// The length of the fixed part of the laszip vlr, before its item records.
+const LASZIP_VLR_LEN: usize = 34;
+
+quick_error! {
+    /// Laszip errors.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Error {
+        /// The chunk table offset is neither -1 nor a valid offset.
+        ChunkTableOffset(offset: i64) {
+            description("invalid chunk table offset")
+            display("invalid chunk table offset: {}", offset)
+        }
+        /// The compressor is not known.
+        Compressor(n: u16) {
+            description("unknown laszip compressor")
+            display("unknown laszip compressor: {}", n)
+        }
+        /// The laszip vlr is too short.
+        Length(len: usize) {
+            description("the laszip vlr is too short")
+            display("the laszip vlr is too short: {} bytes", len)
+        }
+        /// The las data are compressed, but there is no laszip vlr.
+        NoLaszipVlr {
+            description("the las data has no laszip vlr")
+        }
+        /// The las data are not compressed.
+        NotCompressed {
+            description("the las data are not laszip compressed")
+        }
+    }
+}
+
+/// How the points were compressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compressor {
+    /// The points aren't compressed.
+    None,
+    /// Laszip 1.x's compressor, without chunks or a chunk table.
+    Pointwise,
+    /// Laszip 2.x's compressor, for point formats 0 through 5.
+    PointwiseChunked,
+    /// Laszip 3.x's compressor, for point formats 6 through 10.
+    LayeredChunked,
+}
+
+/// The settings in the laszip vlr that are needed to find the compressed chunks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LaszipVlr {
+    /// The compressor.
+    pub compressor: Compressor,
+    /// The laszip version that wrote the data, as (major, minor).
+    pub version: (u8, u8),
+    /// The number of points in each chunk, or `u32::MAX` for variable-sized chunks.
+    pub chunk_size: u32,
+}
+
+/// Where the chunk table's offset was found.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placement {
+    /// The offset was in the i64 before the point data.
+    BeforePoints,
+    /// The i64 before the point data was -1, and the offset was in the last eight bytes.
+    EndOfFile,
+}
+
+/// The location of a laszip chunk table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChunkTableLocation {
+    /// The offset of the chunk table from the start of the las data.
+    pub offset: u64,
+    /// Where the offset was found.
+    pub placement: Placement,
+}
+
+impl Compressor {
+    fn new(n: u16) -> Result<Compressor> {
+        match n {
+            0 => Ok(Compressor::None),
+            1 => Ok(Compressor::Pointwise),
+            2 => Ok(Compressor::PointwiseChunked),
+            3 => Ok(Compressor::LayeredChunked),
+            _ => Err(Error::Compressor(n).into()),
+        }
+    }
+
+    /// Returns true if this compressor writes chunks and a chunk table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::laz::Compressor;
+    /// assert!(Compressor::LayeredChunked.is_chunked());
+    /// assert!(!Compressor::Pointwise.is_chunked());
+    /// ```
+    pub fn is_chunked(&self) -> bool {
+        match *self {
+            Compressor::PointwiseChunked | Compressor::LayeredChunked => true,
+            Compressor::None | Compressor::Pointwise => false,
+        }
+    }
+}
+
+impl LaszipVlr {
+    /// Parses the laszip vlr's data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::laz::{Compressor, LaszipVlr};
+    /// let mut data = vec![0; 34];
+    /// data[0] = 2;
+    /// data[4] = 2;
+    /// data[12] = 100;
+    /// let laszip_vlr = LaszipVlr::new(&data).unwrap();
+    /// assert_eq!(Compressor::PointwiseChunked, laszip_vlr.compressor);
+    /// assert_eq!(100, laszip_vlr.chunk_size);
+    /// ```
+    pub fn new(data: &[u8]) -> Result<LaszipVlr> {
+        if data.len() < LASZIP_VLR_LEN {
+            return Err(Error::Length(data.len()).into());
+        }
+        Ok(LaszipVlr {
+            compressor: Compressor::new(LittleEndian::read_u16(&data[0..2]))?,
+            version: (data[4], data[5]),
+            chunk_size: LittleEndian::read_u32(&data[12..16]),
+        })
+    }
+}
+
+/// Reads the laszip vlr from compressed las data.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use las::laz::{self, Compressor};
+/// let laszip_vlr = laz::read_laszip_vlr(File::open("tests/data/autzen.laz").unwrap()).unwrap();
+/// assert_eq!(Compressor::PointwiseChunked, laszip_vlr.compressor);
+/// assert_eq!(50_000, laszip_vlr.chunk_size);
+/// ```
+pub fn read_laszip_vlr<R: Read + Seek>(mut read: R) -> Result<LaszipVlr> {
+    read.seek(SeekFrom::Start(0))?;
+    let header = raw::Header::read_from(&mut read)?;
+    if header.point_data_record_format & 0x80 == 0 {
+        return Err(Error::NotCompressed.into());
+    }
+    read.seek(SeekFrom::Start(u64::from(header.header_size)))?;
+    for _ in 0..header.number_of_variable_length_records {
+        let vlr = raw::Vlr::read_from(&mut read, false)?;
+        if vlr.record_id == RECORD_ID && is_user_id(&vlr.user_id) {
+            return LaszipVlr::new(&vlr.data);
+        }
+    }
+    Err(Error::NoLaszipVlr.into())
+}
+
+/// Locates the chunk table of compressed las data.
+///
+/// The offset is read from the i64 before the point data. If that is -1, this falls back to the
+/// last eight bytes of the file, not to an evlr: laszip never stores the chunk table or its offset
+/// in an evlr, so there's no evlr to look for. Returns `None` if the compressor doesn't write a
+/// chunk table.
+///
+/// Since this crate can't decompress laszip data, `Reader` doesn't use this. It's for callers
+/// that hand the compressed points to a decompressor.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use las::laz;
+/// let location = laz::locate_chunk_table(File::open("tests/data/autzen.laz").unwrap()).unwrap();
+/// assert!(location.is_some());
+/// ```
+pub fn locate_chunk_table<R: Read + Seek>(mut read: R) -> Result<Option<ChunkTableLocation>> {
+    let laszip_vlr = read_laszip_vlr(&mut read)?;
+    if !laszip_vlr.compressor.is_chunked() {
+        return Ok(None);
+    }
+    read.seek(SeekFrom::Start(0))?;
+    let header = raw::Header::read_from(&mut read)?;
+    read.seek(SeekFrom::Start(u64::from(header.offset_to_point_data)))?;
+    let offset = read.read_i64::<LittleEndian>()?;
+    let (offset, placement) = if offset == -1 {
+        read.seek(SeekFrom::End(-8))?;
+        (read.read_i64::<LittleEndian>()?, Placement::EndOfFile)
+    } else {
+        (offset, Placement::BeforePoints)
+    };
+    if offset <= i64::from(header.offset_to_point_data) {
+        return Err(Error::ChunkTableOffset(offset).into());
+    }
+    Ok(Some(ChunkTableLocation {
+        offset: offset as u64,
+        placement: placement,
+    }))
+}
+
+fn is_user_id(user_id: &[u8]) -> bool {
+    let len = user_id.iter().position(|&b| b == 0).unwrap_or(user_id.len());
+    &user_id[..len] == USER_ID.as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::{Cursor, Write};
+
+    #[test]
+    fn end_of_file_placement() {
+        let mut las = Vec::new();
+        File::open("tests/data/autzen.laz")
+            .unwrap()
+            .read_to_end(&mut las)
+            .unwrap();
+        let offset_to_point_data = LittleEndian::read_u32(&las[96..100]) as usize;
+        LittleEndian::write_i64(&mut las[offset_to_point_data..offset_to_point_data + 8], -1);
+        las.write_all(&[172, 9, 0, 0, 0, 0, 0, 0]).unwrap();
+        let location = locate_chunk_table(Cursor::new(las)).unwrap().unwrap();
+        assert_eq!(2476, location.offset);
+        assert_eq!(Placement::EndOfFile, location.placement);
+    }
+
+    #[test]
+    fn not_compressed() {
+        let file = File::open("tests/data/autzen.las").unwrap();
+        assert!(locate_chunk_table(file).is_err());
+    }
+}
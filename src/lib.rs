@@ -115,40 +115,62 @@
 extern crate byteorder;
 extern crate chrono;
 extern crate num;
+#[cfg(feature = "proj")]
+extern crate proj;
 #[macro_use]
 extern crate quick_error;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "tokio")]
+extern crate tokio;
 extern crate uuid;
 
+#[cfg(feature = "tokio")]
+pub mod async_reader;
+pub mod buffering_writer;
+pub mod extra_bytes;
 pub mod feature;
+pub mod geo_keys;
 pub mod header;
+pub mod laz;
 pub mod point;
 pub mod raw;
 pub mod reader;
 pub mod vlr;
+pub mod waveform;
 pub mod writer;
 
 mod bounds;
 mod color;
 mod error;
 mod gps_time_type;
+mod statistics;
 mod transform;
+mod transformer;
 mod utils;
 mod vector;
 mod version;
+mod warning;
 
+#[cfg(feature = "tokio")]
+pub use async_reader::AsyncReader;
 pub use bounds::Bounds;
+pub use buffering_writer::BufferingWriter;
 pub use color::Color;
-pub use error::Error;
+pub use error::{Error, Phase};
 pub use feature::Feature;
 pub use gps_time_type::GpsTimeType;
-pub use header::{Header, Builder};
+pub use header::{Header, Builder, VlrMergeStrategy};
 pub use point::Point;
-pub use reader::Reader;
+pub use reader::{Reader, peek_version, points_from_bytes};
+pub use statistics::{PercentileMode, Percentiles, Statistics};
 pub use transform::Transform;
+pub use transformer::Transformer;
 pub use vector::Vector;
 pub use version::Version;
 pub use vlr::Vlr;
-pub use writer::Writer;
+pub use warning::Warning;
+pub use writer::{Writer, filter_file, patch_bounds, roundtrip, validate_points, write_points};
 
 /// Crate-specific result type.
 pub type Result<T> = std::result::Result<T, Error>;
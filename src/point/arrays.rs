@@ -0,0 +1,127 @@
+use {Color, Transform, Vector, raw};
+
+/// Points stored as a struct of arrays, one `Vec` per attribute.
+///
+/// Bulk operations over one attribute, e.g. computing bounds or filtering by height, only touch
+/// the memory for that attribute, which is much friendlier to the cache (and to SIMD) than
+/// iterating over `Point`s.
+///
+/// The coordinate, intensity, return, classification, overlap, scan angle, user data, and point
+/// source id arrays are populated for every point format. The others are only populated if the point
+/// format has them, and are empty otherwise:
+///
+/// - `gps_times` for formats 1, 3, 4, 5, 6, 7, 8, 9, and 10,
+/// - `colors` for formats 2, 3, 5, 7, 8, and 10, and
+/// - `nirs` for formats 8 and 10.
+///
+/// Create them with `Reader::read_soa`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PointArrays {
+    /// The x coordinates.
+    pub xs: Vec<f64>,
+    /// The y coordinates.
+    pub ys: Vec<f64>,
+    /// The z coordinates.
+    pub zs: Vec<f64>,
+    /// The intensities.
+    pub intensities: Vec<u16>,
+    /// The return numbers.
+    pub return_numbers: Vec<u8>,
+    /// The numbers of returns.
+    pub number_of_returns: Vec<u8>,
+    /// The classification codes.
+    ///
+    /// As with `Point::classification`, overlap points in the legacy formats are reported as
+    /// unclassified (1), not as class 12, since the overlap class replaces their classification.
+    /// Use `overlaps` to find them.
+    pub classifications: Vec<u8>,
+    /// Whether each point is an overlap point, as with `Point::is_overlap`.
+    pub overlaps: Vec<bool>,
+    /// The scan angles, in degrees.
+    pub scan_angles: Vec<f32>,
+    /// The user data.
+    pub user_data: Vec<u8>,
+    /// The point source ids.
+    pub point_source_ids: Vec<u16>,
+    /// The gps times, if the point format has them.
+    pub gps_times: Vec<f64>,
+    /// The colors, if the point format has them.
+    pub colors: Vec<Color>,
+    /// The near infrared values, if the point format has them.
+    pub nirs: Vec<u16>,
+}
+
+impl PointArrays {
+    /// Returns the number of points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::PointArrays;
+    /// assert_eq!(0, PointArrays::default().len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    /// Returns true if there aren't any points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::PointArrays;
+    /// assert!(PointArrays::default().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    pub(crate) fn with_capacity(n: usize) -> PointArrays {
+        PointArrays {
+            xs: Vec::with_capacity(n),
+            ys: Vec::with_capacity(n),
+            zs: Vec::with_capacity(n),
+            intensities: Vec::with_capacity(n),
+            return_numbers: Vec::with_capacity(n),
+            number_of_returns: Vec::with_capacity(n),
+            classifications: Vec::with_capacity(n),
+            overlaps: Vec::with_capacity(n),
+            scan_angles: Vec::with_capacity(n),
+            user_data: Vec::with_capacity(n),
+            point_source_ids: Vec::with_capacity(n),
+            gps_times: Vec::new(),
+            colors: Vec::new(),
+            nirs: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, mut raw_point: raw::Point, transforms: Vector<Transform>) {
+        self.xs.push(transforms.x.direct(raw_point.x));
+        self.ys.push(transforms.y.direct(raw_point.y));
+        self.zs.push(transforms.z.direct(raw_point.z));
+        self.intensities.push(raw_point.intensity);
+        self.return_numbers.push(raw_point.flags.return_number());
+        self.number_of_returns.push(raw_point.flags.number_of_returns());
+        self.overlaps.push(raw_point.flags.is_overlap());
+        raw_point.flags.clear_overlap_class();
+        self.classifications.push(
+            raw_point
+                .flags
+                .to_classification()
+                .expect("Overlap classification should have been cleared")
+                .into(),
+        );
+        self.scan_angles.push(raw_point.scan_angle.into());
+        self.user_data.push(raw_point.user_data);
+        self.point_source_ids.push(raw_point.point_source_id);
+        if let Some(gps_time) = raw_point.gps_time {
+            self.gps_times.push(gps_time);
+        }
+        if let Some(color) = raw_point.color {
+            self.colors.push(color);
+        }
+        if let Some(nir) = raw_point.nir {
+            self.nirs.push(nir);
+        }
+    }
+}
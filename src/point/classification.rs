@@ -92,6 +92,42 @@ impl Classification {
             _ => unreachable!(),
         })
     }
+
+    /// Returns the name of this classification as given in the las spec.
+    ///
+    /// Reserved and user definable classifications don't have a name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// assert_eq!(Some("ground"), Classification::Ground.name());
+    /// assert_eq!(None, Classification::UserDefinable(64).name());
+    /// ```
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            Classification::CreatedNeverClassified => Some("created, never classified"),
+            Classification::Unclassified => Some("unclassified"),
+            Classification::Ground => Some("ground"),
+            Classification::LowVegetation => Some("low vegetation"),
+            Classification::MediumVegetation => Some("medium vegetation"),
+            Classification::HighVegetation => Some("high vegetation"),
+            Classification::Building => Some("building"),
+            Classification::LowPoint => Some("low point"),
+            Classification::ModelKeyPoint => Some("model key point"),
+            Classification::Water => Some("water"),
+            Classification::Rail => Some("rail"),
+            Classification::RoadSurface => Some("road surface"),
+            Classification::WireGuard => Some("wire guard"),
+            Classification::WireConductor => Some("wire conductor"),
+            Classification::TransmissionTower => Some("transmission tower"),
+            Classification::WireStructureConnector => Some("wire structure connector"),
+            Classification::BridgeDeck => Some("bridge deck"),
+            Classification::HighNoise => Some("high noise"),
+            Classification::Reserved(_) |
+            Classification::UserDefinable(_) => None,
+        }
+    }
 }
 
 impl From<Classification> for u8 {
@@ -64,6 +64,9 @@ pub struct Format {
 impl Format {
     /// Creates a new point format from a u8.
     ///
+    /// If bit 7 is set, the point format is laszip compressed and the remaining bits are the
+    /// point format number.
+    ///
     /// # Examples
     ///
     /// ```
@@ -77,8 +80,14 @@ impl Format {
     /// assert!(format.has_color);
     ///
     /// assert!(Format::new(11).is_err());
+    ///
+    /// let format = Format::new(0x83).unwrap();
+    /// assert!(format.is_compressed);
+    /// assert!(format.has_color);
     /// ```
     pub fn new(n: u8) -> Result<Format> {
+        let is_compressed = n & IS_COMPRESSED_MASK == IS_COMPRESSED_MASK;
+        let n = n & !IS_COMPRESSED_MASK;
         if n > 10 {
             Err(Error::FormatNumber(n).into())
         } else {
@@ -89,7 +98,7 @@ impl Format {
                 has_nir: NIR_FORMATS.contains(&n),
                 is_extended: n >= 6,
                 extra_bytes: 0,
-                is_compressed: n & IS_COMPRESSED_MASK == IS_COMPRESSED_MASK,
+                is_compressed: is_compressed,
             })
         }
     }
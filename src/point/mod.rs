@@ -12,17 +12,28 @@
 //! Point coordinates (x, y, and z) are stored as f64, and are the final coordinates after the
 //! scale and offset from the header are applied.
 
+mod arrays;
 mod classification;
 mod format;
+mod quantized;
 mod scan_direction;
 
+pub use self::arrays::PointArrays;
 pub use self::classification::Classification;
 pub use self::format::Format;
+pub use self::quantized::QuantizedPoint;
 pub use self::scan_direction::ScanDirection;
 
-use {Color, Result, Transform, Vector};
+use {Color, Header, Result, Transform, Vector};
+#[cfg(feature = "rand")]
+use Bounds;
+use extra_bytes::{self, Descriptor, Value};
 use raw;
+#[cfg(feature = "rand")]
+use rand::Rng;
 use raw::point::Waveform;
+use std::io::{Read, Seek};
+use waveform::WaveformReader;
 
 quick_error! {
     /// Point-specific errors
@@ -33,6 +44,11 @@ quick_error! {
             description("invalid classification")
             display("invalid classification: {}", n)
         }
+        /// The point has a different number of extra bytes than the format.
+        ExtraBytesLength(len: usize, expected: u16) {
+            description("the point has a different number of extra bytes than the format")
+            display("the point has {} extra bytes, but the format has {}", len, expected)
+        }
         /// This is an invalid format.
         ///
         /// It has a combination of options that can't exist.
@@ -49,11 +65,20 @@ quick_error! {
         OverlapClassification {
             description("Overlap points are handled by the `is_overlap` member of `las::Point`, not by classifications")
         }
+        /// This point does not have a waveform.
+        NoWaveform {
+            description("this point does not have a waveform")
+        }
         /// This is not a valid return number.
         ReturnNumber(n: u8, version: Option<::Version>) {
             description("invalid return number")
             display("invalid return number: {} (for version: {:?})", n, version)
         }
+        /// The scan angle is out of the range that the point format can store.
+        ScanAngle(angle: f32, format: Format) {
+            description("the scan angle is out of range for the point format")
+            display("the scan angle {} is out of range for point format {}", angle, format)
+        }
         /// This is not a valid scanner channel
         ScannerChannel(n: u8) {
             description("invalid scanner channel")
@@ -62,6 +87,16 @@ quick_error! {
     }
 }
 
+/// What to do with a point's extra bytes when converting it to a point format with a different
+/// number of extra bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtraBytesConversion {
+    /// Return an error.
+    Error,
+    /// Truncate the extra bytes, or pad them with zeros.
+    Truncate,
+}
+
 /// A three dimensional point.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Point {
@@ -105,6 +140,10 @@ pub struct Point {
     pub is_withheld: bool,
 
     /// Is this an overlap point?
+    ///
+    /// This is the same for every point format. Legacy formats (zero through five) don't have an
+    /// overlap bit, so they are read and written with the overlap classification (12) instead.
+    /// See `Classification` for details.
     pub is_overlap: bool,
 
     /// The channel of the scanner, used only in multi-channel systems.
@@ -140,6 +179,13 @@ pub struct Point {
     ///
     /// These can have structure and meaning, but for now they don't.
     pub extra_bytes: Vec<u8>,
+
+    /// The undecoded bytes of this point's record.
+    ///
+    /// This is only set for points that were read from las data with an unknown point format,
+    /// see `reader::Options::read_unknown_point_formats`. All of the other attributes of those
+    /// points are left at their defaults. The raw record is never written.
+    pub raw_record: Option<Vec<u8>>,
 }
 
 impl Point {
@@ -182,10 +228,14 @@ impl Point {
             waveform: raw_point.waveform,
             nir: raw_point.nir,
             extra_bytes: raw_point.extra_bytes,
+            raw_record: None,
         }
     }
     /// Creates a raw las point from this point.
     ///
+    /// Returns an error if any coordinate is NaN or infinite, or can't be represented with the
+    /// transforms.
+    ///
     /// # Examples
     ///
     /// ```
@@ -193,7 +243,35 @@ impl Point {
     /// let point = Point::default();
     /// let raw_point = point.into_raw(Default::default()).unwrap();
     /// ```
-    pub fn into_raw(self, transforms: Vector<Transform>) -> Result<raw::Point> {
+    pub fn into_raw(mut self, transforms: Vector<Transform>) -> Result<raw::Point> {
+        let extra_bytes = ::std::mem::take(&mut self.extra_bytes);
+        self.to_raw_with_extra_bytes(transforms, extra_bytes)
+    }
+
+    /// Creates a raw las point from a borrowed point.
+    ///
+    /// This is like `into_raw`, but it leaves this point intact, so it doesn't need to be cloned
+    /// first. Only the extra bytes are copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// let point = Point { x: 1., ..Default::default() };
+    /// let raw_point = point.to_raw(Default::default()).unwrap();
+    /// assert_eq!(1000, raw_point.x);
+    /// assert_eq!(1., point.x);
+    /// ```
+    pub fn to_raw(&self, transforms: Vector<Transform>) -> Result<raw::Point> {
+        self.to_raw_with_extra_bytes(transforms, self.extra_bytes.clone())
+    }
+
+    fn to_raw_with_extra_bytes(
+        &self,
+        transforms: Vector<Transform>,
+        extra_bytes: Vec<u8>,
+    ) -> Result<raw::Point> {
+        self.verify_finite()?;
         Ok(raw::Point {
             x: transforms.x.inverse(self.x)?,
             y: transforms.y.inverse(self.y)?,
@@ -207,10 +285,38 @@ impl Point {
             color: self.color,
             waveform: self.waveform,
             nir: self.nir,
-            extra_bytes: self.extra_bytes,
+            extra_bytes: extra_bytes,
         })
     }
 
+    pub(crate) fn verify_finite(&self) -> Result<()> {
+        for &(axis, n) in &[('x', self.x), ('y', self.y), ('z', self.z)] {
+            if !n.is_finite() {
+                return Err(::Error::NonFiniteCoordinate(axis, n));
+            }
+        }
+        Ok(())
+    }
+
+    /// Quantizes this point's coordinates with the transforms, returning a point that can be
+    /// hashed and compared for equality.
+    ///
+    /// Two points that quantize to the same integer coordinates (and have the same attributes)
+    /// are equal as `QuantizedPoint`s, even if their floating point coordinates differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// let transforms = Default::default();
+    /// let a = Point { x: 1.0001, ..Default::default() }.quantize(transforms).unwrap();
+    /// let b = Point { x: 1.0002, ..Default::default() }.quantize(transforms).unwrap();
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn quantize(&self, transforms: Vector<Transform>) -> Result<QuantizedPoint> {
+        self.to_raw(transforms).map(QuantizedPoint::new)
+    }
+
     /// Creates the flags bytes for use in a raw point.
     ///
     /// # Examples
@@ -283,12 +389,610 @@ impl Point {
             self.nir.is_some() == format.has_nir &&
             self.extra_bytes.len() == format.extra_bytes as usize
     }
+
+    /// Returns a human-readable, multi-line description of this point.
+    ///
+    /// Only the attributes that the format has are described, e.g. color is skipped for format
+    /// 0. Coordinates are printed with the three decimal places of the default transforms, and the
+    /// classification includes its name if it has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// use las::point::Format;
+    ///
+    /// let point = Point { x: 1., y: 2., z: 3., ..Default::default() };
+    /// let description = point.describe(Format::new(0).unwrap());
+    /// assert!(description.starts_with("x: 1.000\ny: 2.000\nz: 3.000\n"));
+    /// assert!(!description.contains("color"));
+    /// ```
+    pub fn describe(&self, format: Format) -> String {
+        self.describe_with_transforms(format, &Default::default())
+    }
+
+    /// Returns a human-readable, multi-line description of this point, with coordinates printed
+    /// with as many decimal places as the transforms' scales imply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Transform, Vector};
+    /// use las::point::Format;
+    ///
+    /// let point = Point { x: 1., y: 2., z: 3., ..Default::default() };
+    /// let transform = Transform { scale: 0.01, offset: 0. };
+    /// let transforms = Vector { x: transform, y: transform, z: transform };
+    /// let description = point.describe_with_transforms(Format::new(0).unwrap(), &transforms);
+    /// assert!(description.starts_with("x: 1.00\ny: 2.00\nz: 3.00\n"));
+    /// ```
+    pub fn describe_with_transforms(
+        &self,
+        format: Format,
+        transforms: &Vector<Transform>,
+    ) -> String {
+        use std::fmt::Write;
+
+        let mut s = String::new();
+        let _ = writeln!(s, "x: {:.*}", decimal_places(transforms.x.scale), self.x);
+        let _ = writeln!(s, "y: {:.*}", decimal_places(transforms.y.scale), self.y);
+        let _ = writeln!(s, "z: {:.*}", decimal_places(transforms.z.scale), self.z);
+        let _ = writeln!(s, "intensity: {}", self.intensity);
+        let _ = writeln!(
+            s,
+            "return: {} of {}",
+            self.return_number,
+            self.number_of_returns
+        );
+        let _ = writeln!(s, "scan direction: {:?}", self.scan_direction);
+        let _ = writeln!(s, "edge of flight line: {}", self.is_edge_of_flight_line);
+        let classification = u8::from(self.classification);
+        match self.classification.name() {
+            Some(name) => {
+                let _ = writeln!(s, "classification: {} ({})", classification, name);
+            }
+            None => {
+                let _ = writeln!(s, "classification: {}", classification);
+            }
+        }
+        let _ = writeln!(s, "synthetic: {}", self.is_synthetic);
+        let _ = writeln!(s, "key point: {}", self.is_key_point);
+        let _ = writeln!(s, "withheld: {}", self.is_withheld);
+        if format.is_extended {
+            let _ = writeln!(s, "overlap: {}", self.is_overlap);
+            let _ = writeln!(s, "scanner channel: {}", self.scanner_channel);
+        }
+        let _ = writeln!(s, "scan angle: {}", self.scan_angle);
+        let _ = writeln!(s, "user data: {}", self.user_data);
+        let _ = writeln!(s, "point source id: {}", self.point_source_id);
+        if format.has_gps_time {
+            if let Some(gps_time) = self.gps_time {
+                let _ = writeln!(s, "gps time: {}", gps_time);
+            }
+        }
+        if format.has_color {
+            if let Some(color) = self.color {
+                let _ = writeln!(
+                    s,
+                    "color: ({}, {}, {})",
+                    color.red,
+                    color.green,
+                    color.blue
+                );
+            }
+        }
+        if format.has_nir {
+            if let Some(nir) = self.nir {
+                let _ = writeln!(s, "nir: {}", nir);
+            }
+        }
+        if format.has_waveform {
+            if let Some(ref waveform) = self.waveform {
+                let _ = writeln!(
+                    s,
+                    "waveform: descriptor {}, {} bytes at offset {}",
+                    waveform.wave_packet_descriptor_index,
+                    waveform.waveform_packet_size_in_bytes,
+                    waveform.byte_offset_to_waveform_data
+                );
+            }
+        }
+        if format.extra_bytes > 0 && !self.extra_bytes.is_empty() {
+            let _ = writeln!(s, "extra bytes: {:?}", self.extra_bytes);
+        }
+        s
+    }
+
+    /// Converts this point to match a point format.
+    ///
+    /// Attributes that the format doesn't have are dropped, and attributes that the format has but
+    /// this point doesn't are set to their defaults. If this point has the same number of extra
+    /// bytes as the format, they are carried over unchanged. Otherwise, `extra_bytes` decides
+    /// whether this is an error or the extra bytes are truncated (or zero-padded). On error, the
+    /// point is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// use las::point::{ExtraBytesConversion, Format};
+    ///
+    /// let mut format = Format::new(3).unwrap();
+    /// format.extra_bytes = 2;
+    /// let mut point = Point {
+    ///     gps_time: Some(42.),
+    ///     color: Some(Default::default()),
+    ///     extra_bytes: vec![1, 2],
+    ///     ..Default::default()
+    /// };
+    /// assert!(point.matches(format));
+    ///
+    /// let mut format = Format::new(6).unwrap();
+    /// format.extra_bytes = 2;
+    /// point.convert_to(format, ExtraBytesConversion::Error).unwrap();
+    /// assert!(point.matches(format));
+    /// assert_eq!(None, point.color);
+    /// assert_eq!(vec![1, 2], point.extra_bytes);
+    ///
+    /// format.extra_bytes = 1;
+    /// assert!(point.convert_to(format, ExtraBytesConversion::Error).is_err());
+    /// point.convert_to(format, ExtraBytesConversion::Truncate).unwrap();
+    /// assert_eq!(vec![1], point.extra_bytes);
+    /// ```
+    pub fn convert_to(&mut self, format: Format, extra_bytes: ExtraBytesConversion) -> Result<()> {
+        let len = format.extra_bytes as usize;
+        if self.extra_bytes.len() != len {
+            match extra_bytes {
+                ExtraBytesConversion::Error => {
+                    return Err(
+                        Error::ExtraBytesLength(self.extra_bytes.len(), format.extra_bytes).into(),
+                    )
+                }
+                ExtraBytesConversion::Truncate => self.extra_bytes.resize(len, 0),
+            }
+        }
+        self.gps_time = if format.has_gps_time {
+            Some(self.gps_time.unwrap_or_default())
+        } else {
+            None
+        };
+        self.color = if format.has_color {
+            Some(self.color.unwrap_or_default())
+        } else {
+            None
+        };
+        self.waveform = if format.has_waveform {
+            Some(self.waveform.unwrap_or_default())
+        } else {
+            None
+        };
+        self.nir = if format.has_nir {
+            Some(self.nir.unwrap_or_default())
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    /// Removes this point's color, returning it.
+    ///
+    /// Use this to match a point format without color, without a full `convert_to`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Color, Point};
+    /// let mut point = Point { color: Some(Color::new(1, 2, 3)), ..Default::default() };
+    /// assert_eq!(Some(Color::new(1, 2, 3)), point.drop_color());
+    /// assert_eq!(None, point.color);
+    /// assert_eq!(None, point.drop_color());
+    /// ```
+    pub fn drop_color(&mut self) -> Option<Color> {
+        self.color.take()
+    }
+
+    /// Makes sure that this point has a color, and returns a mutable reference to it.
+    ///
+    /// Use this to match a point format with color, without a full `convert_to`. If the point
+    /// doesn't already have a color, it gets the default (black) color. An existing color is left
+    /// alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Color, Point};
+    /// let mut point = Point::default();
+    /// point.with_color().red = 42;
+    /// assert_eq!(Some(Color::new(42, 0, 0)), point.color);
+    /// assert_eq!(42, point.with_color().red);
+    /// ```
+    pub fn with_color(&mut self) -> &mut Color {
+        if self.color.is_none() {
+            self.color = Some(Color::default());
+        }
+        self.color.as_mut().expect("the color was just set")
+    }
+
+    /// Returns a copy of this point without any of the optional attributes, so it matches point
+    /// format zero.
+    ///
+    /// The gps time, color, waveform, near infrared value, and extra bytes are removed. Everything
+    /// else is kept, so a point with attributes that only fit in the extended formats, e.g. a
+    /// return number above five, still can't be written as format zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// use las::point::Format;
+    /// let point = Point { gps_time: Some(42.), intensity: 7, ..Default::default() };
+    /// let stripped = point.strip_to_base();
+    /// assert!(stripped.matches(Format::new(0).unwrap()));
+    /// assert_eq!(7, stripped.intensity);
+    /// ```
+    pub fn strip_to_base(&self) -> Point {
+        Point {
+            gps_time: None,
+            color: None,
+            waveform: None,
+            nir: None,
+            extra_bytes: Vec::new(),
+            raw_record: None,
+            ..self.clone()
+        }
+    }
+
+    /// Creates a random point inside of the bounds that can be written with the point format.
+    ///
+    /// Every attribute is random, but within the range that the format can store, e.g. return
+    /// numbers are at most five and classifications at most 31 for the legacy formats. The point
+    /// has exactly the optional attributes that the format has, so it `matches` the format. The
+    /// waveform, if there is one, is the default waveform. Requires the `rand` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate las;
+    /// extern crate rand;
+    /// # fn main() {
+    /// use std::io::Cursor;
+    /// use las::{Bounds, Builder, Point, Vector, Writer};
+    /// use las::point::Format;
+    ///
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 100., y: 100., z: 10. },
+    /// };
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.point_format = Format::new(8).unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+    ///     .unwrap();
+    /// let mut rng = rand::thread_rng();
+    /// for _ in 0..100 {
+    ///     let point = Point::random_in(bounds, writer.header().point_format(), &mut rng);
+    ///     writer.write(point).unwrap();
+    /// }
+    /// writer.close().unwrap();
+    /// assert_eq!(100, writer.header().number_of_points());
+    /// # }
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn random_in<R: Rng>(bounds: Bounds, format: Format, rng: &mut R) -> Point {
+        fn coordinate<R: Rng>(min: f64, max: f64, rng: &mut R) -> f64 {
+            if min < max { rng.gen_range(min, max) } else { min }
+        }
+
+        let (max_returns, max_classification) = if format.is_extended {
+            (15, 255)
+        } else {
+            (5, 31)
+        };
+        let number_of_returns = rng.gen_range(1, max_returns + 1);
+        let mut classification = rng.gen_range(0, max_classification + 1) as u8;
+        if classification == 12 {
+            classification = 1;
+        }
+        Point {
+            x: coordinate(bounds.min.x, bounds.max.x, rng),
+            y: coordinate(bounds.min.y, bounds.max.y, rng),
+            z: coordinate(bounds.min.z, bounds.max.z, rng),
+            intensity: rng.gen(),
+            return_number: rng.gen_range(1, number_of_returns + 1),
+            number_of_returns: number_of_returns,
+            scan_direction: if rng.gen() {
+                ScanDirection::LeftToRight
+            } else {
+                ScanDirection::RightToLeft
+            },
+            is_edge_of_flight_line: rng.gen(),
+            classification: Classification::new(classification)
+                .expect("the overlap classification was replaced"),
+            is_synthetic: rng.gen(),
+            is_key_point: rng.gen(),
+            is_withheld: rng.gen(),
+            is_overlap: format.is_extended && rng.gen(),
+            scanner_channel: if format.is_extended {
+                rng.gen_range(0, 4)
+            } else {
+                0
+            },
+            scan_angle: f32::from(rng.gen_range(-90i8, 91)),
+            user_data: rng.gen(),
+            point_source_id: rng.gen(),
+            gps_time: if format.has_gps_time {
+                Some(rng.gen_range(0., 1e9))
+            } else {
+                None
+            },
+            color: if format.has_color {
+                Some(Color::new(rng.gen(), rng.gen(), rng.gen()))
+            } else {
+                None
+            },
+            waveform: if format.has_waveform {
+                Some(Waveform::default())
+            } else {
+                None
+            },
+            nir: if format.has_nir { Some(rng.gen()) } else { None },
+            extra_bytes: (0..format.extra_bytes).map(|_| rng.gen()).collect(),
+            raw_record: None,
+        }
+    }
+
+    /// Decodes one of this point's extra bytes values, as described by the header.
+    ///
+    /// Returns `None` if there is no descriptor with that name, or if this point's extra bytes
+    /// can't be decoded by the descriptor. The header's descriptors are parsed on every call, so
+    /// if you're decoding many values, prefer `Header::extra_bytes_descriptors` and
+    /// `Descriptor::decode`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Point};
+    /// use las::extra_bytes::{DataType, Descriptor, Value};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// let mut descriptor = Descriptor::new("reflectance", DataType::I16);
+    /// descriptor.scale = Some([0.01, 0., 0.]);
+    /// builder.vlrs.push(Descriptor::into_vlr(&[descriptor]).unwrap());
+    /// let header = builder.into_header().unwrap();
+    ///
+    /// let point = Point { extra_bytes: vec![0xFF, 0xFF], ..Default::default() };
+    /// assert_eq!(Some(Value::F64(-0.01)), point.extra_byte_value("reflectance", &header));
+    /// assert_eq!(None, point.extra_byte_value("amplitude", &header));
+    /// ```
+    pub fn extra_byte_value(&self, name: &str, header: &Header) -> Option<Value> {
+        let descriptors = header.extra_bytes_descriptors().ok()?;
+        let (start, descriptor) = find_descriptor(&descriptors, name)?;
+        self.extra_bytes
+            .get(start..)
+            .and_then(|bytes| descriptor.decode(bytes).ok())
+    }
+
+    /// Encodes one of this point's extra bytes values, as described by the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Point};
+    /// use las::extra_bytes::{DataType, Descriptor, Value};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// let descriptors = [Descriptor::new("a", DataType::U8), Descriptor::new("b", DataType::U8)];
+    /// builder.vlrs.push(Descriptor::into_vlr(&descriptors).unwrap());
+    /// let header = builder.into_header().unwrap();
+    ///
+    /// let mut point = Point { extra_bytes: vec![0, 0], ..Default::default() };
+    /// point.set_extra_byte_value("b", Value::U8(42), &header).unwrap();
+    /// assert_eq!(vec![0, 42], point.extra_bytes);
+    /// assert!(point.set_extra_byte_value("c", Value::U8(42), &header).is_err());
+    /// ```
+    pub fn set_extra_byte_value(&mut self, name: &str, value: Value, header: &Header) -> Result<()> {
+        let descriptors = header.extra_bytes_descriptors()?;
+        let (start, descriptor) = find_descriptor(&descriptors, name).ok_or_else(|| {
+            extra_bytes::Error::NotFound(name.to_string())
+        })?;
+        let len = self.extra_bytes.len();
+        let bytes = self.extra_bytes.get_mut(start..).ok_or_else(|| {
+            extra_bytes::Error::TooShort(name.to_string(), len)
+        })?;
+        descriptor.encode(&value, bytes)
+    }
+
+    /// Reads this point's raw waveform samples from an external waveform data packet file.
+    ///
+    /// Only external (`.wdp`) waveforms are supported. See the `waveform` module for the
+    /// difference between internal and external waveform data packets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::Point;
+    /// use las::waveform::WaveformReader;
+    /// let mut wdp = WaveformReader::new(Cursor::new(Vec::new()));
+    /// assert!(Point::default().read_waveform(&mut wdp).is_err());
+    /// ```
+    pub fn read_waveform<R: Read + Seek>(&self, wdp: &mut WaveformReader<R>) -> Result<Vec<u8>> {
+        let waveform = self.waveform.ok_or(Error::NoWaveform)?;
+        wdp.read_packet(
+            waveform.byte_offset_to_waveform_data,
+            waveform.waveform_packet_size_in_bytes,
+        )
+    }
+}
+
+fn find_descriptor<'a>(descriptors: &'a [Descriptor], name: &str) -> Option<(usize, &'a Descriptor)> {
+    let mut start = 0;
+    for descriptor in descriptors {
+        if descriptor.name == name {
+            return Some((start, descriptor));
+        }
+        start += descriptor.len();
+    }
+    None
+}
+
+fn decimal_places(scale: f64) -> usize {
+    if scale > 0. && scale < 1. {
+        (-scale.log10() - 1e-9).ceil() as usize
+    } else {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn describe_respects_format() {
+        let transform = Transform {
+            scale: 0.001,
+            offset: 0.,
+        };
+        let transforms = Vector {
+            x: transform,
+            y: transform,
+            z: transform,
+        };
+        let point = Point {
+            x: 1.5,
+            classification: Classification::Ground,
+            gps_time: Some(42.),
+            color: Some(Color::new(1, 2, 3)),
+            ..Default::default()
+        };
+        let description = point.describe_with_transforms(Format::new(3).unwrap(), &transforms);
+        assert!(description.contains("x: 1.500\n"));
+        assert!(description.contains("classification: 2 (ground)\n"));
+        assert!(description.contains("gps time: 42\n"));
+        assert!(description.contains("color: (1, 2, 3)\n"));
+        assert!(!description.contains("nir"));
+        assert!(!description.contains("scanner channel"));
+        let description = point.describe(Format::new(0).unwrap());
+        assert!(description.contains("x: 1.500\n"));
+        assert!(!description.contains("gps time"));
+        assert!(!description.contains("color"));
+    }
+
+    #[test]
+    fn convert_to_passes_color_through() {
+        let mut point = Point {
+            gps_time: Some(1.),
+            color: Some(Color::new(1, 2, 3)),
+            ..Default::default()
+        };
+        point
+            .convert_to(Format::new(7).unwrap(), ExtraBytesConversion::Error)
+            .unwrap();
+        assert_eq!(Some(Color::new(1, 2, 3)), point.color);
+        point
+            .convert_to(Format::new(1).unwrap(), ExtraBytesConversion::Error)
+            .unwrap();
+        assert_eq!(None, point.color);
+    }
+
+    #[test]
+    fn drop_color() {
+        let mut point = Point {
+            color: Some(Color::new(1, 2, 3)),
+            ..Default::default()
+        };
+        assert!(point.matches(Format::new(2).unwrap()));
+        assert_eq!(Some(Color::new(1, 2, 3)), point.drop_color());
+        assert!(point.matches(Format::new(0).unwrap()));
+    }
+
+    #[test]
+    fn strip_to_base() {
+        let point = Point {
+            x: 1.,
+            gps_time: Some(42.),
+            color: Some(Color::new(1, 2, 3)),
+            waveform: Some(Default::default()),
+            nir: Some(4),
+            extra_bytes: vec![5],
+            ..Default::default()
+        };
+        let stripped = point.strip_to_base();
+        assert!(stripped.matches(Format::new(0).unwrap()));
+        assert_eq!(
+            Point {
+                x: 1.,
+                ..Default::default()
+            },
+            stripped
+        );
+    }
+
+    #[test]
+    fn with_color() {
+        let mut point = Point::default();
+        assert!(point.matches(Format::new(0).unwrap()));
+        *point.with_color() = Color::new(1, 2, 3);
+        assert!(point.matches(Format::new(2).unwrap()));
+        point.with_color().blue = 4;
+        assert_eq!(Some(Color::new(1, 2, 4)), point.color);
+    }
+
+    #[test]
+    fn convert_to_preserves_extra_bytes() {
+        let mut point = Point {
+            gps_time: Some(42.),
+            color: Some(Color::new(1, 2, 3)),
+            extra_bytes: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let mut format = Format::new(6).unwrap();
+        format.extra_bytes = 3;
+        point.convert_to(format, ExtraBytesConversion::Error).unwrap();
+        assert!(point.matches(format));
+        assert_eq!(Some(42.), point.gps_time);
+        assert_eq!(vec![1, 2, 3], point.extra_bytes);
+    }
+
+    #[test]
+    fn convert_to_different_extra_bytes() {
+        let mut point = Point {
+            extra_bytes: vec![1, 2],
+            ..Default::default()
+        };
+        let mut format = Format::new(0).unwrap();
+        format.extra_bytes = 3;
+        assert!(point.convert_to(format, ExtraBytesConversion::Error).is_err());
+        assert_eq!(vec![1, 2], point.extra_bytes);
+        point.convert_to(format, ExtraBytesConversion::Truncate).unwrap();
+        assert_eq!(vec![1, 2, 0], point.extra_bytes);
+    }
+
+    #[test]
+    fn scan_direction_toggle() {
+        let mut point = Point::default();
+        for &scan_direction in &[
+            ScanDirection::LeftToRight,
+            ScanDirection::RightToLeft,
+            ScanDirection::LeftToRight,
+        ]
+        {
+            point.scan_direction = scan_direction;
+            let three_bytes = point.flags().unwrap();
+            let (a, b) = three_bytes.to_two_bytes().unwrap();
+            for &flags in &[three_bytes, raw::point::Flags::TwoByte(a, b)] {
+                let raw_point = raw::Point {
+                    flags: flags,
+                    ..Default::default()
+                };
+                assert_eq!(
+                    scan_direction,
+                    Point::new(raw_point, Default::default()).scan_direction
+                );
+            }
+        }
+    }
+
     #[test]
     fn flags_invalid_return_number() {
         assert!(
@@ -333,6 +1037,51 @@ mod tests {
         let point = Point::new(raw_point, Default::default());
         assert_eq!(Classification::Unclassified, point.classification);
         assert!(point.is_overlap);
+    }
+
+    #[test]
+    fn overlap_legacy_formats() {
+        use raw::point::Flags;
+
+        let raw_point = raw::Point {
+            flags: Flags::TwoByte(0, 28),
+            ..Default::default()
+        };
+        let point = Point::new(raw_point, Default::default());
+        assert_eq!(Classification::Reserved(28), point.classification);
+        assert!(!point.is_overlap);
+
+        let point = Point {
+            classification: Classification::Ground,
+            is_overlap: true,
+            ..Default::default()
+        };
+        let raw_point = point.into_raw(Default::default()).unwrap();
+        assert_eq!((0, 12), raw_point.flags.to_two_bytes().unwrap());
+        assert!(Point::new(raw_point, Default::default()).is_overlap);
+    }
+
+    #[test]
+    fn overlap_extended_formats() {
+        use raw::point::Flags;
+
+        let raw_point = raw::Point {
+            flags: Flags::ThreeByte(0, 0, 12),
+            ..Default::default()
+        };
+        let point = Point::new(raw_point, Default::default());
+        assert_eq!(Classification::Unclassified, point.classification);
+        assert!(point.is_overlap);
 
+        let point = Point {
+            classification: Classification::Ground,
+            is_overlap: true,
+            ..Default::default()
+        };
+        let raw_point = point.into_raw(Default::default()).unwrap();
+        assert_eq!(Flags::ThreeByte(0, 8, 2), raw_point.flags);
+        let point = Point::new(raw_point, Default::default());
+        assert_eq!(Classification::Ground, point.classification);
+        assert!(point.is_overlap);
     }
 }
@@ -0,0 +1,162 @@
+use raw;
+use std::hash::{Hash, Hasher};
+
+/// A point whose coordinates have been quantized to the integers that are stored in las data.
+///
+/// Unlike `Point`, which has floating point coordinates, a quantized point implements `Eq` and
+/// `Hash`, so it can be put in a `HashSet` or used as a `HashMap` key, e.g. to remove duplicate
+/// points. Two quantized points are equal if they would be written as the same bytes. Floating
+/// point attributes, like gps time, are compared by their bits, so `NaN` is equal to itself.
+///
+/// Create one with `Point::quantize`.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use las::Point;
+///
+/// let transforms = Default::default();
+/// let a = Point { x: 1.0001, ..Default::default() };
+/// let b = Point { x: 1.0002, ..Default::default() };
+/// let mut set = HashSet::new();
+/// set.insert(a.quantize(transforms).unwrap());
+/// assert!(set.contains(&b.quantize(transforms).unwrap()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct QuantizedPoint {
+    raw: raw::Point,
+}
+
+type Key<'a> = (
+    (i32, i32, i32, u16),
+    (u8, u8, u8),
+    (i16, u8, u16),
+    Option<u64>,
+    Option<(u16, u16, u16)>,
+    Option<(u8, u64, u32, u32, u32, u32, u32)>,
+    Option<u16>,
+    &'a [u8],
+);
+
+impl QuantizedPoint {
+    /// Creates a quantized point from a raw point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::QuantizedPoint;
+    /// let point = QuantizedPoint::new(Default::default());
+    /// ```
+    pub fn new(raw: raw::Point) -> QuantizedPoint {
+        QuantizedPoint { raw: raw }
+    }
+
+    /// Returns a reference to the underlying raw point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// let point = Point { x: 1., ..Default::default() };
+    /// let quantized = point.quantize(Default::default()).unwrap();
+    /// assert_eq!(1000, quantized.raw().x);
+    /// ```
+    pub fn raw(&self) -> &raw::Point {
+        &self.raw
+    }
+
+    /// Converts this quantized point into a raw point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Point;
+    /// let quantized = Point::default().quantize(Default::default()).unwrap();
+    /// let raw_point = quantized.into_raw();
+    /// ```
+    pub fn into_raw(self) -> raw::Point {
+        self.raw
+    }
+
+    fn key(&self) -> Key<'_> {
+        let raw = &self.raw;
+        (
+            (raw.x, raw.y, raw.z, raw.intensity),
+            raw.flags.into(),
+            (raw.scan_angle.into(), raw.user_data, raw.point_source_id),
+            raw.gps_time.map(f64::to_bits),
+            raw.color.map(|color| (color.red, color.green, color.blue)),
+            raw.waveform.map(|waveform| {
+                (
+                    waveform.wave_packet_descriptor_index,
+                    waveform.byte_offset_to_waveform_data,
+                    waveform.waveform_packet_size_in_bytes,
+                    waveform.return_point_waveform_location.to_bits(),
+                    waveform.x_t.to_bits(),
+                    waveform.y_t.to_bits(),
+                    waveform.z_t.to_bits(),
+                )
+            }),
+            raw.nir,
+            &raw.extra_bytes,
+        )
+    }
+}
+
+impl PartialEq for QuantizedPoint {
+    fn eq(&self, other: &QuantizedPoint) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for QuantizedPoint {}
+
+impl Hash for QuantizedPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Point;
+    use std::collections::HashSet;
+
+    #[test]
+    fn dedup() {
+        let transforms = Default::default();
+        let points = vec![
+            Point { x: 1., gps_time: Some(2.), ..Default::default() },
+            Point { x: 1.0004, gps_time: Some(2.), ..Default::default() },
+            Point { x: 1., gps_time: Some(3.), ..Default::default() },
+            Point { x: 1., gps_time: Some(::std::f64::NAN), ..Default::default() },
+            Point { x: 1., gps_time: Some(::std::f64::NAN), ..Default::default() },
+        ];
+        let set = points
+            .iter()
+            .map(|point| point.quantize(transforms).unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(3, set.len());
+    }
+
+    #[test]
+    fn integer_fields() {
+        let transforms = Default::default();
+        let a = Point::default().quantize(transforms).unwrap();
+        let b = Point {
+            intensity: 1,
+            ..Default::default()
+        }.quantize(transforms)
+            .unwrap();
+        let c = Point {
+            extra_bytes: vec![1],
+            ..Default::default()
+        }.quantize(transforms)
+            .unwrap();
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a, Point::default().quantize(transforms).unwrap());
+    }
+}
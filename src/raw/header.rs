@@ -11,6 +11,31 @@ use std::io::{Read, Write};
 /// The documentation for each member is taken directly from the las 1.2 spec, except in cases
 /// where the field's usage has changed, in which case notes about its uses over versions are
 /// included, with particular attention paid to the latest version, 1.4.
+///
+/// Most users should use `las::Header`, which keeps its fields consistent with the vlrs and
+/// points. A raw header is an escape hatch when you need control over the exact bytes, e.g.
+/// reserved bits or header padding. Use `las::Header::into_raw` and `las::Header::from_raw` to
+/// move between the two. Since a `Writer` re-writes the header when it closes, make raw edits to
+/// the finished las data:
+///
+/// ```
+/// use std::io::{Cursor, Seek, SeekFrom};
+/// use las::{Reader, Writer, raw};
+///
+/// let mut writer = Writer::default();
+/// writer.write(Default::default()).unwrap();
+/// let mut cursor = writer.into_inner().unwrap();
+///
+/// let mut raw_header = raw::Header::read_from(&mut cursor).unwrap();
+/// raw_header.global_encoding |= 1 << 15; // a reserved bit
+/// cursor.seek(SeekFrom::Start(0)).unwrap();
+/// raw_header.write_to(&mut cursor).unwrap();
+///
+/// cursor.seek(SeekFrom::Start(0)).unwrap();
+/// assert_eq!(1 << 15, raw::Header::read_from(&mut cursor).unwrap().global_encoding & 1 << 15);
+/// cursor.seek(SeekFrom::Start(0)).unwrap();
+/// assert_eq!(1, Reader::new(cursor).unwrap().points().count());
+/// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct Header {
     /// The file signature must contain the four characters “LASF”, and it is required by the LAS
@@ -244,6 +269,9 @@ impl Header {
     /// color points for las 1.1).
     /// - The point data record length is less than the minimum length of the point data format.
     ///
+    /// If the header size is larger than the size of a header for the version, the extra bytes
+    /// are read into `padding`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -504,6 +532,19 @@ mod tests {
         assert!(write_read(header).is_err());
     }
 
+    #[test]
+    fn header_size_too_large() {
+        let header = Header {
+            header_size: 237,
+            padding: vec![42; 10],
+            ..Default::default()
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        header.write_to(&mut cursor).unwrap();
+        cursor.set_position(0);
+        assert_eq!(header, Header::read_from(cursor).unwrap());
+    }
+
     macro_rules! roundtrip {
         ($name:ident, $minor:expr) => {
             mod $name {
@@ -387,13 +387,13 @@ impl Point {
         } else {
             None
         };
-        point.waveform = if format.has_waveform {
-            Some(Waveform::read_from(&mut read)?)
+        point.nir = if format.has_nir {
+            Some(read.read_u16::<LittleEndian>()?)
         } else {
             None
         };
-        point.nir = if format.has_nir {
-            utils::some_or_none_if_zero(read.read_u16::<LittleEndian>()?)
+        point.waveform = if format.has_waveform {
+            Some(Waveform::read_from(&mut read)?)
         } else {
             None
         };
@@ -603,17 +603,20 @@ impl Flags {
 
     /// Returns whether this point is overlap.
     ///
+    /// For three byte flags, both the overlap bit and the legacy overlap classification count.
+    ///
     /// # Examples
     ///
     /// ```
     /// use las::raw::point::Flags;
     /// assert!(Flags::TwoByte(0, 12).is_overlap());
     /// assert!(Flags::ThreeByte(0, 8, 0).is_overlap());
+    /// assert!(Flags::ThreeByte(0, 0, 12).is_overlap());
     /// ```
     pub fn is_overlap(&self) -> bool {
         match *self {
-            Flags::TwoByte(_, b) => b & 0b1111 == OVERLAP_CLASSIFICATION_CODE,
-            Flags::ThreeByte(_, b, _) => b & 8 == 8,
+            Flags::TwoByte(_, b) => b & 0b1_1111 == OVERLAP_CLASSIFICATION_CODE,
+            Flags::ThreeByte(_, b, c) => b & 8 == 8 || c == OVERLAP_CLASSIFICATION_CODE,
         }
     }
 
@@ -847,6 +850,9 @@ mod tests {
                     if format.has_waveform {
                         point.waveform = Some(Waveform::default());
                     }
+                    if format.has_nir {
+                        point.nir = Some(0);
+                    }
                     let mut cursor = Cursor::new(Vec::new());
                     point.write_to(&mut cursor, format).unwrap();
                     cursor.set_position(0);
@@ -868,6 +874,32 @@ mod tests {
     roundtrip!(format_9, 9);
     roundtrip!(format_10, 10);
 
+    #[test]
+    fn nir_is_independent_of_color() {
+        use std::io::Cursor;
+
+        for &n in &[8, 10] {
+            let format = Format::new(n).unwrap();
+            let point = Point {
+                color: Some(Color::new(1, 2, 3)),
+                waveform: if format.has_waveform {
+                    Some(Waveform::default())
+                } else {
+                    None
+                },
+                nir: Some(0),
+                ..Default::default()
+            };
+            let mut cursor = Cursor::new(Vec::new());
+            point.write_to(&mut cursor, format).unwrap();
+            assert_eq!(format.len() as usize, cursor.get_ref().len());
+            cursor.set_position(0);
+            let read = Point::read_from(cursor, format).unwrap();
+            assert_eq!(Some(0), read.nir);
+            assert_eq!(Some(Color::new(1, 2, 3)), read.color);
+        }
+    }
+
     #[test]
     fn return_number() {
         assert_eq!((0, 0, 0), Flags::TwoByte(0, 0).into());
@@ -937,6 +969,8 @@ mod tests {
     fn is_overlap() {
         assert!(!Flags::TwoByte(0, 0).is_overlap());
         assert!(Flags::TwoByte(0, OVERLAP_CLASSIFICATION_CODE).is_overlap());
+        assert!(Flags::TwoByte(0, 0b1110_1100).is_overlap());
+        assert!(!Flags::TwoByte(0, 28).is_overlap());
         assert!(!Flags::ThreeByte(0, 0, 0).is_overlap());
         assert!(Flags::ThreeByte(0, 8, 0).is_overlap());
         assert_eq!(
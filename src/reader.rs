@@ -35,16 +35,39 @@
 //! let first_point = reader.read().unwrap().unwrap();
 //! let the_rest = reader.points().map(|r| r.unwrap()).collect::<Vec<_>>();
 //! ```
+//!
+//! With the `proj` feature enabled, points can be reprojected as they are read with
+//! `Reader::reproject`.
 
-use {Builder, Header, Point, Result, Vlr, raw};
+use {Bounds, Builder, Header, PercentileMode, Phase, Point, Result, Statistics, Transform, Vector,
+     Version, Vlr, Warning, Writer, raw};
+use error::WithPhase;
+use extra_bytes::Descriptor;
+use point::{Format, PointArrays};
+#[cfg(feature = "proj")]
+use proj::Proj;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use waveform::{WaveformLocation, WaveformReader};
+
+/// The number of point records that are copied at a time by `Reader::copy_raw_records`.
+const COPY_BUFFER_RECORDS: usize = 4096;
 
 quick_error! {
     /// Error while reading.
     #[derive(Clone, Copy, Debug)]
     pub enum Error {
+        /// There is no waveform data packet record.
+        NoWaveformDataPacketRecord {
+            description("there is no waveform data packet record")
+        }
+        /// The point format does not have waveforms.
+        NoWaveforms(format: Format) {
+            description("the point format does not have waveforms")
+            display("point format {} does not have waveforms", format)
+        }
         /// The offset to the point data was too small.
         OffsetToPointDataTooSmall(offset: u32) {
             description("offset to point data too small")
@@ -55,9 +78,113 @@ quick_error! {
             description("offset the evlrs is too small")
             display("offset to the evlrs is too small: {}", offset)
         }
+        /// The point format number is not known.
+        UnsupportedPointFormat(n: u8) {
+            description("unsupported point format")
+            display("unsupported point format: {}", n)
+        }
+        /// The waveforms are not stored where they were asked for.
+        WaveformLocation(location: WaveformLocation) {
+            description("the waveforms are not stored in the requested location")
+            display("the waveforms are stored {:?}, not in the requested location", location)
+        }
     }
 }
 
+/// Options that control how a `Reader` reads and validates las data.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// Should every point be checked against the header's bounds?
+    ///
+    /// Points that are outside of the bounds by more than the scale of the transform produce a
+    /// `Warning::PointOutOfBounds`. This is off by default for performance.
+    pub validate_bounds: bool,
+
+    /// Should invalid text in the header's system identifier and generating software be read
+    /// lossily, instead of erroring?
+    ///
+    /// Invalid text, e.g. Latin-1 or garbage bytes, is converted with `String::from_utf8_lossy`
+    /// and produces a `Warning::NonUtf8String` that holds the raw bytes. Las strings must be
+    /// ascii, so you'll need to fix these strings before writing the header back out.
+    pub lenient_strings: bool,
+
+    /// Should every point's gps time be checked against the header's gps time type?
+    ///
+    /// Gps times outside of `GpsTimeType::plausible_range` produce a
+    /// `Warning::GpsTimeOutOfRange`, which catches data whose gps time type bit is wrong. This is
+    /// off by default for performance.
+    pub validate_gps_time: bool,
+
+    /// Should the vlrs be read until the offset to point data, instead of trusting the header's
+    /// number of vlrs?
+    ///
+    /// Some writers get the number of vlrs wrong, but the offset to point data right. In repair
+    /// mode, vlrs are read for as long as another vlr header and its data fit before the point
+    /// data, and anything left over is kept as the vlr padding. If the number of vlrs that were
+    /// found is different than the header's count, a `Warning::VlrCountMismatch` is recorded.
+    /// Repairing can't tell a vlr apart from padding that happens to look like one.
+    pub repair_vlr_count: bool,
+
+    /// Should the bounds be recomputed from the points if the header's bounds are all zero?
+    ///
+    /// Las data with points but all-zero bounds produce a `Warning::ZeroBoundsWithPoints`, since
+    /// that usually means the writer never updated the bounds. By default only the first point is
+    /// checked, so data whose first point is at the origin don't produce the warning. If this is
+    /// true, all of the points are read when the reader is created instead, the header's bounds
+    /// are replaced with their actual bounds, and the warning is produced if those aren't all
+    /// zero.
+    pub recompute_zero_bounds: bool,
+
+    /// Should las data with an unknown point format number, e.g. from a future version of the
+    /// spec, be read anyways?
+    ///
+    /// If false, unknown point formats are an `Error::UnsupportedPointFormat`. If true, the header
+    /// and vlrs are read as usual, `Reader::unknown_point_format` returns the declared number, and
+    /// each point is read as the raw bytes of the point data record length, in
+    /// `Point::raw_record`. Since the reader can't know what's in those bytes, the header's point
+    /// format is point format zero, with the rest of the record as extra bytes.
+    pub read_unknown_point_formats: bool,
+
+    /// Overrides the header's point data record length.
+    ///
+    /// Some writers get the point data record length wrong, which misaligns every point after the
+    /// first. The override is used for everything the record length is used for: the stride
+    /// between points, the number of extra bytes, and the end of the point data. It must be at
+    /// least the length of the point format, or opening the reader is an error.
+    pub override_record_length: Option<u16>,
+
+    /// Should quirky encodings from old, non-conforming writers be normalized?
+    ///
+    /// Some tools from the mid-2000s marked withheld points in the legacy point formats by
+    /// writing their classification as a negative `i8`, e.g. -2 for a withheld ground point.
+    /// Read as flags, that sets the withheld, key-point, and synthetic bits along with a nonsense
+    /// classification. If this is true, classification bytes in the legacy formats that are
+    /// between -31 and -1 as an `i8` are read as the positive classification with only the
+    /// withheld bit set. This can misread conforming data that are withheld, key-point, and
+    /// synthetic all at once, which is why it's off by default.
+    pub legacy_quirks: bool,
+}
+
+/// The declared sizes of las data compared with the actual length of the stream.
+///
+/// This struct is created by `Reader::size_report`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeReport {
+    /// The size of the point data, as declared by the header: number of points times the point
+    /// record length.
+    pub point_data_size: u64,
+    /// The size of all of the las data, as declared by the header: the header, vlrs, points, point
+    /// padding, and evlrs.
+    pub declared_size: u64,
+    /// The actual length of the stream.
+    pub actual_size: u64,
+    /// Is the declared size the same as the actual size?
+    ///
+    /// If the actual size is smaller, the data are truncated. If it's larger, there are trailing
+    /// bytes after the las data.
+    pub consistent: bool,
+}
+
 /// Reads LAS data.
 #[derive(Debug)]
 pub struct Reader<R: Read + Seek> {
@@ -65,14 +192,20 @@ pub struct Reader<R: Read + Seek> {
     number_of_points: u64,
     number_read: u64,
     offset_to_point_data: u64,
+    options: Options,
+    #[cfg(feature = "proj")]
+    proj: Option<Proj>,
     read: R,
+    unknown_point_format: Option<u8>,
+    warnings: Vec<Warning>,
 }
 
 impl<R: Read + Seek> Reader<R> {
     /// Creates a new reader.
     ///
     /// This does *not* wrap the `Read` in a `BufRead`, so if you're concered about performance you
-    /// should do that wrapping yourself (or use `from_path`).
+    /// should do that wrapping yourself (or use `from_path`). Reading from an unbuffered `File` is
+    /// very slow, since every point read is at least one system call.
     ///
     /// # Examples
     ///
@@ -83,64 +216,105 @@ impl<R: Read + Seek> Reader<R> {
     /// let file = File::open("tests/data/autzen.las").unwrap();
     /// let reader = Reader::new(BufReader::new(file)).unwrap();
     /// ```
-    pub fn new(mut read: R) -> Result<Reader<R>> {
-        let raw_header = raw::Header::read_from(&mut read)?;
-        let mut position = u64::from(raw_header.header_size);
-        let number_of_variable_length_records = raw_header.number_of_variable_length_records;
-        let offset_to_point_data = u64::from(raw_header.offset_to_point_data);
-        let offset_to_end_of_points = raw_header.offset_to_end_of_points();
-        let evlr = raw_header.evlr;
-
-        let mut builder = Builder::new(raw_header)?;
-        if builder.point_format.is_compressed {
-            return Err(::Error::Laszip);
-        }
-        for _ in 0..number_of_variable_length_records {
-            let vlr = raw::Vlr::read_from(&mut read, false).and_then(Vlr::new)?;
-            position += vlr.len(false) as u64;
-            builder.vlrs.push(vlr);
-        }
-        if position > offset_to_point_data {
-            return Err(
-                Error::OffsetToPointDataTooSmall(offset_to_point_data as u32).into(),
-            );
-        } else if position < offset_to_point_data {
-            read.by_ref()
-                .take(offset_to_point_data - position)
-                .read_to_end(&mut builder.vlr_padding)?;
-        }
+    pub fn new(read: R) -> Result<Reader<R>> {
+        Reader::with_options(read, Default::default())
+    }
 
-        read.seek(SeekFrom::Start(offset_to_end_of_points))?;
-        if let Some(evlr) = evlr {
-            if evlr.start_of_first_evlr < offset_to_end_of_points {
-                return Err(
-                    Error::OffsetToEvlrsTooSmall(evlr.start_of_first_evlr).into(),
-                );
-            } else if evlr.start_of_first_evlr > offset_to_end_of_points {
-                let n = evlr.start_of_first_evlr - offset_to_end_of_points;
-                read.by_ref().take(n).read_to_end(
-                    &mut builder.point_padding,
-                )?;
+    /// Creates a new reader with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    /// use las::Reader;
+    /// use las::reader::Options;
+    /// let file = BufReader::new(File::open("tests/data/autzen.las").unwrap());
+    /// let options = Options { validate_bounds: true, ..Default::default() };
+    /// let reader = Reader::with_options(file, options).unwrap();
+    /// ```
+    pub fn with_options(read: R, options: Options) -> Result<Reader<R>> {
+        let mut reader = Reader::open(read, options)?;
+        if reader.number_of_points > 0 && is_zero(reader.header.bounds()) {
+            let is_wrong = if options.recompute_zero_bounds {
+                let bounds = reader.scan_bounds()?;
+                let number_of_points = reader.header.number_of_points();
+                reader.header.set_number_of_points_and_bounds(number_of_points, bounds);
+                !is_zero(bounds)
+            } else {
+                reader.first_point_is_off_origin()?
+            };
+            if is_wrong {
+                reader.warnings.push(Warning::ZeroBoundsWithPoints {
+                    number_of_points: reader.number_of_points,
+                });
             }
-            builder.evlrs.push(
-                raw::Vlr::read_from(&mut read, true).and_then(
-                    Vlr::new,
-                )?,
-            );
         }
+        Ok(reader)
+    }
 
-        read.seek(SeekFrom::Start(offset_to_point_data))?;
-        let header = builder.into_header()?;
-
+    fn open(mut read: R, options: Options) -> Result<Reader<R>> {
+        let mut preamble = Preamble::read_from(&mut read, options)?;
+        if preamble.has_evlrs() {
+            read.seek(SeekFrom::Start(preamble.offset_to_end_of_points))?;
+            preamble.read_evlrs_from(&mut read)?;
+        }
+        read.seek(SeekFrom::Start(preamble.offset_to_point_data))?;
+        let offset_to_point_data = preamble.offset_to_point_data;
+        let unknown_point_format = preamble.unknown_point_format;
+        let (header, warnings) = preamble.into_header()?;
         Ok(Reader {
             number_of_points: header.number_of_points(),
             header: header,
             offset_to_point_data: offset_to_point_data,
+            options: options,
+            #[cfg(feature = "proj")]
+            proj: None,
             read: read,
             number_read: 0,
+            unknown_point_format: unknown_point_format,
+            warnings: warnings,
         })
     }
 
+    fn first_point_is_off_origin(&mut self) -> Result<bool> {
+        // A point that can't be read is reported when it's read, not here.
+        let raw_point = self.read_raw().unwrap_or(None);
+        self.seek(0)?;
+        Ok(raw_point.is_some_and(|raw_point| {
+            let point = Point::new(raw_point, self.header.transforms());
+            point.x != 0. || point.y != 0. || point.z != 0.
+        }))
+    }
+
+    fn scan_bounds(&mut self) -> Result<Bounds> {
+        let transforms = self.header.transforms();
+        let mut bounds = Bounds::default();
+        while let Some(raw_point) = self.read_raw()? {
+            bounds.grow(&Point::new(raw_point, transforms));
+        }
+        self.seek(0)?;
+        Ok(bounds)
+    }
+
+    /// Reads the header, vlrs, and evlrs, without reading any points.
+    ///
+    /// The evlrs are read by seeking directly to the start of the first evlr, so the point data
+    /// are skipped entirely. This is useful for tools that only need metadata.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use las::Reader;
+    /// let file = File::open("tests/data/autzen.las").unwrap();
+    /// let header = Reader::metadata_only(file).unwrap();
+    /// assert_eq!(106, header.number_of_points());
+    /// ```
+    pub fn metadata_only(read: R) -> Result<Header> {
+        Reader::open(read, Default::default()).map(|reader| reader.header)
+    }
+
     /// Returns a reference to this reader's header.
     ///
     /// # Examples
@@ -154,6 +328,99 @@ impl<R: Read + Seek> Reader<R> {
         &self.header
     }
 
+    /// Returns the raw bytes between the end of the header and the start of the point data.
+    ///
+    /// These are the vlrs and any vlr padding, exactly as they are stored in the file. Use
+    /// `Writer::with_vlr_region` to write them back out verbatim. The reader's position is not
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let vlr_region = reader.vlr_region().unwrap();
+    /// let header = reader.header();
+    /// let vlrs_len = header.vlrs().iter().map(|vlr| vlr.len(false)).sum::<usize>();
+    /// assert_eq!(vlrs_len + header.vlr_padding().len(), vlr_region.len());
+    /// ```
+    pub fn vlr_region(&mut self) -> Result<Vec<u8>> {
+        let header_size =
+            u64::from(self.header.version().header_size()) + self.header.padding().len() as u64;
+        let position = self.read.stream_position()?;
+        self.read.seek(SeekFrom::Start(header_size))?;
+        let mut vlr_region = Vec::new();
+        self.read
+            .by_ref()
+            .take(self.offset_to_point_data - header_size)
+            .read_to_end(&mut vlr_region)?;
+        self.read.seek(SeekFrom::Start(position))?;
+        Ok(vlr_region)
+    }
+
+    /// Compares the size of the las data declared by the header with the length of the stream.
+    ///
+    /// The reader's position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let size_report = reader.size_report().unwrap();
+    /// assert!(size_report.consistent);
+    /// ```
+    pub fn size_report(&mut self) -> Result<SizeReport> {
+        let point_data_size = self.header.number_of_points() *
+            u64::from(self.header.point_format().len());
+        let evlrs_size = self.header.evlrs().iter().fold(0, |acc, evlr| {
+            acc + evlr.len(true) as u64
+        });
+        let declared_size = self.offset_to_point_data + point_data_size +
+            self.header.point_padding().len() as u64 + evlrs_size;
+        let position = self.read.stream_position()?;
+        let actual_size = self.read.seek(SeekFrom::End(0))?;
+        self.read.seek(SeekFrom::Start(position))?;
+        Ok(SizeReport {
+            point_data_size: point_data_size,
+            declared_size: declared_size,
+            actual_size: actual_size,
+            consistent: declared_size == actual_size,
+        })
+    }
+
+    /// Returns the warnings that this reader has found so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, Warning};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// // autzen has two liblas vlrs with record id 2112
+    /// assert_eq!(
+    ///     Warning::DuplicateVlr { user_id: "liblas".to_string(), record_id: 2112 },
+    ///     reader.warnings()[0]
+    /// );
+    /// ```
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the point format number of las data with an unknown point format.
+    ///
+    /// This is only ever `Some` if `Options::read_unknown_point_formats` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert_eq!(None, reader.unknown_point_format());
+    /// ```
+    pub fn unknown_point_format(&self) -> Option<u8> {
+        self.unknown_point_format
+    }
+
     /// Reads a point.
     ///
     /// Returns `Ok(None)` if we have already read the last point.
@@ -166,16 +433,258 @@ impl<R: Read + Seek> Reader<R> {
     /// let point = reader.read().unwrap().unwrap();
     /// ```
     pub fn read(&mut self) -> Result<Option<Point>> {
+        if self.unknown_point_format.is_some() {
+            return self.read_raw_record();
+        }
+        let raw_point = match self.read_raw()? {
+            Some(raw_point) => raw_point,
+            None => return Ok(None),
+        };
+        let point = Point::new(raw_point, self.header.transforms());
+        if self.options.validate_bounds {
+            self.validate_bounds(&point);
+        }
+        if self.options.validate_gps_time {
+            self.validate_gps_time(&point);
+        }
+        #[cfg(feature = "proj")]
+        let point = self.reproject_point(point)?;
+        Ok(Some(point))
+    }
+
+    fn read_raw_record(&mut self) -> Result<Option<Point>> {
+        if self.number_read >= self.number_of_points {
+            return Ok(None);
+        }
+        let mut raw_record = vec![0; usize::from(self.header.point_format().len())];
+        let result = self.read
+            .read_exact(&mut raw_record)
+            .map_err(::Error::from)
+            .with_phase(self.reading_point_phase());
+        self.number_read += 1;
+        result?;
+        Ok(Some(Point {
+            raw_record: Some(raw_record),
+            ..Default::default()
+        }))
+    }
+
+    fn validate_bounds(&mut self, point: &Point) {
+        let bounds = self.header.bounds();
+        let transforms = self.header.transforms();
+        let is_outside = |n: f64, min: f64, max: f64, transform: Transform| {
+            n < min - transform.scale || n > max + transform.scale
+        };
+        if is_outside(point.x, bounds.min.x, bounds.max.x, transforms.x) ||
+            is_outside(point.y, bounds.min.y, bounds.max.y, transforms.y) ||
+            is_outside(point.z, bounds.min.z, bounds.max.z, transforms.z)
+        {
+            self.warnings.push(Warning::PointOutOfBounds {
+                index: self.number_read - 1,
+                x: point.x,
+                y: point.y,
+                z: point.z,
+            });
+        }
+    }
+
+    fn validate_gps_time(&mut self, point: &Point) {
+        if let Some(gps_time) = point.gps_time {
+            let gps_time_type = self.header.gps_time_type();
+            let (min, max) = gps_time_type.plausible_range();
+            if !(gps_time >= min && gps_time <= max) {
+                self.warnings.push(Warning::GpsTimeOutOfRange {
+                    index: self.number_read - 1,
+                    gps_time: gps_time,
+                    gps_time_type: gps_time_type,
+                });
+            }
+        }
+    }
+
+    /// Reads a raw point, without applying the header's transforms to its coordinates.
+    ///
+    /// Returns `Ok(None)` if we have already read the last point. Raw points aren't validated or
+    /// reprojected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let raw_point = reader.read_raw().unwrap().unwrap();
+    /// ```
+    pub fn read_raw(&mut self) -> Result<Option<raw::Point>> {
         if self.number_read >= self.number_of_points {
             Ok(None)
         } else {
-            let point = raw::Point::read_from(&mut self.read, self.header.point_format())
-                .map(|raw_point| {
-                    Some(Point::new(raw_point, self.header.transforms()))
-                });
+            let phase = self.reading_point_phase();
+            let mut raw_point = raw::Point::read_from(&mut self.read, self.header.point_format())
+                .with_phase(phase)?;
             self.number_read += 1;
-            point
+            if self.options.legacy_quirks {
+                normalize_negative_classification(&mut raw_point.flags);
+            }
+            Ok(Some(raw_point))
+        }
+    }
+
+    /// Copies all of the remaining point records to `write` without decoding them, calling
+    /// `function` on each record once it has been written, and returns the number of records
+    /// copied.
+    pub(crate) fn copy_raw_records<W, F>(&mut self, write: &mut W, mut function: F) -> Result<u64>
+    where
+        W: Write,
+        F: FnMut(&[u8]),
+    {
+        use std::cmp;
+
+        let len = usize::from(self.header.point_format().len());
+        let mut buffer = vec![0; len * COPY_BUFFER_RECORDS];
+        let mut copied = 0;
+        while self.number_read < self.number_of_points {
+            let n = cmp::min(self.number_of_points - self.number_read, COPY_BUFFER_RECORDS as u64);
+            let buffer = &mut buffer[..n as usize * len];
+            let phase = self.reading_point_phase();
+            self.read.read_exact(buffer).map_err(
+                |err| ::Error::IoDuring(phase, err),
+            )?;
+            write.write_all(buffer)?;
+            for record in buffer.chunks(len) {
+                function(record);
+            }
+            self.number_read += n;
+            copied += n;
+        }
+        Ok(copied)
+    }
+
+    fn reading_point_phase(&self) -> Phase {
+        Phase::ReadingPoint {
+            index: self.number_read,
+            offset: self.offset_to_point_data +
+                self.number_read * u64::from(self.header.point_format().len()),
+        }
+    }
+
+    /// Reads up to `n` points into a struct of arrays.
+    ///
+    /// Fewer than `n` points are returned if there aren't that many left. See `PointArrays` for
+    /// which arrays are populated for each point format. As with raw points, these points aren't
+    /// validated or reprojected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let arrays = reader.read_soa(100).unwrap();
+    /// assert_eq!(100, arrays.zs.len());
+    /// assert_eq!(100, arrays.gps_times.len()); // autzen is point format 1
+    /// assert!(arrays.colors.is_empty());
+    /// let arrays = reader.read_soa(100).unwrap();
+    /// assert_eq!(6, arrays.len());
+    /// ```
+    pub fn read_soa(&mut self, n: usize) -> Result<PointArrays> {
+        let remaining = self.number_of_points.saturating_sub(self.number_read);
+        let n = if (n as u64) < remaining { n } else { remaining as usize };
+        let transforms = self.header.transforms();
+        let mut arrays = PointArrays::with_capacity(n);
+        for _ in 0..n {
+            match self.read_raw()? {
+                Some(raw_point) => arrays.push(raw_point, transforms),
+                None => break,
+            }
+        }
+        Ok(arrays)
+    }
+
+    /// Reads as many points as fit in a budget of `max_bytes` of point data.
+    ///
+    /// The number of points is `max_bytes` divided by the point data record length, so batches
+    /// use about the same memory regardless of the point format. At least one point is read, even
+    /// if a single record is larger than the budget, so that repeated calls always make progress.
+    /// Fewer points are returned if there aren't that many left, and an empty vector once all of
+    /// the points have been read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.read_until_bytes(1024).unwrap();
+    /// assert_eq!(1024 / 28, points.len()); // autzen's point records are 28 bytes
+    /// ```
+    pub fn read_until_bytes(&mut self, max_bytes: usize) -> Result<Vec<Point>> {
+        use std::cmp;
+
+        let record_length = usize::from(self.header.point_format().len());
+        let remaining = self.number_of_points.saturating_sub(self.number_read);
+        let n = cmp::max(1, max_bytes / record_length);
+        let n = if (n as u64) < remaining { n } else { remaining as usize };
+        let mut points = Vec::with_capacity(n);
+        for _ in 0..n {
+            match self.read()? {
+                Some(point) => points.push(point),
+                None => break,
+            }
+        }
+        Ok(points)
+    }
+
+    /// Reprojects all subsequently read points from one coordinate reference system to another.
+    ///
+    /// `from` and `to` can be anything understood by PROJ, e.g. PROJ strings or EPSG codes such
+    /// as `"EPSG:4326"`. Coordinates are transformed as `f64`s before any quantization happens, so
+    /// no precision is lost in the reader.
+    ///
+    /// Unlike a full PROJ pipeline, only x and y are transformed. The `proj` crate's conversions
+    /// are two dimensional, so z is passed through unchanged, which means that heights aren't moved
+    /// between vertical datums (e.g. from ellipsoidal to orthometric heights) even if `from` and
+    /// `to` are compound coordinate reference systems. Vertical shifts have to be applied to z
+    /// separately.
+    ///
+    /// All vlrs and evlrs with the "LASF_Projection" user id are removed from this reader's
+    /// header, since they no longer describe the points.
+    ///
+    /// The header's bounds, scales, and offsets still describe the *original* points. If you write
+    /// reprojected points out, the writer will recalculate the bounds, but you will probably need
+    /// to choose new scales and offsets (e.g. going from meters to degrees).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "proj")]
+    /// # {
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// reader.reproject("EPSG:2992", "EPSG:4326").unwrap();
+    /// let point = reader.read().unwrap().unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "proj")]
+    pub fn reproject(&mut self, from: &str, to: &str) -> Result<()> {
+        let proj = Proj::new_known_crs(from, to, None).map_err(|err| {
+            ::Error::Reprojection(err.to_string())
+        })?;
+        let mut builder = Builder::from(self.header.clone());
+        builder.vlrs.retain(|vlr| !is_projection(vlr));
+        builder.evlrs.retain(|vlr| !is_projection(vlr));
+        self.header = builder.into_header()?;
+        self.proj = Some(proj);
+        Ok(())
+    }
+
+    #[cfg(feature = "proj")]
+    fn reproject_point(&self, mut point: Point) -> Result<Point> {
+        if let Some(ref proj) = self.proj {
+            let (x, y) = proj.convert((point.x, point.y)).map_err(|err| {
+                ::Error::Reprojection(err.to_string())
+            })?;
+            point.x = x;
+            point.y = y;
         }
+        Ok(point)
     }
 
     /// Seeks to the given point number, zero-indexed.
@@ -194,71 +703,1156 @@ impl<R: Read + Seek> Reader<R> {
                 position *
                     u64::from(self.header.point_format().len()),
         ))?;
+        self.number_read = position;
         Ok(())
     }
 
-    /// Returns an iterator over this reader's points.
+    /// Returns the index of the next point that will be read, zero-indexed.
+    ///
+    /// Save this to resume an interrupted job: create a new reader for the same data and `seek`
+    /// to the saved position.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
+    /// use las::Reader;
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
-    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(0, reader.position());
+    /// reader.read().unwrap().unwrap();
+    /// assert_eq!(1, reader.position());
     /// ```
-    pub fn points(&mut self) -> Points<R> {
-        Points { reader: self }
+    pub fn position(&self) -> u64 {
+        self.number_read
     }
-}
 
-impl Reader<BufReader<File>> {
-    /// Creates a new reader from a path.
+    /// Opens an external waveform data packet (`.wdp`) file for this reader's points.
     ///
-    /// The underlying `File` is wrapped in a `BufReader` for performance reasons.
+    /// The `.wdp` file usually has the same base name as the las file. Use `Point::read_waveform`
+    /// to read each point's waveform samples. Returns an error if this reader's point format does
+    /// not have waveforms, or if the header says that the waveforms are internal.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use las::Reader;
+    /// use las::Reader;
     /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// // autzen uses point format 1, which does not have waveforms
+    /// assert!(reader.open_waveforms("tests/data/autzen.wdp").is_err());
     /// ```
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>> {
-        File::open(path).map_err(::Error::from).and_then(|file| {
-            Reader::new(BufReader::new(file))
-        })
+    pub fn open_waveforms<P: AsRef<Path>>(&self, path: P) -> Result<WaveformReader<BufReader<File>>> {
+        self.verify_waveform_location(WaveformLocation::External)?;
+        WaveformReader::from_path(path)
     }
-}
 
-/// An iterator over of the points in a `Reader`.
-///
-/// This struct is generally created by calling `points()` on `Reader`.
-#[derive(Debug)]
-pub struct Points<'a, R: 'a + Read + Seek> {
-    reader: &'a mut Reader<R>,
-}
+    /// Returns a waveform reader over the internal waveform data packet record.
+    ///
+    /// The record is copied out of the header's evlrs, so the byte offsets in this reader's points
+    /// can be used as-is with `Point::read_waveform`. In las 1.4, the record is whichever evlr has
+    /// the waveform data packets user id and record id. Las 1.3 has no evlrs, but its record has
+    /// the same layout as an evlr, and it is found through the header's start of waveform data
+    /// packet record instead, so it is read into the header's evlrs too.
+    ///
+    /// Returns an error if this reader's point format does not have waveforms, if the header says
+    /// that the waveforms are external, or if there is no waveform data packet record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(reader.internal_waveforms().is_err());
+    /// ```
+    pub fn internal_waveforms(&self) -> Result<WaveformReader<Cursor<Vec<u8>>>> {
+        self.verify_waveform_location(WaveformLocation::Internal)?;
+        let evlr = self.header
+            .evlrs()
+            .iter()
+            .find(|evlr| evlr.is_waveform_data_packets())
+            .ok_or(Error::NoWaveformDataPacketRecord)?;
+        let mut bytes = Vec::new();
+        evlr.clone().into_raw(true)?.write_to(&mut bytes)?;
+        Ok(WaveformReader::new(Cursor::new(bytes)))
+    }
 
-impl<'a, R: Read + Seek> Iterator for Points<'a, R> {
-    type Item = Result<Point>;
-    fn next(&mut self) -> Option<Result<Point>> {
-        match self.reader.read() {
-            Ok(None) => None,
-            Ok(Some(point)) => Some(Ok(point)),
-            Err(err) => Some(Err(err)),
+    fn verify_waveform_location(&self, location: WaveformLocation) -> Result<()> {
+        let format = self.header.point_format();
+        match self.header.waveform_location() {
+            None => Err(Error::NoWaveforms(format).into()),
+            Some(actual) if actual != location => Err(Error::WaveformLocation(actual).into()),
+            Some(_) => Ok(()),
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use Writer;
 
-    #[test]
-    fn seek() {
-        let mut writer = Writer::default();
-        writer.write(Default::default()).unwrap();
-        let point = Point {
-            x: 1.,
+    /// Returns an iterator over this reader's points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    pub fn points(&mut self) -> Points<R> {
+        Points { reader: self }
+    }
+
+    /// Returns an iterator over the remaining points whose x and y are inside a polygon.
+    ///
+    /// The polygon's vertices are in the same coordinate reference system as the points, and only
+    /// their x and y are used. The polygon is closed automatically, so the last vertex doesn't
+    /// need to repeat the first. A point is inside if a ray from it crosses the polygon's edges an
+    /// odd number of times (the even-odd rule). Every point is still read, and each test is linear
+    /// in the number of vertices, so the total cost is the number of points times the number of
+    /// vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Reader, Vector};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let bounds = reader.header().bounds();
+    /// let polygon = vec![
+    ///     Vector { x: bounds.min.x - 1., y: bounds.min.y - 1., z: 0. },
+    ///     Vector { x: bounds.max.x + 1., y: bounds.min.y - 1., z: 0. },
+    ///     Vector { x: bounds.max.x + 1., y: bounds.max.y + 1., z: 0. },
+    ///     Vector { x: bounds.min.x - 1., y: bounds.max.y + 1., z: 0. },
+    /// ];
+    /// assert_eq!(106, reader.points_in_polygon(&polygon).count());
+    /// ```
+    pub fn points_in_polygon<'a>(
+        &'a mut self,
+        polygon: &'a [Vector<f64>],
+    ) -> PointsInPolygon<'a, R> {
+        PointsInPolygon {
+            polygon: polygon,
+            reader: self,
+        }
+    }
+
+    /// Returns an iterator over all of this reader's points, from the last to the first.
+    ///
+    /// Each step seeks to the previous point record, which defeats buffering, so this can be
+    /// slower than `points`. When the iterator is done, the reader is positioned after the first
+    /// point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let last = reader.points_rev().next().unwrap().unwrap();
+    /// reader.seek(105).unwrap();
+    /// assert_eq!(reader.read().unwrap().unwrap(), last);
+    /// ```
+    pub fn points_rev(&mut self) -> PointsRev<'_, R> {
+        let remaining = self.number_of_points;
+        PointsRev {
+            reader: self,
+            remaining: remaining,
+        }
+    }
+
+    /// Returns an iterator over this reader's raw points.
+    ///
+    /// Raw points have the integer coordinates as stored in the file, which is useful for exact
+    /// integer arithmetic. Use the header's transforms to convert them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let raw_points = reader.raw_points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    pub fn raw_points(&mut self) -> RawPoints<'_, R> {
+        RawPoints { reader: self }
+    }
+
+    /// Reads all remaining points and groups them by their point source id, e.g. by flight line.
+    ///
+    /// Points aren't necessarily sorted by their source id, so this buffers *every* remaining
+    /// point in memory. For large files, consider filtering `points` by source id instead, one
+    /// source id at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points_by_source = reader.points_by_source().unwrap();
+    /// let count = points_by_source.values().map(|points| points.len()).sum::<usize>();
+    /// assert_eq!(reader.header().number_of_points(), count as u64);
+    /// ```
+    pub fn points_by_source(&mut self) -> Result<HashMap<u16, Vec<Point>>> {
+        let mut points_by_source = HashMap::new();
+        for point in self.points() {
+            let point = point?;
+            points_by_source
+                .entry(point.point_source_id)
+                .or_insert_with(Vec::new)
+                .push(point);
+        }
+        Ok(points_by_source)
+    }
+
+    /// Reads every point, modifies it in place with a function, and writes it to `write`.
+    ///
+    /// This is a streaming read-modify-write in one pass, like `Transformer`, but the function
+    /// gets a `&mut Point` instead of taking and returning one. The new las data use a clone of
+    /// this reader's header, so the vlrs, evlrs, and padding are carried over, and the point
+    /// counts and bounds are recomputed from the written points. The reader is rewound first, and
+    /// the writer is closed before it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let writer = reader
+    ///     .map_in_place(Cursor::new(Vec::new()), |point| point.intensity /= 2)
+    ///     .unwrap();
+    /// assert_eq!(106, writer.header().number_of_points());
+    /// ```
+    pub fn map_in_place<W, F>(&mut self, write: W, mut function: F) -> Result<Writer<W>>
+    where
+        W: Write + Seek,
+        F: FnMut(&mut Point),
+    {
+        self.seek(0)?;
+        let mut writer = Writer::new(write, self.header.clone())?;
+        while let Some(mut point) = self.read()? {
+            function(&mut point);
+            writer.write_ref(&point)?;
+        }
+        writer.close()?;
+        Ok(writer)
+    }
+
+    /// Reads all remaining points and computes their statistics in a single pass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let statistics = reader.statistics().unwrap();
+    /// assert_eq!(reader.header().number_of_points(), statistics.number_of_points);
+    /// ```
+    pub fn statistics(&mut self) -> Result<Statistics> {
+        self.add_to_statistics(Statistics::new())
+    }
+
+    /// Reads all remaining points and computes their statistics, including z and intensity
+    /// percentiles, in a single pass.
+    ///
+    /// See `PercentileMode` for the memory and accuracy tradeoff.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{PercentileMode, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let statistics = reader.statistics_with_percentiles(PercentileMode::Exact).unwrap();
+    /// let percentiles = statistics.percentiles(&[5., 50., 95.]).unwrap();
+    /// assert!(percentiles.z[0] <= percentiles.z[1]);
+    /// ```
+    pub fn statistics_with_percentiles(&mut self, mode: PercentileMode) -> Result<Statistics> {
+        self.add_to_statistics(Statistics::with_percentiles(mode)?)
+    }
+
+    fn add_to_statistics(&mut self, mut statistics: Statistics) -> Result<Statistics> {
+        for point in self.points() {
+            statistics.add_point(&point?);
+        }
+        Ok(statistics)
+    }
+
+    /// Reads all remaining points and counts them by classification code.
+    ///
+    /// Use `statistics` if you need other statistics too, so the points are only read once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let counts = reader.count_by_classification().unwrap();
+    /// assert_eq!(reader.header().number_of_points(), counts.iter().sum());
+    /// ```
+    pub fn count_by_classification(&mut self) -> Result<[u64; 256]> {
+        self.statistics().map(|statistics| statistics.classification_counts)
+    }
+
+    /// Reads all remaining points and counts the ones with the given classification code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let ground = reader.count_of(2).unwrap();
+    /// ```
+    pub fn count_of(&mut self, classification: u8) -> Result<u64> {
+        self.statistics().map(|statistics| statistics.count_of(classification))
+    }
+}
+
+impl Reader<BufReader<File>> {
+    /// Creates a new reader from a path.
+    ///
+    /// The underlying `File` is wrapped in a `BufReader` for performance reasons.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>> {
+        File::open(path).map_err(::Error::from).and_then(|file| {
+            Reader::new(BufReader::new(file))
+        })
+    }
+
+    /// Creates a new reader from a path, with the given `BufReader` capacity in bytes.
+    ///
+    /// `from_path` uses the default capacity, which is fine for most uses. A larger capacity can
+    /// help when reading large files from slow or networked storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::Reader;
+    /// let reader = Reader::with_capacity("tests/data/autzen.las", 1 << 20).unwrap();
+    /// ```
+    pub fn with_capacity<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+    ) -> Result<Reader<BufReader<File>>> {
+        File::open(path).map_err(::Error::from).and_then(|file| {
+            Reader::new(BufReader::with_capacity(capacity, file))
+        })
+    }
+}
+
+/// Reads just the las version from the start of las data.
+///
+/// Only the first 26 bytes are read: the file signature, which must be "LASF", then the fields
+/// up to and including the version. The rest of the header and the vlrs aren't parsed, so this is
+/// much faster than creating a `Reader` when scanning many files.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use las::{Version, peek_version};
+/// let mut file = File::open("tests/data/autzen.las").unwrap();
+/// assert_eq!(Version::new(1, 2), peek_version(&mut file).unwrap());
+/// ```
+pub fn peek_version<R: Read>(read: &mut R) -> Result<Version> {
+    let mut bytes = [0; 26];
+    read.read_exact(&mut bytes)?;
+    if bytes[0..4] != raw::LASF {
+        let signature = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        return Err(::header::Error::FileSignature(signature).into());
+    }
+    Ok(Version::new(bytes[24], bytes[25]))
+}
+
+/// Parses las data that are already in memory, returning the header and a lazy iterator over the
+/// points.
+///
+/// The header and vlrs are parsed up front, just like `Reader::new`. The points are decoded
+/// directly from their slices of `bytes` as the iterator is advanced, without going through a
+/// `Read` or `Seek`. If `bytes` ends before the last point, the iterator returns an error for the
+/// first point that doesn't fit and then stops.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use std::io::Read;
+/// use las::points_from_bytes;
+/// let mut bytes = Vec::new();
+/// File::open("tests/data/autzen.las").unwrap().read_to_end(&mut bytes).unwrap();
+/// let (header, points) = points_from_bytes(&bytes).unwrap();
+/// assert_eq!(header.number_of_points() as usize, points.count());
+/// ```
+pub fn points_from_bytes(bytes: &[u8]) -> Result<(Header, PointsFromBytes<'_>)> {
+    let reader = Reader::new(Cursor::new(bytes))?;
+    let offset_to_point_data = reader.offset_to_point_data as usize;
+    let header = reader.header;
+    Ok((
+        header.clone(),
+        PointsFromBytes {
+            bytes: bytes.get(offset_to_point_data..).unwrap_or(&[]),
+            format: header.point_format(),
+            index: 0,
+            number_of_points: header.number_of_points(),
+            offset: offset_to_point_data as u64,
+            transforms: header.transforms(),
+        },
+    ))
+}
+
+/// An iterator over of the points in a `Reader`.
+///
+/// This struct is generally created by calling `points()` on `Reader`.
+#[derive(Debug)]
+pub struct Points<'a, R: 'a + Read + Seek> {
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R: Read + Seek> Iterator for Points<'a, R> {
+    type Item = Result<Point>;
+    fn next(&mut self) -> Option<Result<Point>> {
+        match self.reader.read() {
+            Ok(None) => None,
+            Ok(Some(point)) => Some(Ok(point)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// An iterator over the points in a `Reader` that are inside a polygon.
+///
+/// This struct is generally created by calling `points_in_polygon()` on `Reader`.
+#[derive(Debug)]
+pub struct PointsInPolygon<'a, R: 'a + Read + Seek> {
+    polygon: &'a [Vector<f64>],
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R: Read + Seek> Iterator for PointsInPolygon<'a, R> {
+    type Item = Result<Point>;
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            match self.reader.read() {
+                Ok(None) => return None,
+                Ok(Some(point)) => {
+                    if polygon_contains(self.polygon, point.x, point.y) {
+                        return Some(Ok(point));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+fn polygon_contains(polygon: &[Vector<f64>], x: f64, y: f64) -> bool {
+    let mut is_inside = false;
+    let mut j = match polygon.len() {
+        0 => return false,
+        n => n - 1,
+    };
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a.y > y) != (b.y > y) && x < (b.x - a.x) * (y - a.y) / (b.y - a.y) + a.x {
+            is_inside = !is_inside;
+        }
+        j = i;
+    }
+    is_inside
+}
+
+/// An iterator over the points in a `Reader`, from the last to the first.
+///
+/// This struct is generally created by calling `points_rev()` on `Reader`.
+#[derive(Debug)]
+pub struct PointsRev<'a, R: 'a + Read + Seek> {
+    reader: &'a mut Reader<R>,
+    remaining: u64,
+}
+
+impl<'a, R: Read + Seek> Iterator for PointsRev<'a, R> {
+    type Item = Result<Point>;
+    fn next(&mut self) -> Option<Result<Point>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if let Err(err) = self.reader.seek(self.remaining) {
+            return Some(Err(err));
+        }
+        match self.reader.read() {
+            Ok(None) => None,
+            Ok(Some(point)) => Some(Ok(point)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Everything in las data that comes before the points, and the evlrs after them.
+///
+/// This is shared by `Reader` and `AsyncReader`, which only differ in how they do their io.
+#[derive(Debug)]
+pub(crate) struct Preamble {
+    builder: Builder,
+    evlr: Option<raw::header::Evlr>,
+    pub(crate) offset_to_end_of_points: u64,
+    pub(crate) offset_to_point_data: u64,
+    unknown_point_format: Option<u8>,
+    warnings: Vec<Warning>,
+}
+
+impl Preamble {
+    /// Reads the header, vlrs, and vlr padding, leaving `read` at the start of the point data.
+    pub(crate) fn read_from<R: Read>(mut read: R, options: Options) -> Result<Preamble> {
+        use feature::Evlrs;
+
+        let mut raw_header = raw::Header::read_from(&mut read).with_phase(Phase::ReadingHeader)?;
+        let mut warnings = Vec::new();
+        let (system_identifier, generating_software) = if options.lenient_strings {
+            (
+                take_lenient_string(
+                    &mut raw_header.system_identifier,
+                    "system_identifier",
+                    &mut warnings,
+                ),
+                take_lenient_string(
+                    &mut raw_header.generating_software,
+                    "generating_software",
+                    &mut warnings,
+                ),
+            )
+        } else {
+            (None, None)
+        };
+        let unknown_point_format = match raw_header.point_data_record_format & 0x7f {
+            n if n > 10 && options.read_unknown_point_formats => {
+                raw_header.point_data_record_format &= 0x80;
+                Some(n)
+            }
+            n if n > 10 => return Err(Error::UnsupportedPointFormat(n).into()),
+            _ => None,
+        };
+        if let Some(point_data_record_length) = options.override_record_length {
+            raw_header.point_data_record_length = point_data_record_length;
+        }
+        let mut position = u64::from(raw_header.header_size);
+        let number_of_variable_length_records = raw_header.number_of_variable_length_records;
+        let offset_to_point_data = u64::from(raw_header.offset_to_point_data);
+        let offset_to_end_of_points = raw_header.offset_to_end_of_points();
+        // Las 1.3 has no evlr fields in its header, but internal waveforms are stored in a single
+        // evlr at the start of the waveform data packet record.
+        let has_las_1_3_internal_waveforms = !raw_header.version.supports::<Evlrs>() &&
+            raw_header.global_encoding & WaveformLocation::Internal.global_encoding_bit() != 0;
+        let evlr = if has_las_1_3_internal_waveforms {
+            raw_header.start_of_waveform_data_packet_record.map(|start| {
+                raw::header::Evlr {
+                    start_of_first_evlr: start,
+                    number_of_evlrs: 1,
+                }
+            })
+        } else {
+            raw_header.evlr
+        };
+
+        let mut builder = Builder::new(raw_header)?;
+        if let Some(system_identifier) = system_identifier {
+            builder.system_identifier = system_identifier;
+        }
+        if let Some(generating_software) = generating_software {
+            builder.generating_software = generating_software;
+        }
+        if builder.point_format.is_compressed {
+            return Err(::Error::Laszip);
+        }
+        if options.repair_vlr_count && position <= offset_to_point_data {
+            let mut region = Vec::new();
+            read.by_ref()
+                .take(offset_to_point_data - position)
+                .read_to_end(&mut region)?;
+            let (vlrs, vlr_padding) = repair_vlrs(region);
+            if vlrs.len() as u32 != number_of_variable_length_records {
+                warnings.push(Warning::VlrCountMismatch {
+                    declared: number_of_variable_length_records,
+                    actual: vlrs.len() as u32,
+                });
+            }
+            builder.vlrs = vlrs;
+            builder.vlr_padding = vlr_padding;
+            position = offset_to_point_data;
+        } else {
+            for _ in 0..number_of_variable_length_records {
+                let vlr = raw::Vlr::read_from(&mut read, false)
+                    .with_phase(Phase::ReadingVlr)
+                    .and_then(Vlr::new)?;
+                position += vlr.len(false) as u64;
+                builder.vlrs.push(vlr);
+            }
+        }
+        if position > offset_to_point_data {
+            return Err(
+                Error::OffsetToPointDataTooSmall(offset_to_point_data as u32).into(),
+            );
+        } else if position < offset_to_point_data {
+            read.by_ref()
+                .take(offset_to_point_data - position)
+                .read_to_end(&mut builder.vlr_padding)?;
+        }
+        Ok(Preamble {
+            builder: builder,
+            evlr: evlr,
+            offset_to_end_of_points: offset_to_end_of_points,
+            offset_to_point_data: offset_to_point_data,
+            unknown_point_format: unknown_point_format,
+            warnings: warnings,
+        })
+    }
+
+    /// Are there any evlrs, i.e. does `read_evlrs_from` need to read anything?
+    pub(crate) fn has_evlrs(&self) -> bool {
+        self.evlr.is_some()
+    }
+
+    /// Reads the point padding and evlrs from `read`, which must be at the end of the points.
+    pub(crate) fn read_evlrs_from<R: Read>(&mut self, mut read: R) -> Result<()> {
+        if let Some(evlr) = self.evlr {
+            if evlr.start_of_first_evlr < self.offset_to_end_of_points {
+                return Err(
+                    Error::OffsetToEvlrsTooSmall(evlr.start_of_first_evlr).into(),
+                );
+            } else if evlr.start_of_first_evlr > self.offset_to_end_of_points {
+                let n = evlr.start_of_first_evlr - self.offset_to_end_of_points;
+                read.by_ref().take(n).read_to_end(
+                    &mut self.builder.point_padding,
+                )?;
+            }
+            for _ in 0..evlr.number_of_evlrs {
+                self.builder.evlrs.push(
+                    raw::Vlr::read_from(&mut read, true)
+                        .with_phase(Phase::ReadingEvlr)
+                        .and_then(Vlr::new)?,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the header, returning it with any warnings about its contents.
+    pub(crate) fn into_header(self) -> Result<(Header, Vec<Warning>)> {
+        let mut warnings = self.warnings;
+        let header = self.builder.into_header()?;
+        warnings.extend(header.duplicate_vlrs().into_iter().map(
+            |(user_id, record_id)| {
+                Warning::DuplicateVlr {
+                    user_id: user_id,
+                    record_id: record_id,
+                }
+            },
+        ));
+        let extra_bytes = usize::from(header.point_format().extra_bytes);
+        match header.extra_bytes_descriptors() {
+            Ok(descriptors) => {
+                let described = descriptors.iter().map(Descriptor::len).sum::<usize>();
+                if !descriptors.is_empty() && described != extra_bytes {
+                    warnings.push(Warning::ExtraBytesLengthMismatch {
+                        described: described as u16,
+                        actual: extra_bytes as u16,
+                    });
+                } else if described < extra_bytes {
+                    warnings.push(Warning::UndescribedExtraBytes {
+                        count: (extra_bytes - described) as u16,
+                    });
+                }
+            }
+            // The extra bytes vlr is there, so the extra bytes aren't undescribed, just unreadable.
+            Err(err) => warnings.push(Warning::NonConforming {
+                version: header.version(),
+                reason: format!("the extra bytes vlr is malformed: {}", err),
+            }),
+        }
+
+        Ok((header, warnings))
+    }
+}
+
+fn is_zero(bounds: Bounds) -> bool {
+    [
+        bounds.min.x,
+        bounds.min.y,
+        bounds.min.z,
+        bounds.max.x,
+        bounds.max.y,
+        bounds.max.z,
+    ].iter()
+        .all(|&n| n == 0.)
+}
+
+/// Reads vlrs from the bytes between the header and the point data until there's no room for
+/// another one, returning the vlrs and the remaining bytes as the vlr padding.
+fn repair_vlrs(region: Vec<u8>) -> (Vec<Vlr>, Vec<u8>) {
+    use byteorder::{ByteOrder, LittleEndian};
+
+    const VLR_HEADER_LEN: usize = 54;
+
+    let mut vlrs = Vec::new();
+    let mut start = 0;
+    while region.len() - start >= VLR_HEADER_LEN {
+        let len = usize::from(LittleEndian::read_u16(&region[start + 20..start + 22]));
+        if start + VLR_HEADER_LEN + len > region.len() {
+            break;
+        }
+        match raw::Vlr::read_from(&region[start..], false).and_then(Vlr::new) {
+            Ok(vlr) => vlrs.push(vlr),
+            Err(_) => break,
+        }
+        start += VLR_HEADER_LEN + len;
+    }
+    (vlrs, region[start..].to_vec())
+}
+
+/// Converts a legacy classification that was written as a negative `i8` into a withheld point.
+fn normalize_negative_classification(flags: &mut raw::point::Flags) {
+    use raw::point::Flags;
+
+    if let Flags::TwoByte(_, ref mut b) = *flags {
+        let n = *b as i8;
+        if (-31..0).contains(&n) {
+            *b = 0b1000_0000 | (-n as u8);
+        }
+    }
+}
+
+fn take_lenient_string(
+    bytes: &mut [u8],
+    field: &'static str,
+    warnings: &mut Vec<Warning>,
+) -> Option<String> {
+    use utils::AsLasStr;
+
+    if (&*bytes).as_las_str().is_ok() {
+        return None;
+    }
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let s = String::from_utf8_lossy(&bytes[..end]).into_owned();
+    warnings.push(Warning::NonUtf8String {
+        field: field,
+        bytes: bytes.to_vec(),
+    });
+    for b in bytes.iter_mut() {
+        *b = 0;
+    }
+    Some(s)
+}
+
+/// An iterator over of the raw points in a `Reader`.
+///
+/// This struct is generally created by calling `raw_points()` on `Reader`.
+#[derive(Debug)]
+pub struct RawPoints<'a, R: 'a + Read + Seek> {
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R: Read + Seek> Iterator for RawPoints<'a, R> {
+    type Item = Result<raw::Point>;
+    fn next(&mut self) -> Option<Result<raw::Point>> {
+        match self.reader.read_raw() {
+            Ok(None) => None,
+            Ok(Some(raw_point)) => Some(Ok(raw_point)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// An iterator over the points in a slice of las data.
+///
+/// This struct is generally created by calling `points_from_bytes`.
+#[derive(Debug)]
+pub struct PointsFromBytes<'a> {
+    bytes: &'a [u8],
+    format: Format,
+    index: u64,
+    number_of_points: u64,
+    offset: u64,
+    transforms: Vector<Transform>,
+}
+
+impl<'a> Iterator for PointsFromBytes<'a> {
+    type Item = Result<Point>;
+    fn next(&mut self) -> Option<Result<Point>> {
+        if self.index >= self.number_of_points {
+            return None;
+        }
+        let len = usize::from(self.format.len()).min(self.bytes.len());
+        let (record, bytes) = self.bytes.split_at(len);
+        self.bytes = bytes;
+        let result = raw::Point::read_from(record, self.format)
+            .with_phase(Phase::ReadingPoint {
+                index: self.index,
+                offset: self.offset,
+            })
+            .map(|raw_point| Point::new(raw_point, self.transforms));
+        self.index = if result.is_ok() {
+            self.index + 1
+        } else {
+            self.number_of_points
+        };
+        self.offset += len as u64;
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.number_of_points - self.index;
+        let complete = (self.bytes.len() / usize::from(self.format.len())) as u64;
+        // A point that doesn't fit in the bytes is returned as one error, and then nothing else.
+        let n = remaining.min(complete + 1) as usize;
+        (n, Some(n))
+    }
+}
+
+#[cfg(feature = "proj")]
+fn is_projection(vlr: &Vlr) -> bool {
+    vlr.user_id == "LASF_Projection"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Writer;
+    use std::io::Cursor;
+
+    #[test]
+    fn duplicate_vlrs() {
+        let mut builder = Builder::default();
+        for data in 0..2 {
+            builder.vlrs.push(Vlr {
+                user_id: "LASF_Projection".to_string(),
+                record_id: 2112,
+                data: vec![data],
+                ..Default::default()
+            });
+        }
+        let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(2, reader.header().vlrs().len());
+        assert_eq!(
+            vec![
+                Warning::DuplicateVlr {
+                    user_id: "LASF_Projection".to_string(),
+                    record_id: 2112,
+                },
+            ],
+            reader.warnings()
+        );
+    }
+
+    #[test]
+    fn multiple_evlrs() {
+        let mut builder = Builder::from((1, 4));
+        for record_id in 1..3 {
+            builder.evlrs.push(Vlr {
+                user_id: "evlr test".to_string(),
+                record_id: record_id,
+                data: vec![record_id as u8; 3],
+                ..Default::default()
+            });
+        }
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        for _ in 0..3 {
+            writer.write(Default::default()).unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        {
+            let evlrs = reader.header().evlrs();
+            assert_eq!(2, evlrs.len());
+            for (evlr, record_id) in evlrs.iter().zip(1..3) {
+                assert_eq!("evlr test", evlr.user_id);
+                assert_eq!(record_id, evlr.record_id);
+                assert_eq!(vec![record_id as u8; 3], evlr.data);
+            }
+        }
+        assert_eq!(3, reader.points().count());
+        assert!(reader.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn large_evlr() {
+        use byteorder::{ByteOrder, LittleEndian};
+
+        let data = (0..70_001u32).map(|n| n as u8).collect::<Vec<_>>();
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: "evlr test".to_string(),
+            data: data.clone(),
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        let cursor = writer.into_inner().unwrap();
+        let raw_header = raw::Header::read_from(&cursor.get_ref()[..]).unwrap();
+        let start = raw_header.evlr.unwrap().start_of_first_evlr as usize;
+        assert_eq!(
+            70_001,
+            LittleEndian::read_u64(&cursor.get_ref()[start + 20..start + 28])
+        );
+        let mut reader = Reader::new(cursor).unwrap();
+        assert_eq!(data, reader.header().evlrs()[0].data);
+        assert_eq!(1, reader.points().count());
+    }
+
+    #[test]
+    fn size_report_trailing_bytes() {
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        let mut bytes = writer.into_inner().unwrap().into_inner();
+        let len = bytes.len() as u64;
+        bytes.extend_from_slice(&[0; 42]);
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let size_report = reader.size_report().unwrap();
+        assert_eq!(
+            u64::from(reader.header().point_format().len()),
+            size_report.point_data_size
+        );
+        assert_eq!(len, size_report.declared_size);
+        assert_eq!(len + 42, size_report.actual_size);
+        assert!(!size_report.consistent);
+        assert!(reader.read().unwrap().is_some());
+    }
+
+    #[test]
+    fn validate_bounds() {
+        use raw::point::Flags;
+
+        let mut writer = Writer::default();
+        writer
+            .write(Point {
+                x: 1.,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        let offset_to_point_data = Reader::new(&mut cursor).unwrap().offset_to_point_data;
+        cursor.set_position(offset_to_point_data);
+        raw::Point {
+            x: 1002,
+            flags: Flags::TwoByte(0b00001001, 0),
+            ..Default::default()
+        }.write_to(&mut cursor, Default::default())
+            .unwrap();
+        cursor.set_position(0);
+
+        let mut reader = Reader::new(&mut cursor).unwrap();
+        reader.read().unwrap().unwrap();
+        assert!(reader.warnings().is_empty());
+
+        cursor.set_position(0);
+        let options = Options {
+            validate_bounds: true,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(cursor, options).unwrap();
+        assert!(reader.warnings().is_empty());
+        reader.read().unwrap().unwrap();
+        assert_eq!(1, reader.warnings().len());
+        match reader.warnings()[0] {
+            Warning::PointOutOfBounds { index, x, .. } => {
+                assert_eq!(0, index);
+                assert!(x > 1.);
+            }
+            ref warning => panic!("unexpected warning: {}", warning),
+        }
+    }
+
+    #[test]
+    fn validate_gps_time() {
+        use GpsTimeType;
+
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(1).unwrap();
+        builder.gps_time_type = GpsTimeType::Week;
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        for &gps_time in &[0., 604_800., 604_801., 1.2e9] {
+            writer
+                .write(Point {
+                    gps_time: Some(gps_time),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let options = Options {
+            validate_gps_time: true,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(writer.into_inner().unwrap(), options).unwrap();
+        reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            vec![
+                Warning::GpsTimeOutOfRange {
+                    index: 2,
+                    gps_time: 604_801.,
+                    gps_time_type: GpsTimeType::Week,
+                },
+                Warning::GpsTimeOutOfRange {
+                    index: 3,
+                    gps_time: 1.2e9,
+                    gps_time_type: GpsTimeType::Week,
+                },
+            ],
+            reader.warnings()
+        );
+    }
+
+    #[test]
+    fn points_by_source() {
+        let mut writer = Writer::default();
+        for &point_source_id in &[1, 2, 1] {
+            writer
+                .write(Point {
+                    point_source_id: point_source_id,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let points_by_source = reader.points_by_source().unwrap();
+        assert_eq!(2, points_by_source.len());
+        assert_eq!(2, points_by_source[&1].len());
+        assert_eq!(1, points_by_source[&2].len());
+    }
+
+    #[test]
+    fn truncated_point() {
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        writer.write(Default::default()).unwrap();
+        let mut data = writer.into_inner().unwrap().into_inner();
+        let len = data.len();
+        data.truncate(len - 1);
+        let mut reader = Reader::new(Cursor::new(data)).unwrap();
+        reader.read().unwrap().unwrap();
+        let offset_to_point_data = reader.offset_to_point_data;
+        match reader.read() {
+            Err(::Error::IoDuring(Phase::ReadingPoint { index, offset }, _)) => {
+                assert_eq!(1, index);
+                assert_eq!(offset_to_point_data + 20, offset);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn compression_bit() {
+        let raw_header = raw::Header {
+            point_data_record_format: 0x81,
+            point_data_record_length: 28,
+            ..Default::default()
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        raw_header.write_to(&mut cursor).unwrap();
+        cursor.set_position(0);
+        match Reader::new(cursor) {
+            Err(::Error::Laszip) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn raw_points() {
+        let mut writer = Writer::default();
+        writer
+            .write(Point {
+                x: 1.,
+                y: -2.,
+                z: 3.5,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let transforms = reader.header().transforms();
+        let raw_points = reader.raw_points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(1, raw_points.len());
+        assert_eq!(((1. - transforms.x.offset) / transforms.x.scale).round() as i32, raw_points[0].x);
+        assert_eq!(((-2. - transforms.y.offset) / transforms.y.scale).round() as i32, raw_points[0].y);
+        assert_eq!(((3.5 - transforms.z.offset) / transforms.z.scale).round() as i32, raw_points[0].z);
+        assert_eq!(1000, raw_points[0].x);
+    }
+
+    #[test]
+    fn lenient_strings() {
+        let mut raw_header = raw::Header::default();
+        raw_header.system_identifier[0] = b'a';
+        raw_header.system_identifier[1] = 0xE9;
+        let mut cursor = Cursor::new(Vec::new());
+        raw_header.write_to(&mut cursor).unwrap();
+        cursor.set_position(0);
+        assert!(Reader::new(&mut cursor).is_err());
+
+        cursor.set_position(0);
+        let options = Options {
+            lenient_strings: true,
+            ..Default::default()
+        };
+        let reader = Reader::with_options(cursor, options).unwrap();
+        assert_eq!("a\u{FFFD}", reader.header().system_identifier());
+        assert_eq!(
+            vec![
+                Warning::NonUtf8String {
+                    field: "system_identifier",
+                    bytes: raw_header.system_identifier.to_vec(),
+                },
+            ],
+            reader.warnings()
+        );
+    }
+
+    #[test]
+    fn header_size_larger_than_version() {
+        let vlr = Vlr {
+            user_id: "gadomski".to_string(),
+            record_id: 42,
+            data: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let raw_header = raw::Header {
+            header_size: 237,
+            offset_to_point_data: 237 + vlr.len(false) as u32,
+            number_of_variable_length_records: 1,
+            padding: vec![0; 10],
+            ..Default::default()
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        raw_header.write_to(&mut cursor).unwrap();
+        vlr.clone()
+            .into_raw(false)
+            .unwrap()
+            .write_to(&mut cursor)
+            .unwrap();
+        cursor.set_position(0);
+        let reader = Reader::new(cursor).unwrap();
+        assert_eq!(vec![0; 10], *reader.header().padding());
+        assert_eq!(vec![vlr], *reader.header().vlrs());
+    }
+
+    #[cfg(feature = "proj")]
+    #[test]
+    fn reproject() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        reader.reproject("EPSG:2992", "EPSG:4326").unwrap();
+        assert!(reader.header().all_vlrs().all(|vlr| !is_projection(vlr)));
+        let point = reader.read().unwrap().unwrap();
+        assert!(point.x > -124. && point.x < -122.);
+        assert!(point.y > 43. && point.y < 45.);
+        let original = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .read()
+            .unwrap()
+            .unwrap();
+        assert_eq!(original.z, point.z);
+    }
+
+    #[test]
+    fn seek() {
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        let point = Point {
+            x: 1.,
             y: 2.,
             z: 3.,
             ..Default::default()
@@ -267,5 +1861,665 @@ mod tests {
         let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
         reader.seek(1).unwrap();
         assert_eq!(point, reader.read().unwrap().unwrap());
+        assert_eq!(None, reader.read().unwrap());
+    }
+
+    #[test]
+    fn resume_from_position() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let expected = reader.points().collect::<Result<Vec<_>>>().unwrap();
+
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        for point in reader.points().take(50) {
+            point.unwrap();
+        }
+        let position = reader.position();
+        assert_eq!(50, position);
+
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        reader.seek(position).unwrap();
+        let rest = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(&expected[50..], &rest[..]);
+    }
+
+    #[test]
+    fn read_until_bytes() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert_eq!(28, reader.header().point_format().len());
+        let expected = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        reader.seek(0).unwrap();
+        assert_eq!(&expected[0..3], &reader.read_until_bytes(100).unwrap()[..]);
+        assert_eq!(&expected[3..5], &reader.read_until_bytes(56).unwrap()[..]);
+        assert_eq!(&expected[5..6], &reader.read_until_bytes(1).unwrap()[..]);
+        assert_eq!(&expected[6..], &reader.read_until_bytes(28 * 1000).unwrap()[..]);
+        assert!(reader.read_until_bytes(100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_soa() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        reader.seek(0).unwrap();
+        let arrays = reader.read_soa(1000).unwrap();
+        assert_eq!(points.len(), arrays.len());
+        for (i, point) in points.iter().enumerate() {
+            assert_eq!(point.x, arrays.xs[i]);
+            assert_eq!(point.y, arrays.ys[i]);
+            assert_eq!(point.z, arrays.zs[i]);
+            assert_eq!(point.intensity, arrays.intensities[i]);
+            assert_eq!(point.return_number, arrays.return_numbers[i]);
+            assert_eq!(u8::from(point.classification), arrays.classifications[i]);
+            assert_eq!(point.is_overlap, arrays.overlaps[i]);
+            assert_eq!(point.gps_time, Some(arrays.gps_times[i]));
+        }
+        assert!(arrays.colors.is_empty());
+        assert!(arrays.nirs.is_empty());
+        assert!(reader.read_soa(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_soa_overlap() {
+        let mut writer = Writer::default();
+        writer
+            .write(Point {
+                is_overlap: true,
+                ..Default::default()
+            })
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let arrays = reader.read_soa(2).unwrap();
+        assert_eq!(vec![true, false], arrays.overlaps);
+        assert_eq!(1, arrays.classifications[0]);
+    }
+
+    #[test]
+    fn points_in_polygon() {
+        let mut writer = Writer::default();
+        for &(x, y) in &[(1., 1.), (5., 1.), (3., 3.9), (3., 4.1), (-1., 1.), (9., 9.)] {
+            writer
+                .write(Point {
+                    x: x,
+                    y: y,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let triangle = vec![
+            Vector {
+                x: 0.,
+                y: 0.,
+                z: 0.,
+            },
+            Vector {
+                x: 6.,
+                y: 0.,
+                z: 0.,
+            },
+            Vector {
+                x: 3.,
+                y: 4.,
+                z: 0.,
+            },
+        ];
+        let points = reader
+            .points_in_polygon(&triangle)
+            .map(|point| point.map(|point| (point.x, point.y)))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(vec![(1., 1.), (5., 1.), (3., 3.9)], points);
+        assert!(!polygon_contains(&[], 0., 0.));
+    }
+
+    #[test]
+    fn repair_vlr_count() {
+        let mut builder = Builder::from((1, 2));
+        for record_id in 1..3 {
+            builder.vlrs.push(Vlr {
+                user_id: "repair".to_string(),
+                record_id: record_id,
+                data: vec![record_id as u8; 3],
+                ..Default::default()
+            });
+        }
+        builder.vlr_padding = vec![42; 10];
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        let vlrs = Reader::new(cursor.clone()).unwrap().header().vlrs().clone();
+        let options = Options {
+            repair_vlr_count: true,
+            ..Default::default()
+        };
+        for &declared in &[1u8, 3] {
+            cursor.get_mut()[100] = declared;
+            let mut reader = Reader::with_options(cursor.clone(), options).unwrap();
+            assert_eq!(&vlrs, reader.header().vlrs());
+            assert_eq!(&vec![42; 10], reader.header().vlr_padding());
+            assert_eq!(
+                &[
+                    Warning::VlrCountMismatch {
+                        declared: u32::from(declared),
+                        actual: 2,
+                    },
+                ],
+                reader.warnings()
+            );
+            assert_eq!(1, reader.points().count());
+        }
+
+        cursor.get_mut()[100] = 1;
+        let reader = Reader::new(cursor).unwrap();
+        assert_eq!(1, reader.header().vlrs().len());
+        assert!(reader.warnings().is_empty());
+    }
+
+    #[test]
+    fn points_from_bytes() {
+        let points = (0..3)
+            .map(|i| {
+                Point {
+                    x: f64::from(i),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut writer = Writer::default();
+        writer.write_chunk(&points).unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+        let (header, points_read) = super::points_from_bytes(&bytes).unwrap();
+        assert_eq!(3, header.number_of_points());
+        assert_eq!(3, points_read.size_hint().0);
+        assert_eq!(points, points_read.collect::<Result<Vec<_>>>().unwrap());
+
+        let (_, points_read) = super::points_from_bytes(&bytes[..bytes.len() - 1]).unwrap();
+        let results = points_read.collect::<Vec<_>>();
+        assert_eq!(3, results.len());
+        match results[2] {
+            Err(::Error::IoDuring(Phase::ReadingPoint { index, .. }, _)) => assert_eq!(2, index),
+            ref result => panic!("unexpected result: {:?}", result),
+        }
+
+        let len = usize::from(header.point_format().len());
+        let (_, points_read) = super::points_from_bytes(&bytes[..bytes.len() - 2 * len]).unwrap();
+        assert_eq!((2, Some(2)), points_read.size_hint());
+        assert_eq!(2, points_read.count());
+    }
+
+    #[test]
+    fn map_in_place() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let bounds = reader.header().bounds();
+        let writer = reader
+            .map_in_place(Cursor::new(Vec::new()), |point| point.z += 10.)
+            .unwrap();
+        let header = writer.header().clone();
+        assert_eq!(reader.header().number_of_points(), header.number_of_points());
+        assert_eq!(reader.header().vlrs(), header.vlrs());
+        assert!((bounds.min.z + 10. - header.bounds().min.z).abs() < 1e-9);
+        assert!((bounds.max.z + 10. - header.bounds().max.z).abs() < 1e-9);
+        let mut other = Reader::new(writer.into_inner().unwrap()).unwrap();
+        for (a, b) in reader.points().zip(other.points()) {
+            assert!((a.unwrap().z + 10. - b.unwrap().z).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn override_record_length() {
+        let mut writer = Writer::default();
+        for &intensity in &[1, 2] {
+            writer
+                .write(Point {
+                    intensity: intensity,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut cursor = writer.into_inner().unwrap();
+        cursor.get_mut()[105] = 18;
+        assert!(Reader::new(cursor.clone()).is_err());
+
+        let options = Options {
+            override_record_length: Some(20),
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(cursor.clone(), options).unwrap();
+        assert_eq!(0, reader.header().point_format().extra_bytes);
+        let intensities = reader
+            .points()
+            .map(|point| point.unwrap().intensity)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2], intensities);
+
+        let options = Options {
+            override_record_length: Some(19),
+            ..Default::default()
+        };
+        match Reader::with_options(cursor, options) {
+            Err(::Error::Header(::header::Error::PointDataRecordLength(_, 19))) => {}
+            result => panic!("unexpected result: {:?}", result.map(|reader| reader.header)),
+        }
+    }
+
+    #[test]
+    fn unknown_point_format() {
+        let mut writer = Writer::default();
+        writer
+            .write(Point {
+                intensity: 42,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        cursor.get_mut()[104] = 15;
+        match Reader::new(cursor.clone()) {
+            Err(::Error::Reader(Error::UnsupportedPointFormat(15))) => {}
+            result => panic!("unexpected result: {:?}", result.map(|reader| reader.header)),
+        }
+
+        let options = Options {
+            read_unknown_point_formats: true,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(cursor.clone(), options).unwrap();
+        assert_eq!(Some(15), reader.unknown_point_format());
+        assert_eq!(1, reader.header().number_of_points());
+        let offset_to_point_data = reader.header().clone().into_raw().unwrap().offset_to_point_data
+            as usize;
+        let point = reader.read().unwrap().unwrap();
+        assert_eq!(
+            Some(&cursor.get_ref()[offset_to_point_data..offset_to_point_data + 20]),
+            point.raw_record.as_ref().map(|record| &record[..])
+        );
+        assert_eq!(0, point.intensity);
+        assert!(reader.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn peek_version() {
+        let header = Builder::from((1, 4)).into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.close().unwrap();
+        let bytes = writer.into_inner().unwrap().into_inner();
+        assert_eq!(Version::new(1, 4), super::peek_version(&mut &bytes[..]).unwrap());
+        assert!(super::peek_version(&mut &b"LASX"[..]).is_err());
+        let mut bytes = bytes;
+        bytes[3] = b'X';
+        assert!(super::peek_version(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn zero_bounds_with_points() {
+        let zeroed = |xs: &[f64]| {
+            let mut writer = Writer::default();
+            for &x in xs {
+                writer
+                    .write(Point {
+                        x: x,
+                        z: -x,
+                        ..Default::default()
+                    })
+                    .unwrap();
+            }
+            let mut cursor = writer.into_inner().unwrap();
+            for b in &mut cursor.get_mut()[179..227] {
+                *b = 0;
+            }
+            cursor
+        };
+        let cursor = zeroed(&[1., 2.]);
+        let expected = vec![Warning::ZeroBoundsWithPoints { number_of_points: 2 }];
+
+        let reader = Reader::new(cursor.clone()).unwrap();
+        assert_eq!(&expected[..], reader.warnings());
+        assert_eq!(0., reader.header().bounds().max.x);
+
+        let options = Options {
+            recompute_zero_bounds: true,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(cursor, options).unwrap();
+        assert_eq!(&expected[..], reader.warnings());
+        let bounds = reader.header().bounds();
+        assert_eq!((1., 2.), (bounds.min.x, bounds.max.x));
+        assert_eq!((-2., -1.), (bounds.min.z, bounds.max.z));
+        assert_eq!(0, reader.position());
+        assert_eq!(2, reader.points().count());
+
+        let cursor = zeroed(&[0., 2.]);
+        assert!(Reader::new(cursor.clone()).unwrap().warnings().is_empty());
+        let reader = Reader::with_options(cursor, options).unwrap();
+        let expected = vec![Warning::ZeroBoundsWithPoints { number_of_points: 2 }];
+        assert_eq!(&expected[..], reader.warnings());
+        let bounds = reader.header().bounds();
+        assert_eq!((0., 2.), (bounds.min.x, bounds.max.x));
+
+        let cursor = zeroed(&[0., 0.]);
+        let reader = Reader::with_options(cursor, options).unwrap();
+        assert!(reader.warnings().is_empty());
+
+        let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        assert!(!reader.warnings().iter().any(|warning| match *warning {
+            Warning::ZeroBoundsWithPoints { .. } => true,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn points_rev() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut expected = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        expected.reverse();
+        let points = reader.points_rev().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(expected, points);
+        assert_eq!(1, reader.position());
+    }
+
+    fn waveform_reader(version: (u8, u8), location: WaveformLocation) -> Reader<Cursor<Vec<u8>>> {
+        use point::Format;
+
+        let mut builder = Builder::from(version);
+        builder.point_format = Format::new(4).unwrap();
+        builder.waveform_location = Some(location);
+        builder.evlrs.push(Vlr {
+            user_id: "LASF_Spec".to_string(),
+            record_id: 65535,
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer
+            .write(Point {
+                gps_time: Some(0.),
+                waveform: Some(raw::point::Waveform {
+                    byte_offset_to_waveform_data: 61,
+                    waveform_packet_size_in_bytes: 2,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .unwrap();
+        Reader::new(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn legacy_quirks() {
+        use point::Classification;
+
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        let offset_to_point_data = Reader::new(cursor.clone())
+            .unwrap()
+            .header()
+            .clone()
+            .into_raw()
+            .unwrap()
+            .offset_to_point_data as usize;
+        cursor.get_mut()[offset_to_point_data + 15] = -2i8 as u8;
+
+        let point = Reader::new(cursor.clone()).unwrap().read().unwrap().unwrap();
+        assert_eq!(Classification::Reserved(30), point.classification);
+        assert!(point.is_withheld);
+        assert!(point.is_key_point);
+        assert!(point.is_synthetic);
+
+        let options = Options {
+            legacy_quirks: true,
+            ..Default::default()
+        };
+        let point = Reader::with_options(cursor, options)
+            .unwrap()
+            .read()
+            .unwrap()
+            .unwrap();
+        assert_eq!(Classification::Ground, point.classification);
+        assert!(point.is_withheld);
+        assert!(!point.is_key_point);
+        assert!(!point.is_synthetic);
+    }
+
+    #[test]
+    fn not_las_data() {
+        let text = b"x,y,z\n1,2,3\n".to_vec();
+        match Reader::new(Cursor::new(text)) {
+            Err(::Error::Header(err @ ::header::Error::FileSignature(_))) => {
+                assert_eq!(
+                    "the file signature was \"x,y,\", not \"LASF\", so this is probably not las \
+                     data",
+                    err.to_string()
+                );
+            }
+            result => panic!("unexpected result: {:?}", result.map(|reader| reader.header)),
+        }
+    }
+
+    #[test]
+    fn no_evlrs_before_las_1_4() {
+        let mut builder = Builder::from((1, 2));
+        builder.padding = vec![232, 3, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(Version::new(1, 2), reader.header().version());
+        assert!(reader.header().evlrs().is_empty());
+        assert_eq!(
+            &[232, 3, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0],
+            reader.header().padding().as_slice()
+        );
+        assert_eq!(1, reader.header().number_of_points());
+        assert!(reader.read().unwrap().is_some());
+        assert!(reader.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn las_1_3_internal_waveforms() {
+        let mut reader = waveform_reader((1, 3), WaveformLocation::Internal);
+        assert!(reader.header().vlrs().is_empty());
+        assert_eq!(1, reader.header().evlrs().len());
+        let offset_to_end_of_points = reader
+            .header()
+            .clone()
+            .into_raw()
+            .unwrap()
+            .offset_to_end_of_points();
+        assert_eq!(
+            Some(offset_to_end_of_points),
+            reader.header().start_of_waveform_data_packet_record().unwrap()
+        );
+        let mut waveforms = reader.internal_waveforms().unwrap();
+        let point = reader.read().unwrap().unwrap();
+        assert_eq!(vec![2, 3], point.read_waveform(&mut waveforms).unwrap());
+    }
+
+    #[test]
+    fn internal_waveforms() {
+        let mut reader = waveform_reader((1, 4), WaveformLocation::Internal);
+        assert_eq!(
+            Some(WaveformLocation::Internal),
+            reader.header().waveform_location()
+        );
+        assert!(reader.open_waveforms("tests/data/autzen.las").is_err());
+        let mut waveforms = reader.internal_waveforms().unwrap();
+        let point = reader.read().unwrap().unwrap();
+        assert_eq!(vec![2, 3], point.read_waveform(&mut waveforms).unwrap());
+    }
+
+    #[test]
+    fn external_waveforms() {
+        let reader = waveform_reader((1, 4), WaveformLocation::External);
+        assert_eq!(
+            Some(WaveformLocation::External),
+            reader.header().waveform_location()
+        );
+        assert!(reader.internal_waveforms().is_err());
+        assert!(reader.open_waveforms("tests/data/autzen.las").is_ok());
+    }
+
+    #[test]
+    fn extra_bytes_length_mismatch() {
+        use extra_bytes::{DataType, Descriptor};
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        builder.vlrs.push(
+            Descriptor::into_vlr(&[Descriptor::new("a", DataType::U32)]).unwrap(),
+        );
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer
+            .write(Point {
+                gps_time: Some(1.),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            vec![
+                Warning::ExtraBytesLengthMismatch {
+                    described: 4,
+                    actual: 0,
+                },
+            ],
+            reader.warnings()
+        );
+        assert_eq!(30, reader.header().point_format().len());
+        assert!(reader.read().unwrap().unwrap().extra_bytes.is_empty());
+        assert!(reader.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn undescribed_extra_bytes() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format.extra_bytes = 3;
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        let point = Point {
+            extra_bytes: vec![1, 2, 3],
+            ..Default::default()
+        };
+        writer.write(point.clone()).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            vec![Warning::UndescribedExtraBytes { count: 3 }],
+            reader.warnings()
+        );
+        assert_eq!(20 + 3, reader.header().point_format().len());
+        assert_eq!(point, reader.read().unwrap().unwrap());
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        writer.write(point.clone()).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(point, reader.read().unwrap().unwrap());
+    }
+
+    #[test]
+    fn malformed_extra_bytes_vlr() {
+        use extra_bytes::{RECORD_ID, USER_ID};
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 2;
+        builder.vlrs.push(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            data: vec![0; 10],
+            ..Default::default()
+        });
+        let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(1, reader.warnings().len());
+        match reader.warnings()[0] {
+            Warning::NonConforming { version, .. } => assert_eq!(Version::new(1, 4), version),
+            ref warning => panic!("unexpected warning: {:?}", warning),
+        }
+    }
+
+    #[test]
+    fn described_extra_bytes() {
+        use extra_bytes::DataType;
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 2;
+        builder
+            .vlrs
+            .push(Descriptor::into_vlr(&[Descriptor::new("a", DataType::U16)]).unwrap());
+        let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert!(reader.warnings().is_empty());
+    }
+
+    #[test]
+    fn metadata_only_skips_points() {
+        use std::io;
+
+        struct NoPoints {
+            cursor: Cursor<Vec<u8>>,
+            points: ::std::ops::Range<u64>,
+        }
+        impl Read for NoPoints {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let position = self.cursor.position();
+                let end = position + buf.len() as u64;
+                if position < self.points.end && self.points.start < end {
+                    let n = if position < self.points.start {
+                        (self.points.start - position) as usize
+                    } else {
+                        panic!("read point data at {}", position)
+                    };
+                    self.cursor.read(&mut buf[..n])
+                } else {
+                    self.cursor.read(buf)
+                }
+            }
+        }
+        impl Seek for NoPoints {
+            fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+                self.cursor.seek(pos)
+            }
+        }
+
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            user_id: "evlr".to_string(),
+            data: vec![42; 3],
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        for _ in 0..10 {
+            writer.write(Default::default()).unwrap();
+        }
+        let mut cursor = writer.into_inner().unwrap();
+        cursor.set_position(0);
+        let raw_header = raw::Header::read_from(&mut cursor).unwrap();
+        let start = u64::from(raw_header.offset_to_point_data);
+        let end = raw_header.offset_to_end_of_points();
+        cursor.set_position(0);
+
+        let header = Reader::metadata_only(NoPoints {
+            cursor: cursor,
+            points: start..end,
+        }).unwrap();
+        assert_eq!(10, header.number_of_points());
+        assert_eq!(1, header.evlrs().len());
+        assert_eq!(vec![42; 3], header.evlrs()[0].data);
+    }
+
+    #[test]
+    fn las_1_0_reserved_bytes() {
+        let mut cursor = Writer::new(Cursor::new(Vec::new()), Header::from((1, 0)))
+            .unwrap()
+            .into_inner()
+            .unwrap();
+        cursor.get_mut()[4..8].copy_from_slice(&[1, 2, 3, 4]);
+        let reader = Reader::new(cursor).unwrap();
+        assert_eq!(0, reader.header().file_source_id());
+        assert!(!reader.header().gps_time_type().is_standard());
     }
 }
@@ -0,0 +1,367 @@
+use {Bounds, Error, Point, Result};
+use std::collections::HashMap;
+
+/// How `Statistics` computes percentiles.
+///
+/// Intensity percentiles are always exact, since intensities are counted in a fixed table of
+/// 65,536 bins (512 KiB). Z percentiles are the tradeoff:
+///
+/// - `Exact` buffers every z value, which takes eight bytes per point, and sorts them when the
+///   percentiles are requested.
+/// - `Histogram` counts z values in bins of the given width, so memory only depends on the range
+///   of z values divided by the width. The percentiles are the centers of their bins, so they are
+///   within half a bin width of the exact values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PercentileMode {
+    /// Buffer all z values, and compute exact percentiles.
+    Exact,
+    /// Count z values in bins of this width, and compute approximate percentiles.
+    ///
+    /// The width must be finite and positive.
+    Histogram(f64),
+}
+
+/// Percentiles of the z values and intensities of a set of points.
+///
+/// Percentiles use the nearest-rank method, so they are always one of the values (or, for
+/// histograms, one of the bin centers).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Percentiles {
+    /// The requested percentiles, from 0 to 100.
+    pub levels: Vec<f64>,
+    /// The z value at each percentile.
+    pub z: Vec<f64>,
+    /// The intensity at each percentile.
+    pub intensity: Vec<u16>,
+}
+
+#[derive(Clone, Debug)]
+enum ZValues {
+    Exact(Vec<f64>),
+    Histogram(f64, HashMap<i64, u64>),
+}
+
+#[derive(Clone, Debug)]
+struct PercentileAccumulator {
+    intensity_counts: Vec<u64>,
+    z: ZValues,
+}
+
+/// Summary statistics about a set of points, computed in a single pass.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Point, Statistics};
+/// use las::point::Classification;
+///
+/// let mut statistics = Statistics::new();
+/// statistics.add_point(&Point { classification: Classification::Ground, ..Default::default() });
+/// assert_eq!(1, statistics.number_of_points);
+/// assert_eq!(1, statistics.count_of(2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Statistics {
+    /// The number of points.
+    pub number_of_points: u64,
+
+    /// The bounds of the points.
+    pub bounds: Bounds,
+
+    /// The number of points of each classification, indexed by classification code.
+    pub classification_counts: [u64; 256],
+
+    /// The number of points that are flagged as overlap points.
+    ///
+    /// Overlap is a flag, not a class, so overlap points are also counted under their
+    /// classification in `classification_counts`. Subtract this from the number of points to
+    /// exclude overlap from e.g. density calculations.
+    pub number_of_overlap_points: u64,
+
+    percentiles: Option<PercentileAccumulator>,
+}
+
+impl Statistics {
+    /// Creates new, empty statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Statistics;
+    /// let statistics = Statistics::new();
+    /// assert_eq!(0, statistics.number_of_points);
+    /// ```
+    pub fn new() -> Statistics {
+        Statistics {
+            number_of_points: 0,
+            bounds: Bounds::default(),
+            classification_counts: [0; 256],
+            number_of_overlap_points: 0,
+            percentiles: None,
+        }
+    }
+
+    /// Creates new, empty statistics that also track z and intensity percentiles.
+    ///
+    /// See `PercentileMode` for the memory and accuracy tradeoff. Returns an error if a histogram's
+    /// bin width isn't finite and positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{PercentileMode, Statistics};
+    /// let exact = Statistics::with_percentiles(PercentileMode::Exact).unwrap();
+    /// let approximate = Statistics::with_percentiles(PercentileMode::Histogram(0.01)).unwrap();
+    /// assert!(Statistics::with_percentiles(PercentileMode::Histogram(0.)).is_err());
+    /// ```
+    pub fn with_percentiles(mode: PercentileMode) -> Result<Statistics> {
+        let z = match mode {
+            PercentileMode::Exact => ZValues::Exact(Vec::new()),
+            PercentileMode::Histogram(width) => {
+                if !width.is_finite() || width <= 0. {
+                    return Err(Error::InvalidHistogramWidth(width));
+                }
+                ZValues::Histogram(width, HashMap::new())
+            }
+        };
+        Ok(Statistics {
+            percentiles: Some(PercentileAccumulator {
+                intensity_counts: vec![0; 1 << 16],
+                z: z,
+            }),
+            ..Statistics::new()
+        })
+    }
+
+    /// Adds a point to these statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut statistics = Statistics::new();
+    /// statistics.add_point(&Point { x: 1., ..Default::default() });
+    /// assert_eq!(1., statistics.bounds.max.x);
+    /// ```
+    pub fn add_point(&mut self, point: &Point) {
+        self.number_of_points += 1;
+        self.bounds.grow(point);
+        self.classification_counts[u8::from(point.classification) as usize] += 1;
+        if point.is_overlap {
+            self.number_of_overlap_points += 1;
+        }
+        if let Some(ref mut percentiles) = self.percentiles {
+            percentiles.intensity_counts[point.intensity as usize] += 1;
+            match percentiles.z {
+                ZValues::Exact(ref mut values) => values.push(point.z),
+                ZValues::Histogram(width, ref mut bins) => {
+                    *bins.entry((point.z / width).floor() as i64).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the number of points with the given classification code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Statistics};
+    /// let mut statistics = Statistics::new();
+    /// statistics.add_point(&Point::default());
+    /// assert_eq!(1, statistics.count_of(0));
+    /// assert_eq!(0, statistics.count_of(2));
+    /// ```
+    pub fn count_of(&self, classification: u8) -> u64 {
+        self.classification_counts[classification as usize]
+    }
+
+    /// Returns the z and intensity percentiles at each level, from 0 to 100.
+    ///
+    /// Returns `None` if these statistics weren't created with `with_percentiles`, or if there
+    /// aren't any points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{PercentileMode, Point, Statistics};
+    /// let mut statistics = Statistics::with_percentiles(PercentileMode::Exact).unwrap();
+    /// for i in 0..100 {
+    ///     statistics.add_point(&Point { z: f64::from(i + 1), ..Default::default() });
+    /// }
+    /// let percentiles = statistics.percentiles(&[5., 50., 95.]).unwrap();
+    /// assert_eq!(vec![5., 50., 95.], percentiles.z);
+    /// assert!(Statistics::new().percentiles(&[50.]).is_none());
+    /// ```
+    pub fn percentiles(&self, levels: &[f64]) -> Option<Percentiles> {
+        let accumulator = match self.percentiles {
+            Some(ref accumulator) if self.number_of_points > 0 => accumulator,
+            _ => return None,
+        };
+        let n = self.number_of_points;
+        let ranks = levels.iter().map(|&level| rank(level, n)).collect::<Vec<_>>();
+        let z = match accumulator.z {
+            ZValues::Exact(ref values) => {
+                let mut values = values.clone();
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal));
+                ranks.iter().map(|&rank| values[rank as usize - 1]).collect()
+            }
+            ZValues::Histogram(width, ref bins) => {
+                let mut bins = bins.iter().collect::<Vec<_>>();
+                bins.sort();
+                ranks
+                    .iter()
+                    .map(|&rank| {
+                        let bin = nearest_rank(bins.iter().map(|&(&bin, &count)| (bin, count)), rank);
+                        (bin as f64 + 0.5) * width
+                    })
+                    .collect()
+            }
+        };
+        let intensity = ranks
+            .iter()
+            .map(|&rank| {
+                nearest_rank(
+                    accumulator.intensity_counts.iter().cloned().enumerate(),
+                    rank,
+                ) as u16
+            })
+            .collect();
+        Some(Percentiles {
+            levels: levels.to_vec(),
+            z: z,
+            intensity: intensity,
+        })
+    }
+}
+
+/// Returns the one-indexed nearest rank of a percentile.
+fn rank(level: f64, n: u64) -> u64 {
+    let rank = (level / 100. * n as f64).ceil() as u64;
+    if rank < 1 {
+        1
+    } else if rank > n {
+        n
+    } else {
+        rank
+    }
+}
+
+/// Returns the value whose cumulative count first reaches the rank, from sorted value counts.
+fn nearest_rank<T: Copy, I: Iterator<Item = (T, u64)>>(counts: I, rank: u64) -> T {
+    let mut cumulative = 0;
+    let mut last = None;
+    for (value, count) in counts {
+        if count == 0 {
+            continue;
+        }
+        cumulative += count;
+        last = Some(value);
+        if cumulative >= rank {
+            break;
+        }
+    }
+    last.expect("there is at least one value")
+}
+
+impl Default for Statistics {
+    fn default() -> Statistics {
+        Statistics::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Classification;
+
+    #[test]
+    fn add_point() {
+        let mut statistics = Statistics::new();
+        for &classification in &[Classification::Ground, Classification::Ground, Classification::Water] {
+            statistics.add_point(&Point {
+                classification: classification,
+                ..Default::default()
+            });
+        }
+        assert_eq!(3, statistics.number_of_points);
+        assert_eq!(2, statistics.count_of(2));
+        assert_eq!(1, statistics.count_of(9));
+        assert_eq!(3, statistics.classification_counts.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn overlap_points() {
+        use {Builder, Reader, Writer};
+        use point::Format;
+        use std::io::Cursor;
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        for &is_overlap in &[true, false, true, false, false] {
+            writer
+                .write(Point {
+                    classification: Classification::Ground,
+                    is_overlap: is_overlap,
+                    gps_time: Some(0.),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let statistics = reader.statistics().unwrap();
+        assert_eq!(5, statistics.number_of_points);
+        assert_eq!(2, statistics.number_of_overlap_points);
+        assert_eq!(5, statistics.count_of(2));
+        assert_eq!(0, statistics.count_of(12));
+    }
+
+    #[test]
+    fn exact_percentiles() {
+        let mut statistics = Statistics::with_percentiles(PercentileMode::Exact).unwrap();
+        for &(z, intensity) in &[(3., 30), (1., 10), (2., 20), (4., 40)] {
+            statistics.add_point(&Point {
+                z: z,
+                intensity: intensity,
+                ..Default::default()
+            });
+        }
+        let percentiles = statistics.percentiles(&[0., 25., 50., 100.]).unwrap();
+        assert_eq!(vec![1., 1., 2., 4.], percentiles.z);
+        assert_eq!(vec![10, 10, 20, 40], percentiles.intensity);
+    }
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut exact = Statistics::with_percentiles(PercentileMode::Exact).unwrap();
+        let mut histogram = Statistics::with_percentiles(PercentileMode::Histogram(0.1)).unwrap();
+        for i in 0..1000 {
+            let point = Point {
+                z: (f64::from(i) * 0.37) % 50.,
+                ..Default::default()
+            };
+            exact.add_point(&point);
+            histogram.add_point(&point);
+        }
+        let levels = [5., 50., 95.];
+        let exact = exact.percentiles(&levels).unwrap();
+        let histogram = histogram.percentiles(&levels).unwrap();
+        for (a, b) in exact.z.iter().zip(&histogram.z) {
+            assert!((a - b).abs() <= 0.05 + 1e-9, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn invalid_histogram_width() {
+        use std::f64;
+
+        for &width in &[0., -0.1, f64::NAN, f64::INFINITY] {
+            match Statistics::with_percentiles(PercentileMode::Histogram(width)) {
+                Err(::Error::InvalidHistogramWidth(_)) => {}
+                result => panic!("unexpected result for {}: {:?}", width, result),
+            }
+        }
+    }
+}
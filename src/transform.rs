@@ -1,4 +1,4 @@
-use Result;
+use {Bounds, Result, Vector};
 use std::fmt;
 
 /// A scale and an offset that transforms xyz coordinates.
@@ -46,6 +46,57 @@ impl Transform {
             Ok(n as i32)
         }
     }
+
+    /// Picks transforms that represent everything in the bounds with the target precision.
+    ///
+    /// The offsets are the floored minimums of the bounds, and the scales are the target
+    /// precision, so coordinates are off by at most half of the target precision after a write
+    /// and read. If a dimension's range is too large to fit into an i32 at that scale, its scale
+    /// is coarsened by factors of ten until it fits. If the bounds are empty, the default
+    /// transforms are returned.
+    ///
+    /// Returns an error if the target precision isn't finite and positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Transform, Vector};
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 500000.5, y: 4000000.5, z: -10. },
+    ///     max: Vector { x: 501000.5, y: 4001000.5, z: 200. },
+    /// };
+    /// let transforms = Transform::best_for_bounds(bounds, 0.01).unwrap();
+    /// assert_eq!(500000., transforms.x.offset);
+    /// assert_eq!(0.01, transforms.x.scale);
+    /// assert!(Transform::best_for_bounds(bounds, 0.).is_err());
+    /// ```
+    pub fn best_for_bounds(bounds: Bounds, target_precision: f64) -> Result<Vector<Transform>> {
+        use Error;
+
+        if !target_precision.is_finite() || target_precision <= 0. {
+            return Err(Error::InvalidPrecision(target_precision));
+        }
+        Ok(Vector {
+            x: Transform::best_for_range(bounds.min.x, bounds.max.x, target_precision),
+            y: Transform::best_for_range(bounds.min.y, bounds.max.y, target_precision),
+            z: Transform::best_for_range(bounds.min.z, bounds.max.z, target_precision),
+        })
+    }
+
+    fn best_for_range(min: f64, max: f64, target_precision: f64) -> Transform {
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return Transform::default();
+        }
+        let offset = min.floor();
+        let mut scale = target_precision;
+        while (max - offset) / scale > f64::from(i32::MAX) {
+            scale *= 10.;
+        }
+        Transform {
+            scale: scale,
+            offset: offset,
+        }
+    }
 }
 
 impl Default for Transform {
@@ -81,4 +132,81 @@ mod tests {
         let n = i32::MIN as f64 * transform.scale - 1.;
         assert!(transform.inverse(n).is_err());
     }
+
+    #[test]
+    fn best_for_bounds() {
+        use Vector;
+
+        let bounds = Bounds {
+            min: Vector {
+                x: 635_589.01,
+                y: 848_886.45,
+                z: 406.59,
+            },
+            max: Vector {
+                x: 638_994.75,
+                y: 853_535.43,
+                z: 615.26,
+            },
+        };
+        let transforms = Transform::best_for_bounds(bounds, 0.001).unwrap();
+        for &(transform, min, max) in &[
+            (transforms.x, bounds.min.x, bounds.max.x),
+            (transforms.y, bounds.min.y, bounds.max.y),
+            (transforms.z, bounds.min.z, bounds.max.z),
+        ]
+        {
+            for &n in &[min, (min + max) / 2., max] {
+                let roundtrip = transform.direct(transform.inverse(n).unwrap());
+                assert!((n - roundtrip).abs() <= 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn best_for_bounds_too_large() {
+        let mut bounds = Bounds::default();
+        bounds.min.x = 0.;
+        bounds.max.x = 1e7;
+        let transforms = Transform::best_for_bounds(bounds, 0.001).unwrap();
+        assert_eq!(0.01, transforms.x.scale);
+        assert!(transforms.x.inverse(1e7).is_ok());
+        assert_eq!(Transform::default(), transforms.y);
+    }
+
+    #[test]
+    fn best_for_bounds_invalid_precision() {
+        use std::f64;
+
+        let bounds = Bounds::default();
+        for &precision in &[0., -0.01, f64::NAN, f64::INFINITY] {
+            match Transform::best_for_bounds(bounds, precision) {
+                Err(::Error::InvalidPrecision(_)) => {}
+                result => panic!("unexpected result for {}: {:?}", precision, result),
+            }
+        }
+    }
+
+    #[test]
+    fn direct_inverse_near_identity() {
+        let transform = Transform {
+            scale: 0.01,
+            offset: 500_000.,
+        };
+        for &n in &[500_000., 500_000.004, 499_123.456, 512_345.678_9, 487_654.321] {
+            let roundtrip = transform.direct(transform.inverse(n).unwrap());
+            assert!((n - roundtrip).abs() <= transform.scale / 2. + 1e-9);
+        }
+    }
+
+    #[test]
+    fn inverse_direct_identity() {
+        let transform = Transform {
+            scale: 0.001,
+            offset: -42.,
+        };
+        for &n in &[0, 1, -1, 123_456, -987_654, i32::MAX, i32::MIN] {
+            assert_eq!(n, transform.inverse(transform.direct(n)).unwrap());
+        }
+    }
 }
@@ -0,0 +1,213 @@
+//! Read, modify, and write las data in one pass.
+
+use {Point, Reader, Result, Writer};
+use std::fmt;
+use std::io::{Read, Seek, Write};
+use writer::Options;
+
+/// Reads every point from a reader, applies a function, and writes the result.
+///
+/// This is the usual read-modify-write workflow. The new las data use a clone of the reader's
+/// header, so everything in it is carried over: the vlrs and evlrs (including the coordinate
+/// reference system and extra bytes descriptors, and any records that this crate doesn't
+/// understand), the header, vlr, and point padding, and each point's extra bytes. Only the point
+/// counts and bounds are recomputed from the written points.
+///
+/// The function must return points that match the reader's point format, or `run` will error.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::{Reader, Transformer};
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let writer = Transformer::new(reader, Cursor::new(Vec::new()))
+///     .map(|mut point| {
+///         point.z += 10.;
+///         point
+///     })
+///     .run()
+///     .unwrap();
+/// assert_eq!(106, writer.header().number_of_points());
+/// ```
+pub struct Transformer<R: Read + Seek, W: Write + Seek, F: FnMut(Point) -> Point> {
+    function: F,
+    options: Options,
+    reader: Reader<R>,
+    write: W,
+}
+
+impl<R: Read + Seek, W: Write + Seek> Transformer<R, W, fn(Point) -> Point> {
+    /// Creates a new transformer that copies the points unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Transformer};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let transformer = Transformer::new(reader, Cursor::new(Vec::new()));
+    /// ```
+    pub fn new(reader: Reader<R>, write: W) -> Transformer<R, W, fn(Point) -> Point> {
+        fn identity(point: Point) -> Point {
+            point
+        }
+        Transformer {
+            function: identity,
+            options: Options::default(),
+            reader: reader,
+            write: write,
+        }
+    }
+}
+
+impl<R: Read + Seek, W: Write + Seek, F: FnMut(Point) -> Point> Transformer<R, W, F> {
+    /// Sets the function that is applied to each point.
+    ///
+    /// This replaces any previous function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Transformer};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let transformer = Transformer::new(reader, Cursor::new(Vec::new())).map(|mut point| {
+    ///     point.intensity /= 2;
+    ///     point
+    /// });
+    /// ```
+    pub fn map<G: FnMut(Point) -> Point>(self, function: G) -> Transformer<R, W, G> {
+        Transformer {
+            function: function,
+            options: self.options,
+            reader: self.reader,
+            write: self.write,
+        }
+    }
+
+    /// Sets the options for the writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Transformer};
+    /// use las::writer::Options;
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = Options { reject_duplicate_vlrs: true, ..Default::default() };
+    /// let transformer = Transformer::new(reader, Cursor::new(Vec::new())).options(options);
+    /// ```
+    pub fn options(mut self, options: Options) -> Transformer<R, W, F> {
+        self.options = options;
+        self
+    }
+
+    /// Reads, transforms, and writes all of the points, then closes and returns the writer.
+    ///
+    /// The reader is rewound first, so points that were already read are included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Transformer};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let writer = Transformer::new(reader, Cursor::new(Vec::new())).run().unwrap();
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(106, reader.header().number_of_points());
+    /// ```
+    pub fn run(mut self) -> Result<Writer<W>> {
+        self.reader.seek(0)?;
+        let header = self.reader.header().clone();
+        let mut writer = Writer::with_options(self.write, header, self.options)?;
+        for point in self.reader.points() {
+            writer.write((self.function)(point?))?;
+        }
+        writer.close()?;
+        Ok(writer)
+    }
+}
+
+impl<R: Read + Seek, W: Write + Seek, F: FnMut(Point) -> Point> fmt::Debug for Transformer<R, W, F>
+where
+    R: fmt::Debug,
+    W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transformer")
+            .field("options", &self.options)
+            .field("reader", &self.reader)
+            .field("write", &self.write)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Builder, Vlr};
+    use std::io::Cursor;
+
+    #[test]
+    fn preserves_unknown_data() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 2;
+        builder.padding = vec![1, 2];
+        builder.vlr_padding = vec![3];
+        builder.point_padding = vec![4, 5];
+        builder.vlrs.push(Vlr {
+            user_id: "unknown".to_string(),
+            record_id: 1,
+            data: vec![6, 7, 8],
+            ..Default::default()
+        });
+        builder.evlrs.push(Vlr {
+            user_id: "unknown".to_string(),
+            record_id: 2,
+            data: vec![9],
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer
+            .write(Point {
+                z: 1.,
+                extra_bytes: vec![10, 11],
+                ..Default::default()
+            })
+            .unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let original = reader.header().clone();
+
+        let writer = Transformer::new(reader, Cursor::new(Vec::new()))
+            .map(|mut point| {
+                point.z += 1.;
+                point
+            })
+            .run()
+            .unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(original.vlrs(), header.vlrs());
+        assert_eq!(original.evlrs(), header.evlrs());
+        assert_eq!(original.padding(), header.padding());
+        assert_eq!(original.vlr_padding(), header.vlr_padding());
+        assert_eq!(original.point_padding(), header.point_padding());
+        assert_eq!(original.point_format(), header.point_format());
+        let point = reader.read().unwrap().unwrap();
+        assert_eq!(2., point.z);
+        assert_eq!(vec![10, 11], point.extra_bytes);
+        assert_eq!(2., header.bounds().max.z);
+    }
+
+    #[test]
+    fn rewinds_reader() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        reader.read().unwrap().unwrap();
+        let vlrs = reader.header().vlrs().clone();
+        let writer = Transformer::new(reader, Cursor::new(Vec::new())).run().unwrap();
+        assert_eq!(106, writer.header().number_of_points());
+        assert_eq!(&vlrs, writer.header().vlrs());
+    }
+}
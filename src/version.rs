@@ -2,6 +2,7 @@ use {Error, Result};
 use Feature;
 use point::Format;
 use std::fmt;
+use std::str::FromStr;
 
 /// LAS version.
 ///
@@ -142,6 +143,37 @@ impl fmt::Display for Version {
     }
 }
 
+impl FromStr for Version {
+    type Err = Error;
+
+    /// Parses a version from its major and minor versions separated by a dot, e.g. "1.4".
+    ///
+    /// Both versions must be only digits, so signs and whitespace are errors. As with
+    /// `Version::new`, this doesn't check that it's an actual las version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Version;
+    /// assert_eq!(Version::new(1, 4), "1.4".parse().unwrap());
+    /// assert!("1.4.0".parse::<Version>().is_err());
+    /// assert!("+1.4".parse::<Version>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Version> {
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        let mut iter = s.split('.');
+        match (iter.next(), iter.next(), iter.next()) {
+            (Some(major), Some(minor), None) if is_digits(major) && is_digits(minor) => {
+                match (major.parse(), minor.parse()) {
+                    (Ok(major), Ok(minor)) => Ok(Version::new(major, minor)),
+                    _ => Err(Error::InvalidVersion(s.to_string())),
+                }
+            }
+            _ => Err(Error::InvalidVersion(s.to_string())),
+        }
+    }
+}
+
 impl From<(u8, u8)> for Version {
     fn from((major, minor): (u8, u8)) -> Version {
         Version {
@@ -200,4 +232,37 @@ mod tests {
     version!(las_1_4, 1, 4, [true; 5], 10);
     version!(las_1_5, 1, 5, [false; 5], -1);
     version!(las_2_0, 2, 0, [false; 5], -1);
+
+    #[test]
+    fn from_str() {
+        for &(s, major, minor) in &[("1.0", 1, 0), ("1.2", 1, 2), ("1.4", 1, 4), ("2.10", 2, 10)] {
+            assert_eq!(Version::new(major, minor), s.parse().unwrap());
+        }
+        for s in &[
+            "",
+            "1",
+            "1.",
+            ".4",
+            "1.4.0",
+            "1,4",
+            "v1.4",
+            " 1.4",
+            "1.-4",
+            "1.256",
+            "+1.4",
+            "1.+4",
+        ] {
+            match s.parse::<Version>() {
+                Err(Error::InvalidVersion(ref bad)) => assert_eq!(s, bad),
+                result => panic!("unexpected result for {:?}: {:?}", s, result),
+            }
+        }
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let version = Version::new(1, 4);
+        assert_eq!("1.4", version.to_string());
+        assert_eq!(version, version.to_string().parse().unwrap());
+    }
 }
@@ -33,6 +33,7 @@ use {Result, raw};
 
 const REGULAR_HEADER_SIZE: usize = 54;
 const EXTENDED_HEADER_SIZE: usize = 60;
+const WAVEFORM_DATA_PACKETS_RECORD_ID: u16 = 65535;
 
 quick_error! {
     /// Vlr-specific errors.
@@ -164,6 +165,22 @@ impl Vlr {
         self.data.len() > u16::MAX as usize
     }
 
+    /// Returns true if this is the waveform data packet record, i.e. it holds internal waveforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let mut vlr = Vlr::default();
+    /// assert!(!vlr.is_waveform_data_packets());
+    /// vlr.user_id = "LASF_Spec".to_string();
+    /// vlr.record_id = 65535;
+    /// assert!(vlr.is_waveform_data_packets());
+    /// ```
+    pub fn is_waveform_data_packets(&self) -> bool {
+        self.user_id == "LASF_Spec" && self.record_id == WAVEFORM_DATA_PACKETS_RECORD_ID
+    }
+
     fn record_length_after_header(&self, is_extended: bool) -> Result<raw::vlr::RecordLength> {
         if is_extended {
             Ok(raw::vlr::RecordLength::Evlr(self.data.len() as u64))
@@ -0,0 +1,189 @@
+use {GpsTimeType, Version};
+use std::fmt;
+
+/// A problem with las data that isn't serious enough to be an error.
+///
+/// Warnings are collected by the `Reader` and the `Writer`, and can be retrieved with
+/// `Reader::warnings` and `Writer::warnings`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// More than one vlr has the same user id and record id.
+    DuplicateVlr {
+        /// The shared user id.
+        user_id: String,
+        /// The shared record id.
+        record_id: u16,
+    },
+
+    /// The extra bytes vlr describes a different number of bytes than the point records have.
+    ///
+    /// The point data record length is authoritative, so each point is read with `actual` extra
+    /// bytes, and descriptors past the end of the extra bytes can't be decoded.
+    ExtraBytesLengthMismatch {
+        /// The number of bytes described by the extra bytes vlr.
+        described: u16,
+        /// The number of extra bytes in each point record.
+        actual: u16,
+    },
+
+    /// A point's gps time is outside of the plausible range for the header's gps time type.
+    ///
+    /// This usually means that the global encoding's gps time type bit is wrong.
+    GpsTimeOutOfRange {
+        /// The index of the point in the las data, zero-indexed.
+        index: u64,
+        /// The point's gps time.
+        gps_time: f64,
+        /// The header's gps time type.
+        gps_time_type: GpsTimeType,
+    },
+
+    /// The las data don't conform to a version of the las spec.
+    ///
+    /// Returned by `Header::validate_against`, and recorded by the `Reader` if the extra bytes vlr
+    /// can't be read.
+    NonConforming {
+        /// The version that was checked against.
+        version: Version,
+        /// What doesn't conform.
+        reason: String,
+    },
+
+    /// A string in the header is not valid text, and was read lossily.
+    NonUtf8String {
+        /// The name of the header field.
+        field: &'static str,
+        /// The raw bytes of the field.
+        bytes: Vec<u8>,
+    },
+
+    /// A point is outside of the bounds declared in the header.
+    PointOutOfBounds {
+        /// The index of the point in the las data, zero-indexed.
+        index: u64,
+        /// The point's x coordinate.
+        x: f64,
+        /// The point's y coordinate.
+        y: f64,
+        /// The point's z coordinate.
+        z: f64,
+    },
+
+    /// The point records have extra bytes that aren't described by an extra bytes vlr.
+    ///
+    /// The bytes are still read into `Point::extra_bytes`, and are written back out unchanged.
+    UndescribedExtraBytes {
+        /// The number of undescribed extra bytes in each point record.
+        count: u16,
+    },
+
+    /// A point value was out of the range of its field, and was clamped when written.
+    ValueClamped {
+        /// The index of the point in the las data, zero-indexed.
+        index: u64,
+        /// The name of the point field.
+        field: &'static str,
+        /// The original value.
+        value: f64,
+        /// The value that was written.
+        clamped: f64,
+    },
+
+    /// A return count was too large for the legacy header fields, and was clamped to `u32::MAX`.
+    ReturnCountSaturated {
+        /// The return number, one-indexed.
+        return_number: u8,
+        /// The actual number of points with that return number.
+        count: u64,
+    },
+
+    /// The header's number of vlrs doesn't match the number of vlrs before the point data.
+    VlrCountMismatch {
+        /// The number of vlrs in the header.
+        declared: u32,
+        /// The number of vlrs that were read.
+        actual: u32,
+    },
+
+    /// The header's bounds are all zero, but there are points.
+    ///
+    /// This usually means that the writer never updated the bounds.
+    ZeroBoundsWithPoints {
+        /// The header's number of points.
+        number_of_points: u64,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::DuplicateVlr {
+                ref user_id,
+                record_id,
+            } => write!(f, "duplicate vlr: user id {}, record id {}", user_id, record_id),
+            Warning::ExtraBytesLengthMismatch { described, actual } => write!(
+                f,
+                "the extra bytes vlr describes {} bytes, but points have {} extra bytes",
+                described,
+                actual
+            ),
+            Warning::GpsTimeOutOfRange {
+                index,
+                gps_time,
+                gps_time_type,
+            } => write!(
+                f,
+                "point {} gps time {} is out of range for {:?} time",
+                index,
+                gps_time,
+                gps_time_type
+            ),
+            Warning::NonConforming {
+                version,
+                ref reason,
+            } => write!(f, "not conforming to las {}: {}", version, reason),
+            Warning::NonUtf8String { field, ref bytes } => {
+                write!(f, "{} is not valid text: {:?}", field, bytes)
+            }
+            Warning::PointOutOfBounds { index, x, y, z } => {
+                write!(f, "point {} is out of bounds: ({}, {}, {})", index, x, y, z)
+            }
+            Warning::UndescribedExtraBytes { count } => {
+                write!(f, "{} extra bytes per point are not described by a vlr", count)
+            }
+            Warning::ValueClamped {
+                index,
+                field,
+                value,
+                clamped,
+            } => write!(
+                f,
+                "point {} {} was clamped from {} to {}",
+                index,
+                field,
+                value,
+                clamped
+            ),
+            Warning::ReturnCountSaturated {
+                return_number,
+                count,
+            } => write!(
+                f,
+                "return count for return number {} saturated: {} points",
+                return_number,
+                count
+            ),
+            Warning::VlrCountMismatch { declared, actual } => write!(
+                f,
+                "the header declares {} vlrs, but there are {}",
+                declared,
+                actual
+            ),
+            Warning::ZeroBoundsWithPoints { number_of_points } => write!(
+                f,
+                "the header bounds are all zero, but there are {} points",
+                number_of_points
+            ),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Read waveform data packets.
+//!
+//! Points in formats that have waveforms (4, 5, 9, and 10) store information about their waveform
+//! packet, but not the waveform samples themselves. Those live in a waveform data packet record,
+//! which can be either:
+//!
+//! - **internal**, stored inside of the las file, with the point's byte offset relative to the
+//!   start of the waveform data packet record, or
+//! - **external**, stored in an auxiliary file with the same base name as the las file but with
+//!   the extension `.wdp`, with the point's byte offset relative to the beginning of that file.
+//!
+//! Which one a file uses is recorded in the header's global encoding, and exposed as
+//! `Header::waveform_location`. A `WaveformReader` reads packets from either. Use
+//! `Reader::open_waveforms` for an external `.wdp` file and `Reader::internal_waveforms` for the
+//! internal record, or create one yourself:
+//!
+//! ```
+//! use std::io::Cursor;
+//! use las::Point;
+//! use las::raw::point::Waveform;
+//! use las::waveform::WaveformReader;
+//!
+//! let mut wdp = WaveformReader::new(Cursor::new(vec![0, 0, 1, 2, 3]));
+//! let point = Point {
+//!     waveform: Some(Waveform {
+//!         byte_offset_to_waveform_data: 2,
+//!         waveform_packet_size_in_bytes: 3,
+//!         ..Default::default()
+//!     }),
+//!     ..Default::default()
+//! };
+//! assert_eq!(vec![1, 2, 3], point.read_waveform(&mut wdp).unwrap());
+//! ```
+
+use Result;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Where a file's waveform data packets are stored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WaveformLocation {
+    /// The packets are in the waveform data packet record, an evlr inside of the las file.
+    Internal,
+    /// The packets are in an external waveform data packet (`.wdp`) file.
+    External,
+}
+
+impl WaveformLocation {
+    /// Returns the location encoded in a global encoding, or `None` if neither bit is set.
+    ///
+    /// Returns an error if both the internal and external bits are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformLocation;
+    /// assert_eq!(None, WaveformLocation::from_global_encoding(0).unwrap());
+    /// assert_eq!(Some(WaveformLocation::Internal), WaveformLocation::from_global_encoding(2).unwrap());
+    /// assert_eq!(Some(WaveformLocation::External), WaveformLocation::from_global_encoding(4).unwrap());
+    /// assert!(WaveformLocation::from_global_encoding(6).is_err());
+    /// ```
+    pub fn from_global_encoding(global_encoding: u16) -> Result<Option<WaveformLocation>> {
+        use header::Error;
+        match (global_encoding & 2 == 2, global_encoding & 4 == 4) {
+            (true, true) => Err(Error::WaveformLocationBits(global_encoding).into()),
+            (true, false) => Ok(Some(WaveformLocation::Internal)),
+            (false, true) => Ok(Some(WaveformLocation::External)),
+            (false, false) => Ok(None),
+        }
+    }
+
+    /// Returns this location's global encoding bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformLocation;
+    /// assert_eq!(2, WaveformLocation::Internal.global_encoding_bit());
+    /// assert_eq!(4, WaveformLocation::External.global_encoding_bit());
+    /// ```
+    pub fn global_encoding_bit(&self) -> u16 {
+        match *self {
+            WaveformLocation::Internal => 2,
+            WaveformLocation::External => 4,
+        }
+    }
+}
+
+/// Reads waveform packets from a waveform data packet record or an external `.wdp` file.
+#[derive(Debug)]
+pub struct WaveformReader<R: Read + Seek> {
+    read: R,
+}
+
+impl<R: Read + Seek> WaveformReader<R> {
+    /// Creates a new waveform reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::waveform::WaveformReader;
+    /// let wdp = WaveformReader::new(Cursor::new(Vec::new()));
+    /// ```
+    pub fn new(read: R) -> WaveformReader<R> {
+        WaveformReader { read: read }
+    }
+
+    /// Reads the raw samples of one waveform packet.
+    ///
+    /// `byte_offset` is relative to the beginning of the `.wdp` file, or the beginning of the
+    /// waveform data packet record's header for internal waveforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::waveform::WaveformReader;
+    /// let mut wdp = WaveformReader::new(Cursor::new(vec![0, 1, 2]));
+    /// assert_eq!(vec![1, 2], wdp.read_packet(1, 2).unwrap());
+    /// ```
+    pub fn read_packet(&mut self, byte_offset: u64, size: u32) -> Result<Vec<u8>> {
+        self.read.seek(SeekFrom::Start(byte_offset))?;
+        let mut packet = vec![0; size as usize];
+        self.read.read_exact(&mut packet)?;
+        Ok(packet)
+    }
+}
+
+impl WaveformReader<BufReader<File>> {
+    /// Creates a new waveform reader from a path.
+    ///
+    /// The underlying `File` is wrapped in a `BufReader` for performance reasons.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformReader;
+    /// let wdp = WaveformReader::from_path("tests/data/autzen.las").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<WaveformReader<BufReader<File>>> {
+        File::open(path).map_err(::Error::from).map(|file| {
+            WaveformReader::new(BufReader::new(file))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn packet_past_end() {
+        let mut wdp = WaveformReader::new(Cursor::new(vec![0, 1, 2]));
+        assert!(wdp.read_packet(2, 2).is_err());
+    }
+
+    #[test]
+    fn global_encoding_roundtrip() {
+        for &location in &[WaveformLocation::Internal, WaveformLocation::External] {
+            assert_eq!(
+                Some(location),
+                WaveformLocation::from_global_encoding(location.global_encoding_bit() | 1)
+                    .unwrap()
+            );
+        }
+    }
+}
@@ -30,12 +30,32 @@
 //! point.color = Some(Color::new(1, 2, 3));
 //! assert!(writer.write(point).is_err()); // the point's color would be lost
 //! ```
+//!
+//! Use `Options` for stricter validation:
+//!
+//! ```
+//! use std::io::Cursor;
+//! use las::{Builder, Vlr, Writer};
+//! use las::writer::Options;
+//!
+//! let mut builder = Builder::default();
+//! builder.vlrs.push(Vlr::default());
+//! builder.vlrs.push(Vlr::default());
+//! let header = builder.into_header().unwrap();
+//! let options = Options { reject_duplicate_vlrs: true, ..Default::default() };
+//! assert!(Writer::with_options(Cursor::new(Vec::new()), header, options).is_err());
+//! ```
 
-use {Header, Point, Result};
+use {Bounds, Builder, Header, Phase, Point, Reader, Result, Transform, Version, Warning};
+use error::WithPhase;
+use extra_bytes::{DataType, Descriptor};
 use point::Format;
+use raw;
 use std::fs::File;
-use std::io::{BufWriter, Cursor, Seek, SeekFrom, Write};
+use byteorder::{ByteOrder, LittleEndian};
+use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use waveform::WaveformLocation;
 
 quick_error! {
     /// Writer errors.
@@ -45,14 +65,93 @@ quick_error! {
         Closed {
             description("the writer is closed")
         }
+        /// The reader's point format or transforms don't match the writer's, so its point
+        /// records can't be copied.
+        CopyRawMismatch {
+            description("the reader's point format or transforms don't match the writer's")
+        }
+        /// More than one vlr has the same user id and record id.
+        DuplicateVlr(user_id: String, record_id: u16) {
+            description("more than one vlr has the same user id and record id")
+            display("duplicate vlr: user id {}, record id {}", user_id, record_id)
+        }
+        /// Points can't be written after an evlr has been started.
+        EvlrsStarted {
+            description("points can't be written after an evlr has been started")
+        }
         /// The attributes of the point format and point do not match.
         PointAttributes(format: Format, point: Point) {
             description("the attributes of the point format and point do not match")
             display("the attributes of point format {:?} does not match point {:?}", format, point)
         }
+        /// A presized writer wrote a different number of points than it declared.
+        PresizedCount(declared: u64, actual: u64) {
+            description("a presized writer wrote a different number of points than it declared")
+            display("the writer declared {} points, but {} were written", declared, actual)
+        }
+        /// A presized writer never seeks, so it can't stream evlrs.
+        PresizedEvlr {
+            description("a presized writer can't stream evlrs")
+        }
+        /// More points were written than were reserved.
+        ReservedPoints(reserved: u64) {
+            description("more points were written than were reserved")
+            display("only {} points were reserved", reserved)
+        }
+        /// The length of the verbatim vlr region does not match the header.
+        VlrRegionLength(expected: usize, actual: usize) {
+            description("the length of the vlr region does not match the header")
+            display("the vlr region should be {} bytes, but is {} bytes", expected, actual)
+        }
     }
 }
 
+/// Options that control how a `Writer` validates its data.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// Should a header with more than one vlr with the same user id and record id be rejected?
+    pub reject_duplicate_vlrs: bool,
+
+    /// Should return counts that overflow the legacy header fields be clamped to `u32::MAX`?
+    ///
+    /// Before las 1.4, at most `u32::MAX` points can share a return number. By default, closing a
+    /// writer with more points than that is an error, and you should upgrade to las 1.4. If this
+    /// is true, the counts are saturated instead and a `Warning::ReturnCountSaturated` is
+    /// recorded.
+    pub saturate_return_counts: bool,
+
+    /// Should point values that are out of range for their field be an error?
+    ///
+    /// The scan angle is stored as a rank from -90 to 90 degrees in legacy point formats, and as
+    /// a scaled value from -180 to 180 degrees in extended formats. By default, a scan angle
+    /// outside of that range is clamped and a `Warning::ValueClamped` is recorded. If this is
+    /// true, writing the point is an error instead. Intensity and point source id are `u16`s on
+    /// `Point`, so they always fit.
+    pub strict_value_ranges: bool,
+
+    /// Reserve space for this many points, so the las data are always the same size.
+    ///
+    /// When the writer is closed, every slot that wasn't written is filled with a zeroed point
+    /// record that is marked as withheld. The header still holds the actual number of points, so
+    /// readers ignore the unused slots, and the data can be appended to in place later. The
+    /// tradeoff is space: the unused slots take up as much room as real points. Writing more
+    /// points than were reserved is an error.
+    ///
+    /// If there are evlrs, they are written after all of the slots, and the unused slots are
+    /// read back as point padding.
+    pub reserve_points: Option<u64>,
+
+    /// Should the min and max of each extra bytes descriptor be computed from the points?
+    ///
+    /// Like the bounds, the min and max of every descriptor in the header's extra bytes vlr are
+    /// accumulated as points are written, and the vlr is re-written with them when the writer is
+    /// closed. Like the no data value, they are the stored values, before any scale and offset,
+    /// each dimension separately, and values that equal the descriptor's no data value are
+    /// skipped. Undocumented extra bytes don't have a min or max. If no point had a value, the min
+    /// and max are cleared.
+    pub compute_extra_bytes_min_max: bool,
+}
+
 /// Writes LAS data.
 ///
 /// The LAS header needs to be re-written when the writer closes. For convenience, this is done via
@@ -71,8 +170,16 @@ quick_error! {
 #[derive(Debug)]
 pub struct Writer<W: Seek + Write> {
     closed: bool,
+    evlrs_started: Option<u64>,
+    extra_bytes_range: Option<ExtraBytesRange>,
     header: Header,
+    header_synced: bool,
+    kept_header: Option<Header>,
+    number_of_streamed_evlrs: u32,
+    options: Options,
+    presized: Option<u64>,
     start: u64,
+    warnings: Vec<Warning>,
     write: W,
 }
 
@@ -89,24 +196,191 @@ impl<W: Seek + Write> Writer<W> {
     /// use las::Writer;
     /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default());
     /// ```
-    pub fn new(mut write: W, mut header: Header) -> Result<Writer<W>> {
+    pub fn new(write: W, header: Header) -> Result<Writer<W>> {
+        Writer::with_options(write, header, Default::default())
+    }
+
+    /// Creates a new writer with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::Writer;
+    /// use las::writer::Options;
+    /// let options = Options { reject_duplicate_vlrs: true, ..Default::default() };
+    /// let writer = Writer::with_options(Cursor::new(Vec::new()), Default::default(), options);
+    /// ```
+    pub fn with_options(write: W, header: Header, options: Options) -> Result<Writer<W>> {
+        Writer::open(write, header, options, None, false)
+    }
+
+    /// Creates a new writer that writes the header as-is, without clearing its point counts and
+    /// bounds.
+    ///
+    /// The caller is responsible for the header: its number of points, number of points by
+    /// return, and bounds are written unchanged, both when the writer is created and when it is
+    /// checkpointed or closed, no matter which points are actually written. If they don't match
+    /// the points, the las data will be invalid. Use this when the final counts and bounds are
+    /// already known, or when the header was copied from data that is being extended.
+    ///
+    /// `Writer::header` still reflects the points that were actually written, so it can be used
+    /// to check the caller's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Writer};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header().clone();
+    /// let mut writer = Writer::new_keep_header(Cursor::new(Vec::new()), header.clone()).unwrap();
+    /// for point in reader.points() {
+    ///     writer.write(point.unwrap()).unwrap();
+    /// }
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(header.bounds(), reader.header().bounds());
+    /// ```
+    pub fn new_keep_header(write: W, header: Header) -> Result<Writer<W>> {
+        Writer::open(write, header, Default::default(), None, true)
+    }
+
+    /// Creates a new writer that writes the given bytes verbatim instead of serializing the vlrs.
+    ///
+    /// `vlr_region` is everything between the end of the header and the start of the point data,
+    /// usually from `Reader::vlr_region`. Its length must match the header's vlrs and vlr
+    /// padding. Use this for read-modify-write operations that only touch points, to guarantee
+    /// that the output vlrs are byte-for-byte identical to the input's.
+    ///
+    /// Re-serializing vlrs can change their bytes, e.g. the reserved field is always written as
+    /// zero and any bytes after the null terminator in the user id or description are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Writer};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let vlr_region = reader.vlr_region().unwrap();
+    /// let header = reader.header().clone();
+    /// let writer = Writer::with_vlr_region(Cursor::new(Vec::new()), header, vlr_region).unwrap();
+    /// ```
+    pub fn with_vlr_region(write: W, header: Header, vlr_region: Vec<u8>) -> Result<Writer<W>> {
+        Writer::open(write, header, Default::default(), Some(vlr_region), false)
+    }
+
+    fn open(
+        mut write: W,
+        mut header: Header,
+        options: Options,
+        vlr_region: Option<Vec<u8>>,
+        keep_header: bool,
+    ) -> Result<Writer<W>> {
+        if options.reject_duplicate_vlrs {
+            if let Some((user_id, record_id)) = header.duplicate_vlrs().into_iter().next() {
+                return Err(Error::DuplicateVlr(user_id, record_id).into());
+            }
+        }
         let start = write.seek(SeekFrom::Current(0))?;
+        let kept_header = if keep_header {
+            Some(header.clone())
+        } else {
+            None
+        };
         header.clear();
-        header.clone().into_raw().and_then(|raw_header| {
-            raw_header.write_to(&mut write)
-        })?;
-        for vlr in header.vlrs() {
-            (*vlr).clone().into_raw(false).and_then(|raw_vlr| {
-                raw_vlr.write_to(&mut write)
-            })?;
+        let raw_header = kept_header.clone().unwrap_or_else(|| header.clone()).into_raw()?;
+        let extra_bytes_range = if options.compute_extra_bytes_min_max && vlr_region.is_none() {
+            ExtraBytesRange::new(&header, start + u64::from(raw_header.header_size))
+        } else {
+            None
+        };
+        if let Some(ref vlr_region) = vlr_region {
+            let len = (raw_header.offset_to_point_data - u32::from(raw_header.header_size)) as usize;
+            if vlr_region.len() != len {
+                return Err(Error::VlrRegionLength(len, vlr_region.len()).into());
+            }
         }
-        if !header.vlr_padding().is_empty() {
-            write.write_all(&header.vlr_padding())?;
+        raw_header.write_to(&mut write).with_phase(
+            Phase::WritingHeader,
+        )?;
+        if let Some(vlr_region) = vlr_region {
+            write
+                .write_all(&vlr_region)
+                .map_err(::Error::from)
+                .with_phase(Phase::WritingVlr)?;
+        } else {
+            write_vlrs(&mut write, &header)?;
         }
         Ok(Writer {
             closed: false,
+            evlrs_started: None,
+            extra_bytes_range: extra_bytes_range,
             header: header,
+            header_synced: true,
+            kept_header: kept_header,
+            number_of_streamed_evlrs: 0,
+            options: options,
+            presized: None,
             start: start,
+            warnings: Vec::new(),
+            write: write,
+        })
+    }
+
+    /// Creates a new writer that writes the final header up front, for when the point count and
+    /// bounds are already known, e.g. from a prior pass.
+    ///
+    /// A presized writer never seeks: the header is written once, and closing the writer only
+    /// writes the point padding and evlrs. This enables writing to append-only sinks, whose `Seek`
+    /// implementation can simply return an error. The header's number of points by return are
+    /// written as-is. `checkpoint` only flushes, and `into_inner` does not seek back to the start
+    /// of the las data.
+    ///
+    /// Writing more points than `number_of_points`, or closing the writer with fewer, is an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Point, Reader, Writer};
+    ///
+    /// let point = Point { x: 1., y: 2., z: 3., ..Default::default() };
+    /// let mut bounds = las::Bounds::default();
+    /// bounds.grow(&point);
+    /// let mut writer = Writer::new_presized(Cursor::new(Vec::new()), Default::default(), bounds, 1)
+    ///     .unwrap();
+    /// writer.write(point).unwrap();
+    /// let mut cursor = writer.into_inner().unwrap();
+    /// cursor.set_position(0);
+    /// assert_eq!(1, Reader::new(cursor).unwrap().header().number_of_points());
+    /// ```
+    pub fn new_presized(
+        mut write: W,
+        mut header: Header,
+        bounds: Bounds,
+        number_of_points: u64,
+    ) -> Result<Writer<W>> {
+        let mut declared = header.clone();
+        declared.set_number_of_points_and_bounds(number_of_points, bounds);
+        declared.into_raw().and_then(|raw_header| {
+            raw_header.write_to(&mut write).with_phase(
+                Phase::WritingHeader,
+            )
+        })?;
+        write_vlrs(&mut write, &header)?;
+        header.clear();
+        Ok(Writer {
+            closed: false,
+            evlrs_started: None,
+            extra_bytes_range: None,
+            header: header,
+            header_synced: false,
+            kept_header: None,
+            number_of_streamed_evlrs: 0,
+            options: Default::default(),
+            presized: Some(number_of_points),
+            start: 0,
+            warnings: Vec::new(),
             write: write,
         })
     }
@@ -124,8 +398,49 @@ impl<W: Seek + Write> Writer<W> {
         &self.header
     }
 
+    /// Returns the warnings that this writer has recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// let mut writer = Writer::default();
+    /// writer.close().unwrap();
+    /// assert!(writer.warnings().is_empty());
+    /// ```
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the number of points written so far for each return number.
+    ///
+    /// The first element is the count for return number one, and so on through return number
+    /// fifteen. Points with a return number of zero aren't counted. These are the header's counts,
+    /// which are written to the las data when the header is synced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Writer};
+    /// let mut writer = Writer::default();
+    /// writer.write(Point { return_number: 2, ..Default::default() }).unwrap();
+    /// assert_eq!(1, writer.points_by_return()[1]);
+    /// ```
+    pub fn points_by_return(&self) -> [u64; 15] {
+        let mut points_by_return = [0; 15];
+        for (i, count) in points_by_return.iter_mut().enumerate() {
+            *count = self.header.number_of_points_by_return(i as u8 + 1).unwrap_or(0);
+        }
+        points_by_return
+    }
+
     /// Writes a point.
     ///
+    /// Legacy point formats (zero through five) share one byte between the classification and
+    /// the synthetic, key point, and withheld flags, so classifications above 31 are rejected for
+    /// those formats. Scan angles that are out of range for the point format are clamped, or are
+    /// an error if `Options::strict_value_ranges` is set.
+    ///
     /// # Examples
     ///
     /// ```
@@ -135,26 +450,411 @@ impl<W: Seek + Write> Writer<W> {
     /// let mut writer = Writer::default();
     /// writer.write(Default::default()).unwrap();
     /// ```
-    pub fn write(&mut self, point: Point) -> Result<()> {
+    pub fn write(&mut self, mut point: Point) -> Result<()> {
+        point.scan_angle = self.prepare(&point)?;
+        let raw_point = point.into_raw(self.header.transforms())?;
+        self.write_raw_point(raw_point)
+    }
+
+    /// Writes a borrowed point.
+    ///
+    /// This behaves exactly like `write`, but it doesn't take ownership of the point, so callers
+    /// that keep their points don't have to clone them first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Writer};
+    /// let mut writer = Writer::default();
+    /// let point = Point { x: 1., ..Default::default() };
+    /// writer.write_ref(&point).unwrap();
+    /// writer.write_ref(&point).unwrap();
+    /// assert_eq!(2, writer.header().number_of_points());
+    /// ```
+    pub fn write_ref(&mut self, point: &Point) -> Result<()> {
+        let scan_angle = self.prepare(point)?;
+        let mut raw_point = point.to_raw(self.header.transforms())?;
+        raw_point.scan_angle = scan_angle.into();
+        self.write_raw_point(raw_point)
+    }
+
+    /// Writes a batch of points.
+    ///
+    /// Each point is checked and written as with `write`. Writing a long stream of points in
+    /// batches lets the caller report progress or check for cancellation between calls, and a
+    /// cancelled writer can still be closed to finish valid las data with the points written so
+    /// far. If a point can't be written, the points before it in the batch have already been
+    /// written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, Writer};
+    /// let points = vec![Point::default(); 10];
+    /// let mut writer = Writer::default();
+    /// for chunk in points.chunks(4) {
+    ///     writer.write_chunk(chunk).unwrap();
+    /// }
+    /// assert_eq!(10, writer.header().number_of_points());
+    /// ```
+    pub fn write_chunk(&mut self, points: &[Point]) -> Result<()> {
+        for point in points {
+            self.write_ref(point)?;
+        }
+        Ok(())
+    }
+
+    /// Copies all of a reader's point records into this writer without decoding them.
+    ///
+    /// When both have the same point format (including the extra bytes) and transforms, the point
+    /// records are byte-identical, so they are copied in bulk. This is much faster than reading
+    /// and writing each point, e.g. when concatenating files. The reader is rewound first. Only the
+    /// positions and return numbers are decoded, to update the point counts and bounds. Since the
+    /// points aren't fully decoded, the reader's validation and reprojection don't apply. Returns
+    /// the number of points copied.
+    ///
+    /// Returns an error if the point formats or transforms don't match. If the copy fails part of
+    /// the way through, e.g. because the reader has fewer points than its header says, the points
+    /// that were already copied are counted and the writer can still be closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Reader, Writer};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header().clone();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// assert_eq!(106, writer.copy_raw_from(&mut reader).unwrap());
+    /// assert_eq!(106, writer.header().number_of_points());
+    /// ```
+    pub fn copy_raw_from<R: Read + Seek>(&mut self, reader: &mut Reader<R>) -> Result<u64> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        if self.evlrs_started.is_some() {
+            return Err(Error::EvlrsStarted.into());
+        }
+        if reader.header().point_format() != self.header.point_format() ||
+            reader.header().transforms() != self.header.transforms()
+        {
+            return Err(Error::CopyRawMismatch.into());
+        }
+        let n = reader.header().number_of_points();
+        if let Some(number_of_points) = self.presized {
+            if self.header.number_of_points() + n > number_of_points {
+                return Err(
+                    Error::PresizedCount(number_of_points, self.header.number_of_points() + n)
+                        .into(),
+                );
+            }
+        }
+        if let Some(reserved) = self.options.reserve_points {
+            if self.header.number_of_points() + n > reserved {
+                return Err(Error::ReservedPoints(reserved).into());
+            }
+        }
+        reader.seek(0)?;
+        let format = self.header.point_format();
+        let transforms = self.header.transforms();
+        let start_of_extra_bytes = usize::from(format.len() - format.extra_bytes);
+        let return_number_mask = if format.is_extended { 0b1111 } else { 0b111 };
+        let header = &mut self.header;
+        let extra_bytes_range = &mut self.extra_bytes_range;
+        let header_synced = &mut self.header_synced;
+        reader.copy_raw_records(&mut self.write, |record| {
+            let point = Point {
+                x: transforms.x.direct(LittleEndian::read_i32(&record[0..4])),
+                y: transforms.y.direct(LittleEndian::read_i32(&record[4..8])),
+                z: transforms.z.direct(LittleEndian::read_i32(&record[8..12])),
+                return_number: record[14] & return_number_mask,
+                ..Default::default()
+            };
+            header.add_point(&point);
+            if let Some(ref mut extra_bytes_range) = *extra_bytes_range {
+                extra_bytes_range.add(&record[start_of_extra_bytes..]);
+            }
+            *header_synced = false;
+        })
+    }
+
+    /// Starts an evlr whose data are streamed into the returned `EvlrWriter`, instead of being
+    /// held in memory in a `Vlr`.
+    ///
+    /// This is for very large evlrs, e.g. a spatial index. Evlrs come after the points, so this
+    /// must happen after all of the points have been written, and before the writer is closed:
+    /// writing a point after starting an evlr is an error. The point padding and the header's
+    /// evlrs are written when the first evlr is started, and the streamed evlrs follow them in
+    /// the order they were started. The evlr's length is back-patched when the `EvlrWriter` is
+    /// finished or dropped, and the header's number of evlrs is updated when this writer is
+    /// closed.
+    ///
+    /// Returns an error if the las version doesn't support evlrs, or if this writer is presized,
+    /// since back-patching the length needs to seek.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use las::{Builder, Reader, Writer};
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+    /// writer.write(Default::default()).unwrap();
+    /// {
+    ///     let mut evlr_writer = writer.begin_evlr("index", 1, "a spatial index").unwrap();
+    ///     evlr_writer.write_all(&[1, 2, 3]).unwrap();
+    ///     evlr_writer.finish().unwrap();
+    /// }
+    /// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    /// assert_eq!(vec![1, 2, 3], reader.header().evlrs()[0].data);
+    /// ```
+    pub fn begin_evlr(
+        &mut self,
+        user_id: &str,
+        record_id: u16,
+        description: &str,
+    ) -> Result<EvlrWriter<'_, W>> {
+        use feature::Evlrs;
+        use Vlr;
+
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        if self.presized.is_some() {
+            return Err(Error::PresizedEvlr.into());
+        }
+        self.header.version().verify_support_for::<Evlrs>()?;
+        let raw_evlr = Vlr {
+            user_id: user_id.to_string(),
+            record_id: record_id,
+            description: description.to_string(),
+            data: Vec::new(),
+        }.into_raw(true)?;
+        if self.evlrs_started.is_none() {
+            self.write_point_padding_and_evlrs()?;
+            let position = self.write.stream_position()?;
+            let evlrs_len = self.header.evlrs().iter().fold(0, |len, evlr| {
+                len + evlr.len(true) as u64
+            });
+            self.evlrs_started = Some(position - evlrs_len - self.start);
+        }
+        let start = self.write.stream_position()?;
+        raw_evlr.write_to(&mut self.write).with_phase(
+            Phase::WritingEvlr,
+        )?;
+        self.number_of_streamed_evlrs += 1;
+        self.header_synced = false;
+        Ok(EvlrWriter {
+            finished: false,
+            len: 0,
+            start: start,
+            writer: self,
+        })
+    }
+
+    /// Checks a point and adds it to the header, returning the scan angle to write.
+    fn prepare(&mut self, point: &Point) -> Result<f32> {
         if self.closed {
             return Err(Error::Closed.into());
         }
+        if self.evlrs_started.is_some() {
+            return Err(Error::EvlrsStarted.into());
+        }
         if !point.matches(self.header.point_format()) {
             return Err(
-                Error::PointAttributes(self.header.point_format(), point).into(),
+                Error::PointAttributes(self.header.point_format(), point.clone()).into(),
             );
         }
-        self.header.add_point(&point);
-        point.into_raw(self.header.transforms()).and_then(
-            |raw_point| {
-                raw_point.write_to(&mut self.write, self.header.point_format())
-            },
-        )?;
+        if let Some(number_of_points) = self.presized {
+            if self.header.number_of_points() >= number_of_points {
+                return Err(
+                    Error::PresizedCount(number_of_points, number_of_points + 1).into(),
+                );
+            }
+        }
+        if let Some(reserved) = self.options.reserve_points {
+            if self.header.number_of_points() >= reserved {
+                return Err(Error::ReservedPoints(reserved).into());
+            }
+        }
+        let classification = u8::from(point.classification);
+        if !self.header.point_format().is_extended && classification > 31 {
+            return Err(::point::Error::Classification(classification).into());
+        }
+        point.verify_finite()?;
+        let scan_angle = self.clamp_scan_angle(point.scan_angle)?;
+        self.header.add_point(point);
+        if let Some(ref mut extra_bytes_range) = self.extra_bytes_range {
+            extra_bytes_range.add(&point.extra_bytes);
+        }
+        self.header_synced = false;
+        Ok(scan_angle)
+    }
+
+    fn write_raw_point(&mut self, raw_point: raw::Point) -> Result<()> {
+        let index = self.header.number_of_points() - 1;
+        raw_point
+            .write_to(&mut self.write, self.header.point_format())
+            .with_phase(Phase::WritingPoint { index: index })?;
+        Ok(())
+    }
+
+    fn stamp_extra_bytes_range(&mut self, extra_bytes_range: ExtraBytesRange) -> Result<()> {
+        let index = extra_bytes_range.index;
+        let position = extra_bytes_range.position;
+        let data = Descriptor::into_vlr(&extra_bytes_range.into_descriptors())?.data;
+        if data.len() != self.header.vlrs()[index].data.len() {
+            // The vlr had bytes that aren't descriptors, so it can't be re-written in place.
+            return Ok(());
+        }
+        if let Some(ref mut kept_header) = self.kept_header {
+            kept_header.set_vlr_data(index, data.clone());
+        }
+        self.header.set_vlr_data(index, data);
+        self.write.seek(SeekFrom::Start(position))?;
+        let vlr = self.header_to_write().vlrs()[index].clone();
+        vlr.into_raw(false).and_then(|raw_vlr| {
+            raw_vlr.write_to(&mut self.write).with_phase(Phase::WritingVlr)
+        })
+    }
+
+    fn write_point_padding_and_evlrs(&mut self) -> Result<()> {
+        if let Some(slot) = self.reserved_slot() {
+            for _ in 0..self.number_of_unused_slots() {
+                self.write.write_all(&slot)?;
+            }
+        }
+        self.write.write_all(self.header.point_padding())?;
+        for raw_evlr in self.header.evlrs().iter().map(|evlr| {
+            evlr.clone().into_raw(true)
+        })
+        {
+            raw_evlr?.write_to(&mut self.write).with_phase(
+                Phase::WritingEvlr,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn reserved_slot(&self) -> Option<Vec<u8>> {
+        self.options.reserve_points?;
+        let format = self.header.point_format();
+        let mut slot = vec![0; usize::from(format.len())];
+        // The withheld bit is in the classification byte of legacy formats, and in the
+        // classification flags of extended formats. Both are the sixteenth byte.
+        slot[15] = if format.is_extended { 0b100 } else { 0b1000_0000 };
+        Some(slot)
+    }
+
+    fn number_of_unused_slots(&self) -> u64 {
+        self.options.reserve_points.map_or(0, |reserved| {
+            reserved.saturating_sub(self.header.number_of_points())
+        })
+    }
+
+    fn clamp_scan_angle(&mut self, angle: f32) -> Result<f32> {
+        let format = self.header.point_format();
+        let max = if format.is_extended { 180. } else { 90. };
+        if -max <= angle && angle <= max {
+            return Ok(angle);
+        }
+        if self.options.strict_value_ranges {
+            return Err(::point::Error::ScanAngle(angle, format).into());
+        }
+        let clamped = if angle.is_nan() { 0. } else { angle.max(-max).min(max) };
+        self.warnings.push(Warning::ValueClamped {
+            index: self.header.number_of_points(),
+            field: "scan angle",
+            value: f64::from(angle),
+            clamped: f64::from(clamped),
+        });
+        Ok(clamped)
+    }
+
+    /// Re-writes the header in the underlying `Write` so it reflects the points written so far.
+    ///
+    /// The writer stays open and more points can be written afterwards. This is useful if someone
+    /// else wants to read the las data while it is still being written. Evlrs are not written
+    /// until the writer is closed, so until then the header says that there are no evlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// let mut writer = Writer::default();
+    /// writer.write(Default::default()).unwrap();
+    /// writer.checkpoint().unwrap();
+    /// writer.write(Default::default()).unwrap();
+    /// ```
+    pub fn checkpoint(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        if self.presized.is_some() {
+            self.write.flush()?;
+            return Ok(());
+        }
+        let position = self.write.stream_position()?;
+        self.write.seek(SeekFrom::Start(self.start))?;
+        self.raw_header_to_write().and_then(|mut raw_header| {
+            raw_header.evlr = None;
+            raw_header.write_to(&mut self.write).with_phase(
+                Phase::WritingHeader,
+            )
+        })?;
+        self.write.seek(SeekFrom::Start(position))?;
+        self.write.flush()?;
+        self.header_synced = true;
         Ok(())
     }
 
+    fn header_to_write(&self) -> Header {
+        self.kept_header.clone().unwrap_or_else(|| self.header.clone())
+    }
+
+    fn raw_header_to_write(&self) -> Result<raw::Header> {
+        let mut raw_header = self.header_to_write().into_raw()?;
+        if let Some(start_of_first_evlr) = self.evlrs_started {
+            if self.number_of_streamed_evlrs > 0 {
+                raw_header.evlr = Some(raw::header::Evlr {
+                    start_of_first_evlr: start_of_first_evlr,
+                    number_of_evlrs: self.header.evlrs().len() as u32 +
+                        self.number_of_streamed_evlrs,
+                });
+            }
+        } else if let Some(ref mut evlr) = raw_header.evlr {
+            // The unused slots are written between the points and the evlrs.
+            let record_length = u64::from(self.header.point_format().len());
+            evlr.start_of_first_evlr += self.number_of_unused_slots() * record_length;
+        }
+        Ok(raw_header)
+    }
+
+    /// Returns true if the header in the underlying `Write` reflects all points written so far.
+    ///
+    /// The header is synced after the writer is created, after `checkpoint`, and after `close`.
+    /// Writing a point un-syncs the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Writer;
+    /// let mut writer = Writer::default();
+    /// assert!(writer.is_header_synced());
+    /// writer.write(Default::default()).unwrap();
+    /// assert!(!writer.is_header_synced());
+    /// writer.checkpoint().unwrap();
+    /// assert!(writer.is_header_synced());
+    /// ```
+    pub fn is_header_synced(&self) -> bool {
+        self.header_synced
+    }
+
     /// Close this writer.
     ///
+    /// Before las 1.4, the header's return counts are 32-bit. If more than `u32::MAX` points share
+    /// a return number, closing returns an error unless `Options::saturate_return_counts` is set.
+    ///
     /// # Examples
     ///
     /// ```
@@ -168,19 +868,37 @@ impl<W: Seek + Write> Writer<W> {
         if self.closed {
             return Err(Error::Closed.into());
         }
-        self.write.write_all(self.header.point_padding())?;
-        for raw_evlr in self.header.evlrs().into_iter().map(|evlr| {
-            evlr.clone().into_raw(true)
-        })
-        {
-            raw_evlr?.write_to(&mut self.write)?;
+        if let Some(number_of_points) = self.presized {
+            if self.header.number_of_points() != number_of_points {
+                return Err(
+                    Error::PresizedCount(number_of_points, self.header.number_of_points()).into(),
+                );
+            }
+        }
+        if self.evlrs_started.is_none() {
+            self.write_point_padding_and_evlrs()?;
+        }
+        if self.presized.is_some() {
+            self.closed = true;
+            self.header_synced = true;
+            return Ok(());
+        }
+        if let Some(extra_bytes_range) = self.extra_bytes_range.take() {
+            self.stamp_extra_bytes_range(extra_bytes_range)?;
+        }
+        if self.options.saturate_return_counts {
+            let warnings = self.header.saturate_number_of_points_by_return();
+            self.warnings.extend(warnings);
         }
         self.write.seek(SeekFrom::Start(self.start))?;
-        self.header.clone().into_raw().and_then(|raw_header| {
-            raw_header.write_to(&mut self.write)
+        self.raw_header_to_write().and_then(|raw_header| {
+            raw_header.write_to(&mut self.write).with_phase(
+                Phase::WritingHeader,
+            )
         })?;
         self.write.seek(SeekFrom::Start(self.start))?;
         self.closed = true;
+        self.header_synced = true;
         Ok(())
     }
 }
@@ -199,7 +917,9 @@ impl<W: Write + Seek + Clone> Writer<W> {
         if !self.closed {
             self.close()?;
         }
-        self.write.seek(SeekFrom::Start(self.start))?;
+        if self.presized.is_none() {
+            self.write.seek(SeekFrom::Start(self.start))?;
+        }
         Ok(self.write.clone())
     }
 }
@@ -226,28 +946,436 @@ impl Default for Writer<Cursor<Vec<u8>>> {
     }
 }
 
-impl<W: Seek + Write> Drop for Writer<W> {
-    fn drop(&mut self) {
-        if !self.closed {
-            self.close().expect("Error when dropping the writer");
-        }
-    }
-}
+/// The offset of the record length in an evlr header, after the reserved bytes, the user id, and
+/// the record id.
+const EVLR_RECORD_LENGTH_OFFSET: u64 = 20;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use Version;
-    use header::Builder;
-    use point::Format;
-    use std::io::Cursor;
+/// The length of an evlr header.
+const EVLR_HEADER_LEN: u64 = 60;
 
-    fn writer(format: Format, version: Version) -> Writer<Cursor<Vec<u8>>> {
-        let mut builder = Builder::default();
-        builder.point_format = format;
-        builder.version = version;
-        Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap()
-    }
+/// Streams the data of one evlr.
+///
+/// Bytes written to this go straight to the underlying `Write`. When this is finished, or
+/// dropped, the evlr's record length is back-patched. If back-patching fails during the drop, a
+/// panic will result, so use `finish` if you want to check for errors.
+///
+/// This struct is created by calling `begin_evlr` on `Writer`.
+#[derive(Debug)]
+pub struct EvlrWriter<'a, W: 'a + Seek + Write> {
+    finished: bool,
+    len: u64,
+    start: u64,
+    writer: &'a mut Writer<W>,
+}
+
+impl<'a, W: Seek + Write> EvlrWriter<'a, W> {
+    /// Finishes this evlr, back-patching its record length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use las::{Builder, Writer};
+    /// let header = Builder::from((1, 4)).into_header().unwrap();
+    /// let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+    /// let mut evlr_writer = writer.begin_evlr("index", 1, "").unwrap();
+    /// evlr_writer.write_all(&[1, 2, 3]).unwrap();
+    /// evlr_writer.finish().unwrap();
+    /// ```
+    pub fn finish(mut self) -> Result<()> {
+        self.back_patch()
+    }
+
+    fn back_patch(&mut self) -> Result<()> {
+        use byteorder::{LittleEndian, WriteBytesExt};
+
+        self.finished = true;
+        let write = &mut self.writer.write;
+        write.seek(SeekFrom::Start(self.start + EVLR_RECORD_LENGTH_OFFSET))?;
+        write.write_u64::<LittleEndian>(self.len)?;
+        write.seek(SeekFrom::Start(self.start + EVLR_HEADER_LEN + self.len))?;
+        Ok(())
+    }
+}
+
+impl<'a, W: Seek + Write> Write for EvlrWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        let n = self.writer.write.write(buf)?;
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        self.writer.write.flush()
+    }
+}
+
+impl<'a, W: Seek + Write> Drop for EvlrWriter<'a, W> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.back_patch().expect("Error when dropping the evlr writer");
+        }
+    }
+}
+
+/// The min and max of each extra bytes descriptor, as they're computed.
+#[derive(Debug)]
+struct ExtraBytesRange {
+    descriptors: Vec<Descriptor>,
+    index: usize,
+    position: u64,
+    ranges: Vec<[Option<(f64, f64)>; 3]>,
+}
+
+impl ExtraBytesRange {
+    fn new(header: &Header, start_of_vlrs: u64) -> Option<ExtraBytesRange> {
+        use extra_bytes::{RECORD_ID, USER_ID};
+
+        let index = header.vlrs().iter().position(|vlr| {
+            vlr.user_id == USER_ID && vlr.record_id == RECORD_ID
+        })?;
+        let descriptors = header.extra_bytes_descriptors().ok()?;
+        let position = header.vlrs()[..index].iter().fold(start_of_vlrs, |position, vlr| {
+            position + vlr.len(false) as u64
+        });
+        Some(ExtraBytesRange {
+            ranges: vec![[None; 3]; descriptors.len()],
+            descriptors: descriptors,
+            index: index,
+            position: position,
+        })
+    }
+
+    fn add(&mut self, extra_bytes: &[u8]) {
+        use extra_bytes::decode_one;
+
+        let mut start = 0;
+        for (descriptor, range) in self.descriptors.iter().zip(&mut self.ranges) {
+            let end = start + descriptor.len();
+            let bytes = match extra_bytes.get(start..end) {
+                Some(bytes) => bytes,
+                None => break,
+            };
+            start = end;
+            let is_undocumented = matches!(descriptor.data_type, DataType::Undocumented(_));
+            if is_undocumented || !(1..=3).contains(&descriptor.dimensions) {
+                continue;
+            }
+            let n = descriptor.data_type.len();
+            for (i, bytes) in bytes.chunks(n).enumerate().take(3) {
+                // The min, max, and no data are stored in the same units as the values, before
+                // any scale and offset.
+                let value = match decode_one(descriptor.data_type, bytes).as_f64() {
+                    Some(value) if !value.is_nan() => value,
+                    _ => continue,
+                };
+                if descriptor.no_data.is_some_and(|no_data| no_data[i] == value) {
+                    continue;
+                }
+                range[i] = Some(match range[i] {
+                    Some((min, max)) => (min.min(value), max.max(value)),
+                    None => (value, value),
+                });
+            }
+        }
+    }
+
+    fn into_descriptors(self) -> Vec<Descriptor> {
+        let mut descriptors = self.descriptors;
+        for (descriptor, range) in descriptors.iter_mut().zip(self.ranges) {
+            if range.iter().all(Option::is_none) {
+                descriptor.min = None;
+                descriptor.max = None;
+            } else {
+                let mut min = [0.; 3];
+                let mut max = [0.; 3];
+                for (i, range) in range.iter().enumerate() {
+                    if let Some((a, b)) = *range {
+                        min[i] = a;
+                        max[i] = b;
+                    }
+                }
+                descriptor.min = Some(min);
+                descriptor.max = Some(max);
+            }
+        }
+        descriptors
+    }
+}
+
+fn write_vlrs<W: Write>(mut write: W, header: &Header) -> Result<()> {
+    for vlr in header.vlrs() {
+        (*vlr).clone().into_raw(false).and_then(|raw_vlr| {
+            raw_vlr.write_to(&mut write).with_phase(Phase::WritingVlr)
+        })?;
+    }
+    if !header.vlr_padding().is_empty() {
+        write.write_all(header.vlr_padding())?;
+    }
+    Ok(())
+}
+
+/// Copies the points that match a predicate from a reader to a new las file, in one pass.
+///
+/// The new las data use the reader's header, except for the point counts and bounds, which are
+/// recomputed from the copied points. Points are streamed, not buffered. Returns the number of
+/// points written.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::{Reader, filter_file};
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut cursor = Cursor::new(Vec::new());
+/// let n = filter_file(&mut reader, &mut cursor, |point| point.return_number == 1).unwrap();
+/// cursor.set_position(0);
+/// assert_eq!(n, Reader::new(cursor).unwrap().header().number_of_points());
+/// ```
+pub fn filter_file<R, W, F>(reader: &mut Reader<R>, write: W, mut predicate: F) -> Result<u64>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+    F: FnMut(&Point) -> bool,
+{
+    let mut writer = Writer::new(write, reader.header().clone())?;
+    for point in reader.points() {
+        let point = point?;
+        if predicate(&point) {
+            writer.write(point)?;
+        }
+    }
+    writer.close()?;
+    Ok(writer.header().number_of_points())
+}
+
+/// Writes a complete las file from points, without any header configuration.
+///
+/// The point format is inferred from the first point, e.g. a point with gps time and color is
+/// written as format 3. The scales and offsets are picked from the coordinate range with
+/// `Transform::best_for_bounds` and a precision of 0.001. Since the range is only known after the
+/// last point, **all points are buffered in memory** before anything is written. If that's too
+/// much memory, configure a header yourself and use a `Writer`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::{Point, Reader, write_points};
+/// let points = vec![
+///     Point { x: 1., y: 2., z: 3., ..Default::default() },
+///     Point { x: 4., y: 5., z: 6., ..Default::default() },
+/// ];
+/// let mut cursor = Cursor::new(Vec::new());
+/// write_points(&mut cursor, points).unwrap();
+/// cursor.set_position(0);
+/// assert_eq!(2, Reader::new(cursor).unwrap().header().number_of_points());
+/// ```
+pub fn write_points<W, I>(write: W, points: I) -> Result<()>
+where
+    W: Write + Seek,
+    I: IntoIterator<Item = Point>,
+{
+    let points: Vec<Point> = points.into_iter().collect();
+    let mut builder = Builder::default();
+    if let Some(point) = points.first() {
+        let is_extended = point.nir.is_some() || point.return_number > 5 ||
+            point.number_of_returns > 5 || point.scanner_channel > 0;
+        builder.point_format = Format {
+            has_gps_time: point.gps_time.is_some(),
+            has_color: point.color.is_some(),
+            is_extended: is_extended,
+            has_waveform: point.waveform.is_some(),
+            has_nir: point.nir.is_some(),
+            extra_bytes: point.extra_bytes.len() as u16,
+            is_compressed: false,
+        };
+        if is_extended {
+            builder.version = Version::new(1, 4);
+        } else if point.waveform.is_some() {
+            builder.version = Version::new(1, 3);
+        }
+        if point.waveform.is_some() {
+            builder.waveform_location = Some(WaveformLocation::External);
+        }
+        let mut bounds = Bounds::default();
+        for point in &points {
+            bounds.grow(point);
+        }
+        builder.transforms = Transform::best_for_bounds(bounds, 0.001)?;
+    }
+    let mut writer = Writer::new(write, builder.into_header()?)?;
+    for point in points {
+        writer.write(point)?;
+    }
+    writer.close()
+}
+
+/// The offset of the bounds in the las header, in bytes.
+///
+/// The header fields before the bounds are the same in every version, from las 1.0 through las
+/// 1.4, so the bounds always start at byte 179 and end at byte 227.
+pub const BOUNDS_OFFSET: u64 = 179;
+
+/// Overwrites just the bounds in already-written las data.
+///
+/// The 48 bytes at `BOUNDS_OFFSET` are overwritten in the header's on-disk order: max x, min x,
+/// max y, min y, max z, min z. Nothing else, not even the point counts, is touched, so this is
+/// only useful when the bounds were computed elsewhere. The las data must start at the beginning
+/// of `write`. Afterwards, `write` is positioned at the end of the bounds.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::{Bounds, Reader, Vector, Writer, patch_bounds};
+/// let mut cursor = Cursor::new(Vec::new());
+/// Writer::new(&mut cursor, Default::default()).unwrap().close().unwrap();
+/// let bounds = Bounds {
+///     min: Vector { x: 1., y: 2., z: 3. },
+///     max: Vector { x: 4., y: 5., z: 6. },
+/// };
+/// patch_bounds(&mut cursor, bounds).unwrap();
+/// cursor.set_position(0);
+/// assert_eq!(bounds, Reader::new(cursor).unwrap().header().bounds());
+/// ```
+pub fn patch_bounds<W: Write + Seek>(mut write: W, bounds: Bounds) -> Result<()> {
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    write.seek(SeekFrom::Start(BOUNDS_OFFSET))?;
+    for &n in &[
+        bounds.max.x,
+        bounds.min.x,
+        bounds.max.y,
+        bounds.min.y,
+        bounds.max.z,
+        bounds.min.z,
+    ]
+    {
+        write.write_f64::<LittleEndian>(n)?;
+    }
+    Ok(())
+}
+
+/// Writes points to in-memory las data and reads them back, returning the header and points that
+/// were read.
+///
+/// Use this to check that a header configuration and its points round-trip, e.g. in tests or to
+/// reproduce an issue. The returned header has the point counts and bounds of the written points.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Point, roundtrip};
+/// let point = Point { x: 1., y: 2., z: 3., ..Default::default() };
+/// let (header, points) = roundtrip(Default::default(), vec![point.clone()]).unwrap();
+/// assert_eq!(1, header.number_of_points());
+/// assert_eq!(vec![point], points);
+/// ```
+pub fn roundtrip(header: Header, points: Vec<Point>) -> Result<(Header, Vec<Point>)> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), header)?;
+    writer.write_chunk(&points)?;
+    let mut reader = Reader::new(writer.into_inner()?)?;
+    let points = reader.points().collect::<Result<Vec<_>>>()?;
+    Ok((reader.header().clone(), points))
+}
+
+/// Checks that points could be written with a header, without writing anything.
+///
+/// Every point goes through the same checks as `Writer::write`, e.g. that it matches the header's
+/// point format and that its coordinates fit in the header's transforms, and then the header is
+/// checked as it would be when the writer is closed. Returns the first error. Nothing is
+/// buffered, so this works on arbitrarily long streams of points.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Header, Point, validate_points};
+/// let points = vec![Point::default(); 2];
+/// assert!(validate_points(Header::default(), points).is_ok());
+/// let points = vec![Point { gps_time: Some(1.), ..Default::default() }];
+/// assert!(validate_points(Header::default(), points).is_err());
+/// ```
+pub fn validate_points<I>(header: Header, points: I) -> Result<()>
+where
+    I: IntoIterator<Item = Point>,
+{
+    let mut writer = Writer::new(DryRun::default(), header)?;
+    for point in points {
+        writer.write(point)?;
+    }
+    writer.close()
+}
+
+/// A `Write + Seek` that throws its bytes away, but keeps track of where it is.
+#[derive(Debug, Default)]
+struct DryRun {
+    position: u64,
+    len: u64,
+}
+
+impl Write for DryRun {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        self.position += buf.len() as u64;
+        self.len = self.len.max(self.position);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for DryRun {
+    fn seek(&mut self, pos: SeekFrom) -> ::std::io::Result<u64> {
+        use std::io::{Error, ErrorKind};
+
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => offset(self.len, n),
+            SeekFrom::Current(n) => offset(self.position, n),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(Error::new(ErrorKind::InvalidInput, "seek to a negative position")),
+        }
+    }
+}
+
+fn offset(position: u64, n: i64) -> Option<u64> {
+    if n < 0 {
+        position.checked_sub(n.wrapping_neg() as u64)
+    } else {
+        position.checked_add(n as u64)
+    }
+}
+
+impl<W: Seek + Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.close().expect("Error when dropping the writer");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Format;
+    use std::io::Cursor;
+
+    fn writer(format: Format, version: Version) -> Writer<Cursor<Vec<u8>>> {
+        let mut builder = Builder::default();
+        builder.point_format = format;
+        builder.version = version;
+        if format.has_waveform {
+            builder.waveform_location = Some(WaveformLocation::External);
+        }
+        Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap()
+    }
 
     #[test]
     fn already_closed() {
@@ -293,6 +1421,192 @@ mod tests {
         assert!(writer.write(point).is_err());
     }
 
+    #[test]
+    fn missing_nir_format_10() {
+        let format = Format::new(10).unwrap();
+        let mut writer = writer(format, Version::new(1, 4));
+        let point = Point {
+            gps_time: Some(0.),
+            color: Some(Default::default()),
+            waveform: Some(Default::default()),
+            ..Default::default()
+        };
+        assert!(writer.write(point).is_err());
+    }
+
+    #[test]
+    fn legacy_classification_too_large() {
+        use point::Classification;
+
+        let mut writer = writer(Format::new(0).unwrap(), Version::new(1, 2));
+        let point = Point {
+            classification: Classification::Reserved(32),
+            is_withheld: true,
+            ..Default::default()
+        };
+        assert!(writer.write(point).is_err());
+        assert_eq!(0, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn scan_angle_clamped() {
+        let mut writer = writer(Format::new(0).unwrap(), Version::new(1, 2));
+        writer
+            .write(Point {
+                scan_angle: 200.,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            vec![
+                Warning::ValueClamped {
+                    index: 0,
+                    field: "scan angle",
+                    value: 200.,
+                    clamped: 90.,
+                },
+            ],
+            writer.warnings()
+        );
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(90., reader.read().unwrap().unwrap().scan_angle);
+    }
+
+    #[test]
+    fn scan_angle_strict() {
+        let header = Header::from((1, 4));
+        let options = Options {
+            strict_value_ranges: true,
+            ..Default::default()
+        };
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+        let point = Point {
+            scan_angle: -90.5,
+            ..Default::default()
+        };
+        assert!(writer.write(point).is_err());
+        assert_eq!(0, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn reserve_points() {
+        use Vlr;
+
+        for &version in &[(1, 2), (1, 4)] {
+            let mut builder = Builder::from(version);
+            if version == (1, 4) {
+                builder.evlrs.push(Vlr::default());
+            }
+            let options = Options {
+                reserve_points: Some(10),
+                ..Default::default()
+            };
+            let header = builder.into_header().unwrap();
+            let mut writer = Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+            for _ in 0..3 {
+                writer.write(Default::default()).unwrap();
+            }
+            let cursor = writer.into_inner().unwrap();
+            let bytes = cursor.get_ref().clone();
+            let mut reader = Reader::new(cursor).unwrap();
+            let offset = reader.header().clone().into_raw().unwrap().offset_to_point_data as usize;
+            let evlrs_len = reader.header().evlrs().iter().map(|evlr| evlr.len(true)).sum::<usize>();
+            assert_eq!(offset + 10 * 20 + evlrs_len, bytes.len());
+            assert_eq!(0b1000_0000, bytes[offset + 3 * 20 + 15]);
+            assert_eq!(3, reader.header().number_of_points());
+            assert_eq!(3, reader.points().count());
+            assert_eq!(version == (1, 4), !reader.header().evlrs().is_empty());
+            if version == (1, 4) {
+                assert_eq!(7 * 20, reader.header().point_padding().len());
+            }
+        }
+    }
+
+    #[test]
+    fn reserve_points_exceeded() {
+        let options = Options {
+            reserve_points: Some(1),
+            ..Default::default()
+        };
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), Default::default(), options)
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        assert!(writer.write(Default::default()).is_err());
+    }
+
+    #[test]
+    fn copy_raw_from() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let header = reader.header().clone();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+        for point in reader.points() {
+            writer.write(point.unwrap()).unwrap();
+        }
+        let expected = writer.into_inner().unwrap().into_inner();
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+        reader.read().unwrap();
+        assert_eq!(106, writer.copy_raw_from(&mut reader).unwrap());
+        assert_eq!(
+            header.number_of_points_by_return(1).unwrap(),
+            writer.points_by_return()[0]
+        );
+        assert_eq!(expected, writer.into_inner().unwrap().into_inner());
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.copy_raw_from(&mut reader).unwrap();
+        writer.copy_raw_from(&mut reader).unwrap();
+        let mut other = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(212, other.header().number_of_points());
+        assert_eq!(212, other.points().count());
+    }
+
+    #[test]
+    fn copy_raw_from_truncated() {
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        let header = writer.header().clone();
+        let mut bytes = writer.into_inner().unwrap().into_inner();
+        let len = bytes.len();
+        bytes.truncate(len - 1);
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        assert!(writer.copy_raw_from(&mut reader).is_err());
+        assert_eq!(0, writer.header().number_of_points());
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(0, reader.header().number_of_points());
+    }
+
+    #[test]
+    fn copy_raw_from_mismatch() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut writer = Writer::default();
+        match writer.copy_raw_from(&mut reader) {
+            Err(::Error::Writer(Error::CopyRawMismatch)) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn points_by_return() {
+        let mut writer = Writer::default();
+        for &return_number in &[0, 1, 1, 3, 5] {
+            writer
+                .write(Point {
+                    return_number: return_number,
+                    number_of_returns: 5,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            [2, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            writer.points_by_return()
+        );
+        assert_eq!(Some(2), writer.header().number_of_points_by_return(1));
+    }
+
     #[test]
     fn missing_waveform() {
         let format = Format::new(4).unwrap();
@@ -300,6 +1614,490 @@ mod tests {
         assert!(writer.write(Default::default()).is_err());
     }
 
+    #[test]
+    fn reject_duplicate_vlrs() {
+        use Vlr;
+
+        let mut builder = Builder::default();
+        builder.vlrs.push(Vlr::default());
+        builder.vlrs.push(Vlr::default());
+        let header = builder.into_header().unwrap();
+        assert!(Writer::new(Cursor::new(Vec::new()), header.clone()).is_ok());
+        let options = Options {
+            reject_duplicate_vlrs: true,
+            ..Default::default()
+        };
+        assert!(Writer::with_options(Cursor::new(Vec::new()), header, options).is_err());
+    }
+
+    #[test]
+    fn write_points() {
+        use {Color, Reader};
+
+        let points = vec![
+            Point {
+                x: 500000.5,
+                gps_time: Some(1.),
+                color: Some(Color::new(1, 2, 3)),
+                ..Default::default()
+            },
+            Point {
+                x: 10500000.5,
+                gps_time: Some(2.),
+                color: Some(Color::new(4, 5, 6)),
+                ..Default::default()
+            },
+        ];
+        let mut cursor = Cursor::new(Vec::new());
+        super::write_points(&mut cursor, points.clone()).unwrap();
+        cursor.set_position(0);
+        let mut reader = Reader::new(cursor).unwrap();
+        assert_eq!(3, reader.header().point_format().to_u8().unwrap());
+        assert_eq!(500000., reader.header().transforms().x.offset);
+        assert_eq!(0.01, reader.header().transforms().x.scale);
+        assert_eq!(0.001, reader.header().transforms().y.scale);
+        let read = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(points, read);
+    }
+
+    #[test]
+    fn filter_file() {
+        use Reader;
+
+        let mut writer = Writer::default();
+        for i in 0..10 {
+            writer
+                .write(Point {
+                    x: f64::from(i),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let mut cursor = Cursor::new(Vec::new());
+        assert_eq!(
+            5,
+            super::filter_file(&mut reader, &mut cursor, |p| p.x >= 5.).unwrap()
+        );
+        cursor.set_position(0);
+        let mut reader = Reader::new(cursor).unwrap();
+        assert_eq!(5, reader.header().number_of_points());
+        assert_eq!(5., reader.header().bounds().min.x);
+        assert_eq!(9., reader.header().bounds().max.x);
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(5, points.len());
+        assert!(points.iter().all(|p| p.x >= 5.));
+    }
+
+    #[derive(Debug)]
+    struct AppendOnly(Vec<u8>);
+
+    impl Write for AppendOnly {
+        fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> ::std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for AppendOnly {
+        fn seek(&mut self, _: SeekFrom) -> ::std::io::Result<u64> {
+            Err(::std::io::Error::new(
+                ::std::io::ErrorKind::Other,
+                "append only",
+            ))
+        }
+    }
+
+    #[test]
+    fn new_presized_does_not_seek() {
+        use {Reader, Vlr};
+
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr::default());
+        let header = builder.into_header().unwrap();
+        let points = vec![
+            Point {
+                x: 1.,
+                ..Default::default()
+            },
+            Point {
+                x: 2.,
+                ..Default::default()
+            },
+        ];
+        let mut bounds = Bounds::default();
+        for point in &points {
+            bounds.grow(point);
+        }
+        let mut writer = Writer::new_presized(AppendOnly(Vec::new()), header, bounds, 2).unwrap();
+        for point in points.clone() {
+            writer.write(point).unwrap();
+        }
+        writer.checkpoint().unwrap();
+        writer.close().unwrap();
+        let bytes = writer.write.0.clone();
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(2, reader.header().number_of_points());
+        assert_eq!(bounds, reader.header().bounds());
+        assert_eq!(1, reader.header().evlrs().len());
+        assert_eq!(points, reader.points().collect::<Result<Vec<_>>>().unwrap());
+    }
+
+    #[test]
+    fn new_presized_count_mismatch() {
+        let mut writer = Writer::new_presized(
+            Cursor::new(Vec::new()),
+            Default::default(),
+            Bounds::default(),
+            1,
+        ).unwrap();
+        assert!(writer.close().is_err());
+        writer.write(Default::default()).unwrap();
+        assert!(writer.write(Default::default()).is_err());
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn checkpoint() {
+        use Reader;
+
+        let mut writer = Writer::default();
+        writer.write(Default::default()).unwrap();
+        writer.checkpoint().unwrap();
+        let cursor = Cursor::new(writer.write.get_ref().clone());
+        assert_eq!(1, Reader::new(cursor).unwrap().header().number_of_points());
+        writer.write(Default::default()).unwrap();
+        assert!(!writer.is_header_synced());
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(2, reader.header().number_of_points());
+        assert_eq!(2, reader.points().count());
+    }
+
+    #[test]
+    fn checkpoint_without_evlrs() {
+        use Vlr;
+
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr::default());
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        writer.checkpoint().unwrap();
+        let cursor = Cursor::new(writer.write.get_ref().clone());
+        let reader = Reader::new(cursor).unwrap();
+        assert_eq!(1, reader.header().number_of_points());
+        assert!(reader.header().evlrs().is_empty());
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(1, reader.header().evlrs().len());
+    }
+
+    #[test]
+    fn start_of_waveform_data_packet_record() {
+        use {Reader, Vlr};
+        use std::io::{Read, Seek};
+
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format::new(4).unwrap();
+        builder.waveform_location = Some(WaveformLocation::Internal);
+        builder.evlrs.push(Vlr {
+            user_id: "LASF_Spec".to_string(),
+            record_id: 65535,
+            data: vec![42; 8],
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer
+            .write(Point {
+                gps_time: Some(0.),
+                waveform: Some(Default::default()),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        let raw_header = ::raw::Header::read_from(&mut cursor).unwrap();
+        let start = raw_header.start_of_waveform_data_packet_record.unwrap();
+        assert_eq!(raw_header.evlr.unwrap().start_of_first_evlr, start);
+        cursor.seek(SeekFrom::Start(start + 60)).unwrap();
+        let mut data = Vec::new();
+        cursor.read_to_end(&mut data).unwrap();
+        assert_eq!(vec![42; 8], data);
+
+        cursor.set_position(0);
+        let reader = Reader::new(cursor).unwrap();
+        assert_eq!(
+            Some(start),
+            reader.header().start_of_waveform_data_packet_record().unwrap()
+        );
+    }
+
+    #[test]
+    fn with_vlr_region() {
+        use {Reader, Vlr};
+
+        let mut builder = Builder::default();
+        builder.vlrs.push(Vlr {
+            data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        let mut cursor = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap()
+            .into_inner()
+            .unwrap();
+        // Set the reserved field, which would be lost by re-serializing.
+        cursor.get_mut()[227] = 42;
+        let original = cursor.get_ref().clone();
+
+        let mut reader = Reader::new(cursor).unwrap();
+        let vlr_region = reader.vlr_region().unwrap();
+        assert_eq!(original[227..], vlr_region[..]);
+        let header = reader.header().clone();
+        let writer = Writer::with_vlr_region(Cursor::new(Vec::new()), header.clone(), vlr_region)
+            .unwrap();
+        assert_eq!(original, writer.into_inner().unwrap().into_inner());
+
+        assert!(Writer::with_vlr_region(Cursor::new(Vec::new()), header, vec![0; 3]).is_err());
+    }
+
+    #[test]
+    fn non_finite_coordinate() {
+        let mut writer = Writer::default();
+        match writer.write(Point {
+            x: ::std::f64::NAN,
+            ..Default::default()
+        }) {
+            Err(::Error::NonFiniteCoordinate('x', _)) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(
+            writer
+                .write(Point {
+                    z: ::std::f64::INFINITY,
+                    ..Default::default()
+                })
+                .is_err()
+        );
+        assert_eq!(0, writer.header().number_of_points());
+    }
+
+    #[test]
+    fn new_keep_header() {
+        use Vector;
+
+        let mut header = Header::default();
+        let bounds = Bounds {
+            min: Vector {
+                x: -10.,
+                y: -10.,
+                z: -10.,
+            },
+            max: Vector {
+                x: 10.,
+                y: 10.,
+                z: 10.,
+            },
+        };
+        header.set_number_of_points_and_bounds(2, bounds);
+        let mut writer = Writer::new_keep_header(Cursor::new(Vec::new()), header).unwrap();
+        writer.write(Default::default()).unwrap();
+        writer.checkpoint().unwrap();
+        writer
+            .write(Point {
+                x: 1.,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(1., writer.header().bounds().max.x);
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(2, reader.header().number_of_points());
+        assert_eq!(bounds, reader.header().bounds());
+        assert_eq!(2, reader.points().count());
+    }
+
+    #[test]
+    fn write_chunk_then_cancel() {
+        let points = (0..10)
+            .map(|i| {
+                Point {
+                    x: f64::from(i),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut writer = Writer::default();
+        for (i, chunk) in points.chunks(3).enumerate() {
+            if i == 2 {
+                break;
+            }
+            writer.write_chunk(chunk).unwrap();
+        }
+        writer.close().unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let points_read = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(&points[..6], &points_read[..]);
+    }
+
+    #[test]
+    fn compute_extra_bytes_min_max() {
+        use Vlr;
+        use extra_bytes::DataType;
+
+        let mut descriptor = Descriptor::new("amplitude", DataType::U16);
+        descriptor.no_data = Some([0., 0., 0.]);
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 2;
+        builder.vlrs.push(Vlr::default());
+        builder.vlrs.push(Descriptor::into_vlr(&[descriptor]).unwrap());
+        let header = builder.into_header().unwrap();
+        let options = Options {
+            compute_extra_bytes_min_max: true,
+            ..Default::default()
+        };
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+        for &amplitude in &[5u8, 2, 0, 9] {
+            writer
+                .write(Point {
+                    extra_bytes: vec![amplitude, 0],
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.close().unwrap();
+        let descriptors = writer.header().extra_bytes_descriptors().unwrap();
+        assert_eq!(Some([2., 0., 0.]), descriptors[0].min);
+        assert_eq!(Some([9., 0., 0.]), descriptors[0].max);
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            descriptors,
+            reader.header().extra_bytes_descriptors().unwrap()
+        );
+        assert_eq!(4, reader.header().number_of_points());
+    }
+
+    #[test]
+    fn compute_extra_bytes_min_max_scaled() {
+        use Vlr;
+        use byteorder::{LittleEndian, WriteBytesExt};
+        use extra_bytes::DataType;
+
+        let mut descriptor = Descriptor::new("height", DataType::I16);
+        descriptor.no_data = Some([-1., 0., 0.]);
+        descriptor.scale = Some([0.01, 0., 0.]);
+        descriptor.offset = Some([10., 0., 0.]);
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.extra_bytes = 2;
+        builder.vlrs.push(Descriptor::into_vlr(&[descriptor]).unwrap());
+        let header = builder.into_header().unwrap();
+        let options = Options {
+            compute_extra_bytes_min_max: true,
+            ..Default::default()
+        };
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), header, options).unwrap();
+        for &height in &[123i16, -1, 456] {
+            let mut extra_bytes = Vec::new();
+            extra_bytes.write_i16::<LittleEndian>(height).unwrap();
+            writer
+                .write(Point {
+                    extra_bytes: extra_bytes,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.close().unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let descriptors = reader.header().extra_bytes_descriptors().unwrap();
+        assert_eq!(Some([123., 0., 0.]), descriptors[0].min);
+        assert_eq!(Some([456., 0., 0.]), descriptors[0].max);
+    }
+
+    #[test]
+    fn begin_evlr() {
+        use Vlr;
+
+        let mut builder = Builder::from((1, 4));
+        builder.evlrs.push(Vlr {
+            data: vec![42],
+            ..Default::default()
+        });
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        writer.write(Default::default()).unwrap();
+        {
+            let mut evlr_writer = writer.begin_evlr("stream", 1, "streamed").unwrap();
+            evlr_writer.write_all(&[1, 2]).unwrap();
+            evlr_writer.write_all(&[3]).unwrap();
+            evlr_writer.finish().unwrap();
+        }
+        {
+            let mut evlr_writer = writer.begin_evlr("stream", 2, "dropped").unwrap();
+            evlr_writer.write_all(&[4; 70_000]).unwrap();
+        }
+        assert!(writer.write(Default::default()).is_err());
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(2, reader.header().number_of_points());
+        let evlrs = reader.header().evlrs();
+        assert_eq!(3, evlrs.len());
+        assert_eq!(vec![42], evlrs[0].data);
+        assert_eq!("stream", evlrs[1].user_id);
+        assert_eq!(1, evlrs[1].record_id);
+        assert_eq!("streamed", evlrs[1].description);
+        assert_eq!(vec![1, 2, 3], evlrs[1].data);
+        assert_eq!(2, evlrs[2].record_id);
+        assert_eq!(vec![4; 70_000], evlrs[2].data);
+    }
+
+    #[test]
+    fn begin_evlr_requires_evlrs() {
+        let mut writer = Writer::default();
+        assert!(writer.begin_evlr("stream", 1, "").is_err());
+    }
+
+    #[test]
+    fn validate_points_mismatch() {
+        let points = vec![
+            Point::default(),
+            Point {
+                color: Some(Default::default()),
+                ..Default::default()
+            },
+        ];
+        match validate_points(Header::default(), points) {
+            Err(::Error::Writer(Error::PointAttributes(format, point))) => {
+                assert_eq!(Format::new(0).unwrap(), format);
+                assert!(point.color.is_some());
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let point = Point {
+            x: 1e20,
+            ..Default::default()
+        };
+        assert!(validate_points(Header::default(), vec![point]).is_err());
+    }
+
+    #[test]
+    fn write_ref_matches_write() {
+        let point = Point {
+            x: 1.,
+            scan_angle: 100.,
+            ..Default::default()
+        };
+        let mut by_value = Writer::default();
+        by_value.write(point.clone()).unwrap();
+        let mut by_ref = Writer::default();
+        by_ref.write_ref(&point).unwrap();
+        assert_eq!(100., point.scan_angle);
+        assert_eq!(by_value.warnings(), by_ref.warnings());
+        assert_eq!(
+            by_value.into_inner().unwrap().into_inner(),
+            by_ref.into_inner().unwrap().into_inner()
+        );
+    }
+
     #[test]
     fn write_not_at_start() {
         use Reader;
@@ -313,4 +2111,73 @@ mod tests {
         let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
         assert_eq!(point, reader.read().unwrap().unwrap());
     }
+
+    #[test]
+    fn patch_bounds_only_changes_bounds() {
+        use Vector;
+
+        let mut writer = writer(Format::new(0).unwrap(), Version::new(1, 4));
+        writer
+            .write(Point {
+                x: 1.,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        let original = cursor.get_ref().clone();
+        let bounds = Bounds {
+            min: Vector {
+                x: -1.,
+                y: -2.,
+                z: -3.,
+            },
+            max: Vector {
+                x: 1.,
+                y: 2.,
+                z: 3.,
+            },
+        };
+        patch_bounds(&mut cursor, bounds).unwrap();
+        let patched = cursor.get_ref().clone();
+        assert_eq!(original.len(), patched.len());
+        assert_eq!(original[..179], patched[..179]);
+        assert_eq!(original[227..], patched[227..]);
+        assert_ne!(original[179..227], patched[179..227]);
+        cursor.set_position(0);
+        assert_eq!(bounds, Reader::new(cursor).unwrap().header().bounds());
+    }
+
+    #[test]
+    fn zero_length_vlrs() {
+        use Vlr;
+        use byteorder::{ByteOrder, LittleEndian};
+
+        let flag = Vlr {
+            user_id: "flag".to_string(),
+            record_id: 1,
+            ..Default::default()
+        };
+        let data = Vlr {
+            user_id: "data".to_string(),
+            record_id: 2,
+            data: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs = vec![flag.clone(), data.clone()];
+        builder.evlrs = vec![flag.clone()];
+        let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap())
+            .unwrap();
+        writer.write(Default::default()).unwrap();
+        let cursor = writer.into_inner().unwrap();
+        let header_size = LittleEndian::read_u16(&cursor.get_ref()[94..96]) as usize;
+        assert_eq!(
+            0,
+            LittleEndian::read_u16(&cursor.get_ref()[header_size + 20..header_size + 22])
+        );
+        let mut reader = Reader::new(cursor).unwrap();
+        assert_eq!(&vec![flag.clone(), data], reader.header().vlrs());
+        assert_eq!(&vec![flag], reader.header().evlrs());
+        assert_eq!(Point::default(), reader.read().unwrap().unwrap());
+    }
 }
@@ -28,3 +28,29 @@ autzen!(las_1_1, 1, 1);
 autzen!(las_1_2, 1, 2);
 autzen!(las_1_3, 1, 3);
 autzen!(las_1_4, 1, 4);
+
+#[test]
+fn count_by_classification() {
+    let mut reader = las::Reader::from_path("tests/data/autzen.las").unwrap();
+    let counts = reader.count_by_classification().unwrap();
+    assert_eq!(82, counts[1]);
+    assert_eq!(24, counts[2]);
+    assert_eq!(106, counts.iter().sum::<u64>());
+}
+
+#[test]
+fn geo_keys_roundtrip() {
+    use las::{Builder, Reader, Writer};
+    use std::io::Cursor;
+
+    let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+    assert_eq!(Some(1), geo_keys.model_type());
+    assert_eq!(Some(2994), geo_keys.projected_cs_type());
+
+    let mut builder = Builder::from((1, 2));
+    builder.vlrs = geo_keys.to_vlrs();
+    let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(geo_keys, reader.header().geo_keys().unwrap().unwrap());
+}
@@ -14,13 +14,17 @@ pub fn roundtrip(builder: Builder, point: &Point, should_succeed: bool) {
         assert!(builder.into_header().is_err());
         return;
     };
-    let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
     writer.write(point.clone()).unwrap();
-    let header = writer.header().clone();
+    let written = writer.header().clone();
     let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
     assert_eq!(*point, reader.read().unwrap().unwrap());
     assert_eq!(None, reader.read().unwrap());
-    assert_eq!(header, *reader.header());
+    assert_eq!(written, *reader.header());
+
+    let (read, points) = las::roundtrip(header, vec![point.clone()]).unwrap();
+    assert_eq!(written, read);
+    assert_eq!(vec![point.clone()], points);
 }
 
 macro_rules! roundtrip_point {
@@ -31,6 +35,9 @@ macro_rules! roundtrip_point {
         roundtrip_point!($name, $modify_point, $min_version_minor, |_f| {});
     };
     ($name:ident, $modify_point:expr, $min_version_minor:expr, $modify_point_format:expr) => {
+        roundtrip_point!($name, $modify_point, $min_version_minor, $modify_point_format, |_b| {});
+    };
+    ($name:ident, $modify_point:expr, $min_version_minor:expr, $modify_point_format:expr, $modify_builder:expr) => {
         #[test]
         fn $name() {
             use las::{Builder, Point, Version};
@@ -44,6 +51,7 @@ macro_rules! roundtrip_point {
             $modify_point(&mut point);
             let mut builder = Builder::from(version);
             builder.point_format = point_format;
+            $modify_builder(&mut builder);
             ::roundtrip(builder, &point, should_succeed);
         }
     };
@@ -78,17 +86,33 @@ mod $name {
     mod point {
         use las::Color;
         use las::point::{Classification, ScanDirection};
+        use las::raw::point::Waveform;
+        use las::waveform::WaveformLocation;
 
         roundtrip_point!(xyz, |p: &mut Point| { p.x = 1.; p.y = 2.; p.z = 3.; });
         roundtrip_point!(intensity, |p: &mut Point| p.intensity = 42);
         roundtrip_point!(return_number, |p: &mut Point| p.return_number = 2);
         roundtrip_point!(number_of_returns, |p: &mut Point| p.number_of_returns = 2);
         roundtrip_point!(scan_direction, |p: &mut Point| p.scan_direction = ScanDirection::LeftToRight);
+        roundtrip_point!(scan_direction_extended, |p: &mut Point| {
+            p.scan_direction = ScanDirection::LeftToRight;
+            p.gps_time = Some(42.);
+        }, 4, |f: &mut Format| f.extend());
         roundtrip_point!(is_edge_of_flight_line, |p: &mut Point| p.is_edge_of_flight_line = true);
+        roundtrip_point!(is_edge_of_flight_line_extended, |p: &mut Point| {
+            p.is_edge_of_flight_line = true;
+            p.gps_time = Some(42.);
+        }, 4, |f: &mut Format| f.extend());
         roundtrip_point!(classification, |p: &mut Point| p.classification = Classification::Ground);
         roundtrip_point!(is_synthetic, |p: &mut Point| p.is_synthetic = true);
         roundtrip_point!(is_key_point, |p: &mut Point| p.is_key_point = true);
         roundtrip_point!(is_withheld, |p: &mut Point| p.is_withheld = true);
+        roundtrip_point!(classification_with_flags, |p: &mut Point| {
+            p.classification = Classification::Reserved(31);
+            p.is_synthetic = true;
+            p.is_key_point = true;
+            p.is_withheld = true;
+        });
         roundtrip_point!(is_overlap, |p: &mut Point| {
             p.classification = Classification::Unclassified;
             p.is_overlap = true;
@@ -102,7 +126,20 @@ mod $name {
         roundtrip_point!(point_source_id, |p: &mut Point| p.point_source_id = 42);
         roundtrip_point!(gps_time, |p: &mut Point| p.gps_time = Some(42.), 0, |f: &mut Format| f.has_gps_time = true);
         roundtrip_point!(color, |p: &mut Point| p.color = Some(Color { red: 1, green: 2, blue: 3 }), 2, |f: &mut Format| f.has_color = true);
-        // TODO waveform
+        roundtrip_point!(waveform_internal, |p: &mut Point| {
+            p.gps_time = Some(42.);
+            p.waveform = Some(Waveform { byte_offset_to_waveform_data: 60, ..Default::default() });
+        }, 3, |f: &mut Format| {
+            f.has_gps_time = true;
+            f.has_waveform = true;
+        }, |b: &mut Builder| b.waveform_location = Some(WaveformLocation::Internal));
+        roundtrip_point!(waveform_external, |p: &mut Point| {
+            p.gps_time = Some(42.);
+            p.waveform = Some(Waveform { byte_offset_to_waveform_data: 60, ..Default::default() });
+        }, 3, |f: &mut Format| {
+            f.has_gps_time = true;
+            f.has_waveform = true;
+        }, |b: &mut Builder| b.waveform_location = Some(WaveformLocation::External));
         roundtrip_point!(nir, |p: &mut Point| {
             p.color = Some(Color { red: 1, green: 2, blue: 3});
             p.nir = Some(42);
@@ -112,6 +149,26 @@ mod $name {
             f.has_color = true;
             f.has_nir = true;
         });
+        roundtrip_point!(nir_zero, |p: &mut Point| {
+            p.color = Some(Color { red: 1, green: 2, blue: 3});
+            p.nir = Some(0);
+            p.gps_time = Some(42.);
+        }, 4, |f: &mut Format| {
+            f.extend();
+            f.has_color = true;
+            f.has_nir = true;
+        });
+        roundtrip_point!(nir_with_waveform, |p: &mut Point| {
+            p.color = Some(Color { red: 1, green: 2, blue: 3});
+            p.nir = Some(42);
+            p.gps_time = Some(42.);
+            p.waveform = Some(Default::default());
+        }, 4, |f: &mut Format| {
+            f.extend();
+            f.has_color = true;
+            f.has_nir = true;
+            f.has_waveform = true;
+        }, |b: &mut Builder| b.waveform_location = Some(WaveformLocation::External));
         roundtrip_point!(extra_bytes, |p: &mut Point| p.extra_bytes = vec![42], 0, |f: &mut Format| f.extra_bytes = 1);
     }
 
@@ -124,6 +181,10 @@ mod $name {
         roundtrip_builder!(gps_time_type, |b: &mut Builder| b.gps_time_type = GpsTimeType::Standard, 2);
         roundtrip_builder!(has_synthetic_return_numbers, |b: &mut Builder| b.has_synthetic_return_numbers = true, 3);
         roundtrip_builder!(guid, |b: &mut Builder| b.guid = Uuid::from_bytes(&[42; 16]).unwrap());
+        roundtrip_builder!(guid_bytes, |b: &mut Builder| {
+            let bytes = (0..16).map(|n| n * 17).collect::<Vec<u8>>();
+            b.guid = Uuid::from_bytes(&bytes).unwrap();
+        });
         roundtrip_builder!(system_identifier, |b: &mut Builder| b.system_identifier = "roundtrip test".to_string());
         roundtrip_builder!(generating_software, |b: &mut Builder| b.generating_software = "roundtrip test".to_string());
         roundtrip_builder!(date, |b: &mut Builder| b.date = Some(Utc.ymd(2017, 10, 30)));